@@ -0,0 +1,390 @@
+//! Runs a draw without a browser, for scripts and cron jobs.
+//!
+//! Reads a list from JSON (a saved list or an exported event), TOML or CSV,
+//! draws with the same engine as the app, and prints the results or writes
+//! one file per giver.
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use secret_satan_engine::entropy::{Contribution, Seed};
+use secret_satan_engine::reveal::reveals;
+use secret_satan_engine::transfer::import_event;
+use secret_satan_engine::{csv, Assignment, DrawMode, SecretSatan};
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+const USAGE: &str = "Usage: secret-satan-cli [OPTIONS] <INPUT>
+
+Draws a gift giving list from INPUT, a .json, .toml or .csv file, or - for stdin.
+
+Options:
+  --input-format <json|toml|csv>  Format of INPUT, if the extension doesn't say
+  --seed <NUMBER>                 Draw the same list every time for this seed
+  --contribution <NAME=VALUE>     Seed the draw from everyone's contributions instead,
+                                  as the app does; repeat once per person
+  --mode <one-to-one|group:MIN-MAX>
+                                  Override the list's draw mode
+  --format <text|json|csv>        How to write the results [default: text]
+  --out-dir <DIR>                 Write one file per giver instead of to stdout
+  -h, --help                      Show this message
+
+Exit codes: 0 drawn, 1 no draw is possible, 2 bad arguments or input, 3 couldn't write output";
+
+/// Why the CLI stopped, and the exit code that goes with it.
+#[derive(Debug, PartialEq)]
+enum Failure {
+    Usage(String),
+    Input(String),
+    Infeasible(String),
+    Output(String),
+}
+
+impl Failure {
+    fn code(&self) -> u8 {
+        match self {
+            Failure::Infeasible(_) => 1,
+            Failure::Usage(_) | Failure::Input(_) => 2,
+            Failure::Output(_) => 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum InputFormat {
+    Json,
+    Toml,
+    Csv,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+    Csv,
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct Options {
+    input: Option<String>,
+    input_format: Option<InputFormat>,
+    seed: Option<u64>,
+    contributions: Vec<Contribution>,
+    mode: Option<DrawMode>,
+    format: OutputFormat,
+    out_dir: Option<PathBuf>,
+    help: bool,
+}
+
+fn main() -> ExitCode {
+    match run(std::env::args().skip(1).collect()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(failure) => {
+            match &failure {
+                Failure::Usage(message) => eprintln!("error: {message}\n\n{USAGE}"),
+                Failure::Input(message) | Failure::Infeasible(message) | Failure::Output(message) => {
+                    eprintln!("error: {message}")
+                }
+            }
+            ExitCode::from(failure.code())
+        }
+    }
+}
+
+fn run(args: Vec<String>) -> Result<(), Failure> {
+    let options = parse_args(args)?;
+    if options.help {
+        println!("{USAGE}");
+        return Ok(());
+    }
+    let input = options.input.clone().ok_or_else(|| Failure::Usage("no input file given".to_string()))?;
+
+    let raw = if input == "-" {
+        let mut raw = String::new();
+        std::io::stdin()
+            .read_to_string(&mut raw)
+            .map_err(|error| Failure::Input(format!("couldn't read stdin: {error}")))?;
+        raw
+    } else {
+        std::fs::read_to_string(&input).map_err(|error| Failure::Input(format!("couldn't read {input}: {error}")))?
+    };
+    let format = match options.input_format {
+        Some(format) => format,
+        None => format_of(&input).ok_or_else(|| Failure::Usage(format!("can't tell what format {input} is; use --input-format")))?,
+    };
+
+    let (event, mut list) = read_list(&raw, format)?;
+    if let Some(mode) = options.mode {
+        list.mode = mode;
+    }
+    let results = match options.seed {
+        Some(seed) => list.draw_with_rng(&mut StdRng::seed_from_u64(seed)),
+        None if !options.contributions.is_empty() => {
+            let seed = Seed::derive(options.contributions.clone());
+            eprintln!("{}\n", seed.derivation());
+            list.draw_with_rng(&mut seed.rng())
+        }
+        None => list.draw(),
+    }
+    .map_err(|error| Failure::Infeasible(error.to_string()))?;
+
+    match &options.out_dir {
+        Some(dir) => write_per_person(dir, &event, &results, options.format),
+        None => {
+            print!("{}", render(&results, options.format));
+            Ok(())
+        }
+    }
+}
+
+fn parse_args(args: Vec<String>) -> Result<Options, Failure> {
+    let mut options = Options::default();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = |flag: &str| args.next().ok_or_else(|| Failure::Usage(format!("{flag} needs a value")));
+        match arg.as_str() {
+            "-h" | "--help" => options.help = true,
+            "--input-format" => {
+                options.input_format = Some(match value(&arg)?.as_str() {
+                    "json" => InputFormat::Json,
+                    "toml" => InputFormat::Toml,
+                    "csv" => InputFormat::Csv,
+                    other => return Err(Failure::Usage(format!("unknown input format {other}"))),
+                })
+            }
+            "--seed" => {
+                let seed = value(&arg)?;
+                options.seed = Some(seed.parse().map_err(|_| Failure::Usage(format!("--seed must be a whole number, not {seed}")))?);
+            }
+            "--contribution" => {
+                let contribution = value(&arg)?;
+                let (name, value) = contribution
+                    .split_once('=')
+                    .ok_or_else(|| Failure::Usage(format!("--contribution must be NAME=VALUE, not {contribution}")))?;
+                options.contributions.push(Contribution {
+                    name: name.to_string(),
+                    value: value.to_string(),
+                });
+            }
+            "--mode" => options.mode = Some(parse_mode(&value(&arg)?)?),
+            "--format" => {
+                options.format = match value(&arg)?.as_str() {
+                    "text" => OutputFormat::Text,
+                    "json" => OutputFormat::Json,
+                    "csv" => OutputFormat::Csv,
+                    other => return Err(Failure::Usage(format!("unknown output format {other}"))),
+                }
+            }
+            "--out-dir" => options.out_dir = Some(PathBuf::from(value(&arg)?)),
+            flag if flag.starts_with("--") => return Err(Failure::Usage(format!("unknown option {flag}"))),
+            _ if options.input.is_none() => options.input = Some(arg),
+            _ => return Err(Failure::Usage(format!("unexpected argument {arg}"))),
+        }
+    }
+    if options.seed.is_some() && !options.contributions.is_empty() {
+        return Err(Failure::Usage("use --seed or --contribution, not both".to_string()));
+    }
+    Ok(options)
+}
+
+fn parse_mode(mode: &str) -> Result<DrawMode, Failure> {
+    if mode == "one-to-one" {
+        return Ok(DrawMode::OneToOne);
+    }
+    let sizes = mode.strip_prefix("group:").and_then(|sizes| {
+        let (min, max) = sizes.split_once('-').unwrap_or((sizes, sizes));
+        Some((min.parse::<usize>().ok()?, max.parse::<usize>().ok()?))
+    });
+    match sizes {
+        Some((min_team_size, max_team_size)) if min_team_size >= 1 && min_team_size <= max_team_size => {
+            Ok(DrawMode::GroupGift { min_team_size, max_team_size })
+        }
+        _ => Err(Failure::Usage(format!("--mode must be one-to-one or group:MIN-MAX, not {mode}"))),
+    }
+}
+
+fn format_of(path: &str) -> Option<InputFormat> {
+    match Path::new(path).extension()?.to_str()?.to_lowercase().as_str() {
+        "json" => Some(InputFormat::Json),
+        "toml" => Some(InputFormat::Toml),
+        "csv" => Some(InputFormat::Csv),
+        _ => None,
+    }
+}
+
+/// The event's name and list. TOML is read as the same shape as JSON.
+fn read_list(raw: &str, format: InputFormat) -> Result<(String, SecretSatan), Failure> {
+    let json = match format {
+        InputFormat::Json => raw.to_string(),
+        InputFormat::Toml => {
+            let value: toml::Value = toml::from_str(raw).map_err(|error| Failure::Input(format!("this isn't valid TOML: {error}")))?;
+            serde_json::to_string(&value).map_err(|error| Failure::Input(error.to_string()))?
+        }
+        InputFormat::Csv => {
+            let rows = csv::parse(raw);
+            let headers = rows.first().ok_or_else(|| Failure::Input("the CSV file is empty".to_string()))?;
+            let mapping = csv::ColumnMapping::guess(headers);
+            if mapping.name.is_none() {
+                return Err(Failure::Input("the CSV file needs a column with \"name\" in its heading".to_string()));
+            }
+            let preview = csv::preview(&rows[1..], mapping, &SecretSatan::new());
+            if let Some(row) = preview.iter().find(|row| row.duplicate) {
+                return Err(Failure::Input(format!("{} is listed more than once", row.participant.name)));
+            }
+            for row in &preview {
+                for name in &row.unknown_exclusions {
+                    eprintln!("warning: {} excludes {name}, who isn't on the list", row.participant.name);
+                }
+            }
+            return Ok(("Secret Satan".to_string(), csv::apply(&SecretSatan::new(), &preview)));
+        }
+    };
+    let imported = import_event(&json).map_err(Failure::Input)?;
+    Ok((imported.name.unwrap_or_else(|| "Secret Satan".to_string()), imported.list))
+}
+
+fn render(results: &[Assignment], format: OutputFormat) -> String {
+    match format {
+        OutputFormat::Text => results
+            .iter()
+            .map(|assignment| {
+                let verb = if assignment.givers.len() > 1 { "are" } else { "is" };
+                format!("{} {verb} giving to {}\n", assignment.giver_label(), assignment.receiver_label())
+            })
+            .collect(),
+        OutputFormat::Json => serde_json::to_string_pretty(results).unwrap_or_default() + "\n",
+        OutputFormat::Csv => {
+            let mut out = String::from("Giver,Receiver\r\n");
+            for (giver, receiver) in results.iter().flat_map(Assignment::pairs) {
+                out.push_str(&format!("{},{}\r\n", quote(&giver), quote(&receiver)));
+            }
+            out
+        }
+    }
+}
+
+fn quote(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// One file per giver holding only their own result.
+fn write_per_person(dir: &Path, event: &str, results: &[Assignment], format: OutputFormat) -> Result<(), Failure> {
+    let failed = |error: std::io::Error| Failure::Output(format!("couldn't write to {}: {error}", dir.display()));
+    let reveals = reveals(event, results);
+    check_file_stems(reveals.iter().map(|reveal| reveal.giver.as_str()))?;
+    std::fs::create_dir_all(dir).map_err(failed)?;
+    for reveal in reveals {
+        let (extension, contents) = match format {
+            OutputFormat::Text => {
+                let mut text = format!("{}\nHi {}! You're giving a gift to {}", reveal.event, reveal.giver, reveal.receiver);
+                if !reveal.sharing_with.is_empty() {
+                    text.push_str(&format!(" together with {}", reveal.sharing_with.join(", ")));
+                }
+                ("txt", text + ".\n")
+            }
+            OutputFormat::Json => ("json", serde_json::to_string_pretty(&reveal).unwrap_or_default() + "\n"),
+            OutputFormat::Csv => ("csv", format!("Giver,Receiver\r\n{},{}\r\n", quote(&reveal.giver), quote(&reveal.receiver))),
+        };
+        std::fs::write(dir.join(format!("{}.{extension}", file_stem(&reveal.giver))), contents).map_err(failed)?;
+    }
+    Ok(())
+}
+
+/// A giver's name made safe to use as a file name.
+fn file_stem(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect()
+}
+
+/// Fails before anything is written if two givers would get the same file,
+/// ignoring case since not every file system tells them apart.
+fn check_file_stems<'a>(names: impl IntoIterator<Item = &'a str>) -> Result<(), Failure> {
+    let mut seen = HashMap::new();
+    for name in names {
+        let stem = file_stem(name);
+        if let Some(other) = seen.insert(stem.to_lowercase(), name) {
+            return Err(Failure::Output(format!(
+                "{other} and {name} would both be written to {stem}; rename one of them"
+            )));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn flags_are_parsed() {
+        let options = parse_args(args(&["--seed", "7", "--mode", "group:2-3", "--format", "csv", "list.toml"])).unwrap();
+        assert_eq!(options.seed, Some(7));
+        assert_eq!(options.mode, Some(DrawMode::GroupGift { min_team_size: 2, max_team_size: 3 }));
+        assert_eq!(options.format, OutputFormat::Csv);
+        assert_eq!(options.input.as_deref(), Some("list.toml"));
+
+        assert_eq!(parse_args(args(&["--seed", "x"])).unwrap_err().code(), 2);
+        assert_eq!(parse_args(args(&["--mode", "group:3-2"])).unwrap_err().code(), 2);
+
+        let options = parse_args(args(&["--contribution", "Alice=tin=sel", "list.csv"])).unwrap();
+        assert_eq!(options.contributions[0].value, "tin=sel");
+        assert_eq!(parse_args(args(&["--contribution", "Alice"])).unwrap_err().code(), 2);
+        assert_eq!(parse_args(args(&["--seed", "7", "--contribution", "Alice=tinsel"])).unwrap_err().code(), 2);
+    }
+
+    #[test]
+    fn toml_lists_are_read() {
+        let raw = r#"
+            [[participants]]
+            name = "Alice"
+            excluding = ["Bob"]
+
+            [[participants]]
+            name = "Bob"
+
+            [[participants]]
+            name = "Charlie"
+        "#;
+        let (_, list) = read_list(raw, InputFormat::Toml).unwrap();
+        assert_eq!(list.participants.len(), 3);
+        assert_eq!(list.participants[0].excluding, vec!["Bob".to_string()]);
+    }
+
+    #[test]
+    fn the_same_seed_draws_the_same_list() {
+        let (_, list) = read_list("Name\nAlice\nBob\nCharlie\nDana\n", InputFormat::Csv).unwrap();
+        let first = list.draw_with_rng(&mut StdRng::seed_from_u64(42)).unwrap();
+        let second = list.draw_with_rng(&mut StdRng::seed_from_u64(42)).unwrap();
+        assert_eq!(first, second);
+        assert_eq!(render(&first, OutputFormat::Text).lines().count(), 4);
+    }
+
+    #[test]
+    fn names_sharing_a_file_exit_with_three() {
+        assert!(check_file_stems(["Ann B.", "Bob"]).is_ok());
+        assert_eq!(check_file_stems(["Ann B.", "Bob", "Ann B!"]).unwrap_err().code(), 3);
+        assert_eq!(check_file_stems(["ann", "Ann"]).unwrap_err().code(), 3);
+    }
+
+    #[test]
+    fn impossible_draws_exit_with_one() {
+        let dir = std::env::temp_dir().join(format!("secret-satan-cli-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("pair.csv");
+        std::fs::write(&input, "Name\nAlice\nBob\n").unwrap();
+
+        let failure = run(args(&[input.to_str().unwrap()])).unwrap_err();
+        assert_eq!(failure.code(), 1);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
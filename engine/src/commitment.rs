@@ -0,0 +1,169 @@
+//! Proving the draw wasn't changed after it was made.
+//!
+//! When names are drawn the organizer publishes a commitment: the SHA-256 of
+//! a random salt and the results. Nobody can work out the results from it,
+//! and the organizer can't find different results that match it. Later they
+//! publish the opening, the salt and results themselves, and anyone can hash
+//! them again to check they're what was committed to.
+use crate::entropy::Seed;
+use crate::Assignment;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt;
+
+/// The results of a draw, with the salt that hides them in the commitment.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Opening {
+    pub salt: String,
+    pub results: Vec<Assignment>,
+}
+
+impl Opening {
+    /// Salts `results` with 16 random bytes.
+    pub fn new<R: Rng + ?Sized>(results: Vec<Assignment>, rng: &mut R) -> Opening {
+        let mut bytes = [0u8; 16];
+        rng.fill_bytes(&mut bytes);
+        Opening {
+            salt: hex(&bytes),
+            results,
+        }
+    }
+
+    /// What gets hashed: the salt, a newline, then the results as compact
+    /// JSON, exactly as `to_text` writes them.
+    pub fn preimage(&self) -> String {
+        let results = serde_json::to_string(&self.results).unwrap_or_default();
+        format!("{}\n{results}", self.salt)
+    }
+
+    /// The SHA-256 of `preimage`, in hex. This is what's published at draw
+    /// time.
+    pub fn commitment(&self) -> String {
+        hex(&Sha256::digest(self.preimage().as_bytes()))
+    }
+
+    /// The opening as it's published.
+    pub fn to_text(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    pub fn from_text(text: &str) -> Result<Opening, VerifyError> {
+        serde_json::from_str(text.trim()).map_err(|_| VerifyError::Unreadable)
+    }
+}
+
+/// A draw's commitment, kept with its opening until it's time to publish it.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Commitment {
+    pub hash: String,
+    pub opening: Opening,
+    /// When the hash was shared, as the app recorded it. Results aren't shown
+    /// until it has been, so the organizer can't draw again until they like
+    /// what they see and only share that draw's hash.
+    #[serde(default)]
+    pub published: Option<String>,
+    /// Whether the opening has been published.
+    pub revealed: bool,
+    /// What the draw was seeded with, when everyone contributed to it.
+    pub seed: Option<Seed>,
+}
+
+impl Commitment {
+    pub fn new<R: Rng + ?Sized>(results: Vec<Assignment>, seed: Option<Seed>, rng: &mut R) -> Commitment {
+        let opening = Opening::new(results, rng);
+        Commitment {
+            hash: opening.commitment(),
+            opening,
+            published: None,
+            revealed: false,
+            seed,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum VerifyError {
+    /// The opening isn't one this app published.
+    Unreadable,
+    /// The opening doesn't hash to the commitment, so the results or salt
+    /// aren't the ones committed to.
+    Mismatch,
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            VerifyError::Unreadable => write!(f, "The published draw couldn't be read. Make sure it was copied in full."),
+            VerifyError::Mismatch => write!(f, "The published draw doesn't match the commitment."),
+        }
+    }
+}
+
+impl std::error::Error for VerifyError {}
+
+/// Checks a published opening against the commitment published at draw
+/// time, returning the results if they match.
+pub fn verify(commitment: &str, opening: &str) -> Result<Vec<Assignment>, VerifyError> {
+    let opening = Opening::from_text(opening)?;
+    match opening.commitment().eq_ignore_ascii_case(commitment.trim()) {
+        true => Ok(opening.results),
+        false => Err(VerifyError::Mismatch),
+    }
+}
+
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn results() -> Vec<Assignment> {
+        vec![
+            Assignment {
+                givers: vec!["Alice".to_string()],
+                receivers: vec!["Bob".to_string()],
+            },
+            Assignment {
+                givers: vec!["Bob".to_string()],
+                receivers: vec!["Alice".to_string()],
+            },
+        ]
+    }
+
+    #[test]
+    fn published_draws_check_out() {
+        let commitment = Commitment::new(results(), None, &mut StdRng::seed_from_u64(1));
+        assert_eq!(commitment.hash.len(), 64);
+        assert_eq!(verify(&commitment.hash, &commitment.opening.to_text()), Ok(results()));
+        assert_eq!(verify(&commitment.hash.to_uppercase(), &commitment.opening.to_text()), Ok(results()));
+    }
+
+    #[test]
+    fn changed_draws_do_not() {
+        let commitment = Commitment::new(results(), None, &mut StdRng::seed_from_u64(1));
+        let mut swapped = commitment.opening.clone();
+        swapped.results.reverse();
+        assert_eq!(verify(&commitment.hash, &swapped.to_text()), Err(VerifyError::Mismatch));
+        assert_eq!(verify(&commitment.hash, "Alice -> Bob"), Err(VerifyError::Unreadable));
+
+        // The salt keeps the same results from giving the same commitment.
+        let again = Commitment::new(results(), None, &mut StdRng::seed_from_u64(2));
+        assert_ne!(again.hash, commitment.hash);
+    }
+
+    #[test]
+    fn the_hash_is_of_the_salt_and_results() {
+        let opening = Opening {
+            salt: "00".to_string(),
+            results: Vec::new(),
+        };
+        assert_eq!(opening.preimage(), "00\n[]");
+        // The same as `printf '00\n[]' | sha256sum`.
+        assert_eq!(opening.commitment(), "f83e68616b3de2325fee8a83228ae3166fd981af9d2930e100436ff28ad39264");
+    }
+}
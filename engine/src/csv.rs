@@ -0,0 +1,274 @@
+//! Bringing participants in from a spreadsheet, and sending them back out.
+//!
+//! Only as much CSV as spreadsheets actually produce: quoted fields with
+//! doubled quotes, CRLF or LF line endings, and a comma, semicolon or tab
+//! between fields.
+use crate::{Participant, SecretSatan};
+use std::collections::{HashMap, HashSet};
+
+/// What separates several names or tags inside one cell.
+const LIST_SEPARATORS: [char; 2] = [';', '|'];
+
+/// Which column holds each field. Only the name is required.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ColumnMapping {
+    pub name: Option<usize>,
+    pub email: Option<usize>,
+    /// A household, team or any other labels.
+    pub tags: Option<usize>,
+    pub exclusions: Option<usize>,
+}
+
+impl ColumnMapping {
+    /// Matches columns to fields by their headings, e.g. "Full name" or
+    /// "Household". A heading that is just "Name" or "Full name" wins over
+    /// one that only mentions it, like "Team name".
+    pub fn guess(headers: &[String]) -> ColumnMapping {
+        let find = |words: &[&str]| {
+            headers.iter().position(|header| {
+                let header = header.to_lowercase();
+                words.iter().any(|word| header.contains(word))
+            })
+        };
+        let exact = headers
+            .iter()
+            .position(|header| ["name", "full name"].contains(&header.trim().to_lowercase().as_str()));
+        ColumnMapping {
+            name: exact.or_else(|| find(&["name"])),
+            email: find(&["email", "e-mail"]),
+            tags: find(&["household", "tag", "team", "group"]),
+            exclusions: find(&["exclu", "not give", "can't give", "avoid"]),
+        }
+    }
+}
+
+/// One row of the file as it would be added, and what's wrong with it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreviewRow {
+    pub participant: Participant,
+    /// The name is already on the list or earlier in the file.
+    pub duplicate: bool,
+    /// Exclusions naming nobody on the list or in the file. Those that do
+    /// name someone are spelled the way their row or the list spells them.
+    pub unknown_exclusions: Vec<String>,
+}
+
+/// Splits CSV text into rows of fields. Blank lines are skipped.
+pub fn parse(text: &str) -> Vec<Vec<String>> {
+    let text = text.trim_start_matches('\u{feff}');
+    let delimiter = detect_delimiter(text);
+
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' if quoted => quoted = false,
+            '"' if field.is_empty() => quoted = true,
+            c if c == delimiter && !quoted => row.push(std::mem::take(&mut field)),
+            '\r' if !quoted => {}
+            '\n' if !quoted => {
+                row.push(std::mem::take(&mut field));
+                if row.iter().any(|field| !field.trim().is_empty()) {
+                    rows.push(std::mem::take(&mut row));
+                }
+                row.clear();
+            }
+            c => field.push(c),
+        }
+    }
+    row.push(field);
+    if row.iter().any(|field| !field.trim().is_empty()) {
+        rows.push(row);
+    }
+    rows
+}
+
+/// Whichever of comma, semicolon or tab appears most in the first line.
+fn detect_delimiter(text: &str) -> char {
+    let first_line = text.lines().next().unwrap_or_default();
+    [',', ';', '\t']
+        .into_iter()
+        .max_by_key(|&delimiter| first_line.matches(delimiter).count())
+        .filter(|&delimiter| first_line.contains(delimiter))
+        .unwrap_or(',')
+}
+
+/// Turns the rows after the header into participants and checks them
+/// against `current`.
+pub fn preview(rows: &[Vec<String>], mapping: ColumnMapping, current: &SecretSatan) -> Vec<PreviewRow> {
+    let Some(name_column) = mapping.name else {
+        return Vec::new();
+    };
+    let cell = |row: &Vec<String>, column: Option<usize>| {
+        column.and_then(|column| row.get(column)).map(|cell| cell.trim().to_string()).unwrap_or_default()
+    };
+    let list = |cell: String| -> Vec<String> {
+        cell.split(LIST_SEPARATORS)
+            .map(|item| item.trim().to_string())
+            .filter(|item| !item.is_empty())
+            .collect()
+    };
+
+    let participants: Vec<Participant> = rows
+        .iter()
+        .filter(|row| !cell(row, Some(name_column)).is_empty())
+        .map(|row| {
+            let mut participant = Participant::new(cell(row, Some(name_column)));
+            participant.email = Some(cell(row, mapping.email)).filter(|email| !email.is_empty());
+            participant.tags = list(cell(row, mapping.tags));
+            participant.excluding = list(cell(row, mapping.exclusions));
+            participant
+        })
+        .collect();
+
+    // Exclusions are matched ignoring case, but the draw matches names
+    // exactly, so each is rewritten to the name it matched. The list's
+    // spelling wins over the file's, and earlier rows over later ones.
+    let mut known: HashMap<String, String> = HashMap::new();
+    for p in current.participants.iter().chain(participants.iter()) {
+        known.entry(p.name.to_lowercase()).or_insert_with(|| p.name.clone());
+    }
+    let mut seen: HashSet<String> = current.participants.iter().map(|p| p.name.to_lowercase()).collect();
+    participants
+        .into_iter()
+        .map(|mut participant| {
+            let mut unknown_exclusions = Vec::new();
+            for name in &mut participant.excluding {
+                match known.get(&name.to_lowercase()) {
+                    Some(exact) => name.clone_from(exact),
+                    None => unknown_exclusions.push(name.clone()),
+                }
+            }
+            PreviewRow {
+                duplicate: !seen.insert(participant.name.to_lowercase()),
+                unknown_exclusions,
+                participant,
+            }
+        })
+        .collect()
+}
+
+/// Adds the rows that aren't duplicates to `current`.
+pub fn apply(current: &SecretSatan, rows: &[PreviewRow]) -> SecretSatan {
+    let mut list = current.clone();
+    for row in rows.iter().filter(|row| !row.duplicate) {
+        list.add_participant(row.participant.clone());
+    }
+    list
+}
+
+/// The list as CSV, with the same columns the importer guesses.
+pub fn export(list: &SecretSatan) -> String {
+    let mut out = String::from("Name,Email,Household,Exclusions\r\n");
+    for participant in &list.participants {
+        let fields = [
+            participant.name.clone(),
+            participant.email.clone().unwrap_or_default(),
+            participant.tags.join("; "),
+            participant.excluding.join("; "),
+        ];
+        let fields: Vec<String> = fields.iter().map(|field| quote(field)).collect();
+        out.push_str(&fields.join(","));
+        out.push_str("\r\n");
+    }
+    out
+}
+
+fn quote(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r', ';', '\t']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quoted_fields_and_other_delimiters_are_parsed() {
+        let rows = parse("Name;Notes\r\n\"Smith; Ann\";\"said \"\"hi\"\"\"\r\n\r\nBob;\n");
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[1], vec!["Smith; Ann".to_string(), "said \"hi\"".to_string()]);
+        assert_eq!(rows[2], vec!["Bob".to_string(), String::new()]);
+    }
+
+    #[test]
+    fn columns_are_guessed_from_headings() {
+        let headers: Vec<String> = ["Email address", "Full name", "Household", "Exclusions"]
+            .iter()
+            .map(|header| header.to_string())
+            .collect();
+        let mapping = ColumnMapping::guess(&headers);
+        assert_eq!(mapping.name, Some(1));
+        assert_eq!(mapping.email, Some(0));
+        assert_eq!(mapping.tags, Some(2));
+        assert_eq!(mapping.exclusions, Some(3));
+    }
+
+    #[test]
+    fn a_heading_that_is_just_name_wins() {
+        let headers = |headers: &[&str]| headers.iter().map(|header| header.to_string()).collect::<Vec<_>>();
+        assert_eq!(ColumnMapping::guess(&headers(&["Team name", "Name", "Email"])).name, Some(1));
+        assert_eq!(ColumnMapping::guess(&headers(&["Username", " Full Name "])).name, Some(1));
+        assert_eq!(ColumnMapping::guess(&headers(&["Household", "Participant name"])).name, Some(1));
+    }
+
+    #[test]
+    fn preview_flags_duplicates_and_unknown_exclusions() {
+        let mut current = SecretSatan::new();
+        current.add_participant(Participant::new("Alice".to_string()));
+        let rows = parse("Name,Exclusions\nalice,\nBob,Alice; Zed\nCharlie,Bob\nBob,\n");
+        let mapping = ColumnMapping::guess(&rows[0]);
+
+        let preview = preview(&rows[1..], mapping, &current);
+        let duplicates: Vec<bool> = preview.iter().map(|row| row.duplicate).collect();
+        assert_eq!(duplicates, vec![true, false, false, true]);
+        assert_eq!(preview[1].unknown_exclusions, vec!["Zed".to_string()]);
+        assert!(preview[2].unknown_exclusions.is_empty());
+
+        let list = apply(&current, &preview);
+        assert_eq!(list.participants.len(), 3);
+    }
+
+    #[test]
+    fn exclusions_are_spelled_like_the_names_they_match() {
+        let mut current = SecretSatan::new();
+        current.add_participant(Participant::new("Alice".to_string()));
+        let rows = parse("Name,Exclusions
+Bob,alice; CHARLIE
+Charlie,bob
+");
+        let mapping = ColumnMapping::guess(&rows[0]);
+
+        let preview = preview(&rows[1..], mapping, &current);
+        assert!(preview.iter().all(|row| row.unknown_exclusions.is_empty()));
+        let list = apply(&current, &preview);
+        assert_eq!(list.participants[1].excluding, vec!["Alice".to_string(), "Charlie".to_string()]);
+        assert_eq!(list.participants[2].excluding, vec!["Bob".to_string()]);
+    }
+
+    #[test]
+    fn exported_lists_import_the_same() {
+        let mut original = SecretSatan::new();
+        let mut alice = Participant::new("Alice, Jr.".to_string());
+        alice.email = Some("alice@example.com".to_string());
+        alice.tags = vec!["Smiths".to_string()];
+        alice.excluding = vec!["Bob".to_string()];
+        original.add_participant(alice);
+        original.add_participant(Participant::new("Bob".to_string()));
+
+        let rows = parse(&export(&original));
+        let mapping = ColumnMapping::guess(&rows[0]);
+        let imported = apply(&SecretSatan::new(), &preview(&rows[1..], mapping, &SecretSatan::new()));
+        assert_eq!(imported, original);
+    }
+}
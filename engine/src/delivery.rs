@@ -0,0 +1,237 @@
+//! Telling each giver who they're giving to, by email or however else the
+//! event is set up to, so the organizer doesn't have to look at the list.
+//!
+//! This keeps results out of sight, not out of reach: the draw still runs in
+//! the organizer's browser, and the results are kept with the rest of the
+//! event, so an organizer who wants to can still see them.
+use crate::reveal::Reveal;
+use crate::SecretSatan;
+use serde::{Deserialize, Serialize};
+
+/// The message sent when the organizer doesn't write their own. Anything in
+/// braces is filled in by `render`.
+pub const DEFAULT_TEMPLATE: &str = "Hi {giver},
+
+You're the Secret Santa for {receiver} in {event}!
+{details}{wishlist}
+Keep it a secret!";
+
+/// Posted to everyone once results have gone out.
+pub const DRAWN_ANNOUNCEMENT: &str = "Names have been drawn for {event}! Everyone has been sent who they're giving to.{details}";
+
+/// The reminder offered when the organizer doesn't write their own.
+pub const DEFAULT_REMINDER: &str = "Just a reminder that {event} is coming up.{details}
+
+Have you got your gift yet?";
+
+/// One message, ready to send.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Email {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub enum DeliveryStatus {
+    #[default]
+    NotSent,
+    /// Nothing the event sends through can reach the giver, e.g. it only
+    /// uses email and they have no address.
+    NoAddress,
+    Sent,
+    /// Sending was refused or the service couldn't be reached, and why.
+    Failed(String),
+}
+
+/// Where an event's results and reminders go.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct NotificationSettings {
+    /// Email each giver their result, and everyone the reminders.
+    pub email: bool,
+    /// Post reminders to a chat webhook, like Slack's or Matrix's. Results
+    /// never go there, since everyone in the channel would see them.
+    pub webhook: Option<String>,
+    /// Write a file per message for sending by hand.
+    pub files: bool,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> NotificationSettings {
+        NotificationSettings {
+            email: true,
+            webhook: None,
+            files: false,
+        }
+    }
+}
+
+/// An event's settings, and which of them the server can actually act on.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct NotificationOptions {
+    pub settings: NotificationSettings,
+    /// Whether the server has a mail server to send through.
+    pub email_available: bool,
+    /// Whether the server has a folder to write to.
+    pub files_available: bool,
+}
+
+/// How telling one giver their result went.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Delivery {
+    pub giver: String,
+    pub email: Option<String>,
+    pub status: DeliveryStatus,
+    pub attempts: u32,
+}
+
+/// Fills in `template` for one giver: `{giver}`, `{receiver}`, `{event}`,
+/// `{budget}`, `{date}`, `{details}` (the budget and date as a line each,
+/// when they're set) and `{wishlist}` (the receivers' wishes, when they
+/// have any).
+pub fn render(template: &str, reveal: &Reveal, list: &SecretSatan) -> String {
+    let mut receiver = reveal.receiver.clone();
+    if !reveal.sharing_with.is_empty() {
+        receiver.push_str(&format!(", together with {}", reveal.sharing_with.join(", ")));
+    }
+    let receivers: Vec<String> = list
+        .results
+        .iter()
+        .find(|assignment| assignment.givers.contains(&reveal.giver))
+        .map(|assignment| assignment.receivers.clone())
+        .unwrap_or_default();
+    let wishes: Vec<String> = list
+        .participants
+        .iter()
+        .filter(|participant| receivers.contains(&participant.name))
+        .filter_map(|participant| Some(format!("\n{}: {}", participant.name, set(&participant.wishlist)?)))
+        .collect();
+    let wishlist = match wishes.is_empty() {
+        true => String::new(),
+        false => format!("\nTheir wishes:{}\n", wishes.concat()),
+    };
+
+    let mut values = vec![("giver", reveal.giver.clone()), ("receiver", receiver), ("wishlist", wishlist)];
+    values.extend(shared_values(&reveal.event, list));
+    fill(template, &values)
+}
+
+/// Fills in the parts of `template` that are the same for everyone:
+/// `{event}`, `{budget}`, `{date}` and `{details}`.
+pub fn render_for_everyone(template: &str, event: &str, list: &SecretSatan) -> String {
+    fill(template, &shared_values(event, list))
+}
+
+fn shared_values(event: &str, list: &SecretSatan) -> Vec<(&'static str, String)> {
+    let mut details = String::new();
+    if let Some(budget) = set(&list.budget) {
+        details.push_str(&format!("\nBudget: {budget}"));
+    }
+    if let Some(date) = set(&list.date) {
+        details.push_str(&format!("\nDate: {date}"));
+    }
+    vec![
+        ("event", event.to_string()),
+        ("budget", set(&list.budget).unwrap_or_default()),
+        ("date", set(&list.date).unwrap_or_default()),
+        ("details", details),
+    ]
+}
+
+/// Replaces each `{name}` in `template` with its value, in one pass, so
+/// braces in the values themselves are left alone. Unknown names are kept.
+fn fill(template: &str, values: &[(&str, String)]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        out.push_str(&rest[..open]);
+        let after = &rest[open + 1..];
+        let value = after
+            .find('}')
+            .and_then(|close| values.iter().find(|(name, _)| *name == &after[..close]).map(|(_, value)| (close, value)));
+        match value {
+            Some((close, value)) => {
+                out.push_str(value);
+                rest = &after[close + 1..];
+            }
+            None => {
+                out.push('{');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn set(field: &Option<String>) -> Option<String> {
+    field.clone().filter(|field| !field.trim().is_empty())
+}
+
+/// The subject of the message telling a giver their result.
+pub fn result_subject(event: &str) -> String {
+    format!("Your Secret Santa match for {event}")
+}
+
+/// A giver's email address, if they gave one.
+pub fn address(list: &SecretSatan, giver: &str) -> Option<String> {
+    let participant = list.participants.iter().find(|participant| participant.name == giver)?;
+    set(&participant.email)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reveal::reveals;
+    use crate::{Assignment, Participant};
+
+    fn list() -> SecretSatan {
+        let mut list = SecretSatan::new();
+        let mut alice = Participant::new("Alice".to_string());
+        alice.email = Some("alice@example.com".to_string());
+        list.add_participant(alice);
+        let mut bob = Participant::new("Bob".to_string());
+        bob.wishlist = Some("Socks".to_string());
+        list.add_participant(bob);
+        list.set_results(vec![
+            Assignment {
+                givers: vec!["Alice".to_string()],
+                receivers: vec!["Bob".to_string()],
+            },
+            Assignment {
+                givers: vec!["Bob".to_string()],
+                receivers: vec!["Alice".to_string()],
+            },
+        ]);
+        list.budget = Some("$20".to_string());
+        list
+    }
+
+    #[test]
+    fn messages_are_filled_in() {
+        let list = list();
+        let reveals = reveals("Office", &list.results);
+        let body = render(DEFAULT_TEMPLATE, &reveals[0], &list);
+        assert!(body.contains("Secret Santa for Bob in Office"));
+        assert!(body.contains("Budget: $20"));
+        assert!(!body.contains("Date:"));
+        assert!(body.contains("Bob: Socks"));
+        assert_eq!(render("{giver} -> {receiver}, {date}", &reveals[0], &list), "Alice -> Bob, ");
+        assert_eq!(render_for_everyone(DRAWN_ANNOUNCEMENT, "Office", &list), "Names have been drawn for Office! Everyone has been sent who they're giving to.\nBudget: $20");
+    }
+
+    #[test]
+    fn braces_in_names_are_not_filled_in_again() {
+        let mut list = list();
+        list.results[0].givers = vec!["{receiver}".to_string()];
+        let reveals = reveals("{event}", &list.results);
+        assert_eq!(render("{giver} -> {receiver} at {event}, {unknown}", &reveals[0], &list), "{receiver} -> Bob at {event}, {unknown}");
+    }
+
+    #[test]
+    fn givers_without_an_address_have_none() {
+        let list = list();
+        assert_eq!(address(&list, "Alice").as_deref(), Some("alice@example.com"));
+        assert_eq!(address(&list, "Bob"), None);
+    }
+}
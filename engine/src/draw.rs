@@ -0,0 +1,506 @@
+//! The matching engine behind `SecretSatan::assign_participants`.
+//!
+//! Everything in here works on indices so it doesn't care what a "node" is;
+//! the caller decides who may give to whom and gets back `(giver, receiver)`
+//! index pairs.
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+/// How many search steps we'll take before switching to the fallback.
+/// Keeps pathological lists from hanging the page.
+const SEARCH_BUDGET: usize = 200_000;
+
+/// How many fresh matchings the fallback tries before giving up.
+const FALLBACK_ATTEMPTS: usize = 20;
+
+/// Why `one_to_one` found no matching.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Unmatched {
+    /// There's no matching at all.
+    Impossible,
+    /// The search ran out of steps and the fallback found nothing either, so
+    /// a matching may still exist.
+    GaveUp,
+}
+
+/// Finds a perfect one-to-one matching between `givers` and `receivers`.
+///
+/// `allowed(g, r)` says whether giver `g` may give to receiver `r`. Pairs
+/// where two people would give to each other are avoided, and pairs where
+/// `repeated(g, r)` is true are a last resort, used as few times as possible.
+///
+/// The search is exhaustive, so when it finishes without a matching there
+/// isn't one. If it runs out of steps first, matchings are built directly
+/// and reciprocal pairs swapped out of them instead.
+pub(crate) fn one_to_one<R: Rng + ?Sized>(
+    givers: &[usize],
+    receivers: &[usize],
+    allowed: impl Fn(usize, usize) -> bool,
+    repeated: impl Fn(usize, usize) -> bool,
+    rng: &mut R,
+) -> Result<Vec<(usize, usize)>, Unmatched> {
+    if givers.len() != receivers.len() {
+        return Err(Unmatched::Impossible);
+    }
+
+    let candidates: Vec<Vec<usize>> = givers
+        .iter()
+        .map(|&g| {
+            let mut options: Vec<usize> = (0..receivers.len())
+                .filter(|&r| allowed(g, receivers[r]))
+                .collect();
+            options.shuffle(rng);
+            // Fresh pairs first, so the search finds them before repeats.
+            options.sort_by_key(|&r| repeated(g, receivers[r]));
+            options
+        })
+        .collect();
+    let repeats: Vec<Vec<bool>> = givers
+        .iter()
+        .zip(candidates.iter())
+        .map(|(&g, options)| options.iter().map(|&r| repeated(g, receivers[r])).collect())
+        .collect();
+
+    // Cheap check first: if there's no matching even when reciprocal pairs
+    // are allowed, there's no point searching.
+    if maximum_matching(&candidates, receivers.len()) < givers.len() {
+        return Err(Unmatched::Impossible);
+    }
+
+    let mut order: Vec<usize> = (0..givers.len()).collect();
+    order.shuffle(rng);
+    // Most constrained givers first keeps the search short.
+    order.sort_by_key(|&g| candidates[g].len());
+
+    // One search, with one budget, that keeps looking for a matching with
+    // fewer repeats until it runs out of places to look.
+    let mut search = Search {
+        givers,
+        receivers,
+        candidates: &candidates,
+        repeats: &repeats,
+        order: &order,
+        taken: vec![None; receivers.len()],
+        chosen: vec![None; givers.len()],
+        repeat_count: 0,
+        best: None,
+        best_repeats: givers.len() + 1,
+        steps: 0,
+    };
+    search.run(0);
+    if search.steps <= SEARCH_BUDGET {
+        return search.best.map(|best| pairs(givers, receivers, &best)).ok_or(Unmatched::Impossible);
+    }
+
+    // Out of steps, so the best so far may not be the best there is. The
+    // fallback sticks to fresh pairs when it can, so it may do better.
+    let fallback = fallback(givers, receivers, &candidates, &repeats, rng)
+        .map(|chosen| (count_repeats(&candidates, &repeats, &chosen), chosen));
+    let best = search.best.map(|best| (search.best_repeats, best));
+    [best, fallback]
+        .into_iter()
+        .flatten()
+        .min_by_key(|(repeats, _)| *repeats)
+        .map(|(_, chosen)| pairs(givers, receivers, &chosen))
+        .ok_or(Unmatched::GaveUp)
+}
+
+fn count_repeats(candidates: &[Vec<usize>], repeats: &[Vec<bool>], chosen: &[Option<usize>]) -> usize {
+    chosen
+        .iter()
+        .enumerate()
+        .filter(|&(g, r)| {
+            candidates[g]
+                .iter()
+                .position(|&option| Some(option) == *r)
+                .is_some_and(|i| repeats[g][i])
+        })
+        .count()
+}
+
+fn pairs(givers: &[usize], receivers: &[usize], chosen: &[Option<usize>]) -> Vec<(usize, usize)> {
+    chosen
+        .iter()
+        .enumerate()
+        .map(|(g, r)| (givers[g], receivers[r.expect("every giver is matched")]))
+        .collect()
+}
+
+/// Finds a matching without searching: a random perfect matching, with
+/// reciprocal pairs swapped away one at a time. Tries fresh pairs only
+/// first, then repeats too. Returns the receiver slot for each giver.
+fn fallback<R: Rng + ?Sized>(
+    givers: &[usize],
+    receivers: &[usize],
+    candidates: &[Vec<usize>],
+    repeats: &[Vec<bool>],
+    rng: &mut R,
+) -> Option<Vec<Option<usize>>> {
+    let fresh: Vec<Vec<usize>> = candidates
+        .iter()
+        .zip(repeats)
+        .map(|(options, repeats)| {
+            options
+                .iter()
+                .zip(repeats)
+                .filter(|(_, &repeat)| !repeat)
+                .map(|(&r, _)| r)
+                .collect()
+        })
+        .collect();
+
+    for options in [fresh, candidates.to_vec()] {
+        let mut allowed = vec![vec![false; receivers.len()]; givers.len()];
+        for (g, options) in options.iter().enumerate() {
+            for &r in options {
+                allowed[g][r] = true;
+            }
+        }
+        for _ in 0..FALLBACK_ATTEMPTS {
+            let mut shuffled = options.clone();
+            for options in &mut shuffled {
+                options.shuffle(rng);
+            }
+            let mut matching = Matching::new(&shuffled, receivers.len());
+            if !(0..givers.len()).all(|g| matching.augment(g)) {
+                // Fresh pairs alone can't cover everyone.
+                break;
+            }
+            let mut chosen = vec![None; givers.len()];
+            for (r, g) in matching.owner.iter().enumerate() {
+                chosen[g.expect("every slot is taken")] = Some(r);
+            }
+            if untangle(givers, receivers, &allowed, &mut chosen, rng) {
+                return Some(chosen);
+            }
+        }
+    }
+    None
+}
+
+/// Swaps receivers between pairs of givers until nobody gives to the person
+/// giving to them. Each swap leaves fewer reciprocal pairs, so this always
+/// finishes. Returns false if it gets stuck.
+fn untangle<R: Rng + ?Sized>(
+    givers: &[usize],
+    receivers: &[usize],
+    allowed: &[Vec<bool>],
+    chosen: &mut [Option<usize>],
+    rng: &mut R,
+) -> bool {
+    let reciprocal = |chosen: &[Option<usize>], g: usize| {
+        let receiver = receivers[chosen[g].expect("every giver is matched")];
+        givers
+            .iter()
+            .position(|&n| n == receiver)
+            .is_some_and(|other| chosen[other].is_some_and(|slot| receivers[slot] == givers[g]))
+    };
+    let mut others: Vec<usize> = (0..givers.len()).collect();
+    while let Some(g) = (0..givers.len()).find(|&g| reciprocal(chosen, g)) {
+        others.shuffle(rng);
+        let swapped = others.iter().any(|&other| {
+            let (mine, theirs) = (chosen[g].expect("matched"), chosen[other].expect("matched"));
+            if other == g || !allowed[g][theirs] || !allowed[other][mine] {
+                return false;
+            }
+            chosen.swap(g, other);
+            if reciprocal(chosen, g) || reciprocal(chosen, other) {
+                chosen.swap(g, other);
+                return false;
+            }
+            true
+        });
+        if !swapped {
+            return false;
+        }
+    }
+    true
+}
+
+/// Splits `givers` into one team per receiver, with team sizes differing by
+/// at most one.
+///
+/// `allowed(g, r)` says whether giver `g` may be on the team for receiver `r`.
+/// Returns `(team, receiver)` pairs, or `None` when no split exists.
+pub(crate) fn teams<R: Rng + ?Sized>(
+    givers: &[usize],
+    receivers: &[usize],
+    allowed: impl Fn(usize, usize) -> bool,
+    rng: &mut R,
+) -> Option<Vec<(Vec<usize>, usize)>> {
+    if receivers.is_empty() || givers.len() < receivers.len() {
+        return None;
+    }
+    let base = givers.len() / receivers.len();
+    let extra = givers.len() % receivers.len();
+
+    // Every receiver has `base` seats that must be filled, plus one spare seat
+    // when the givers don't split evenly. The spare seats are tried in random
+    // order so nobody is favoured with the bigger team.
+    let mut required = Vec::new();
+    let mut spare = Vec::new();
+    for r in 0..receivers.len() {
+        required.extend(std::iter::repeat_n(r, base));
+        if extra > 0 {
+            spare.push(r);
+        }
+    }
+    required.shuffle(rng);
+    spare.shuffle(rng);
+    let seats: Vec<usize> = required.iter().chain(spare.iter()).copied().collect();
+
+    // Matching runs from seats to givers, so a seat that is filled stays filled.
+    let candidates: Vec<Vec<usize>> = seats
+        .iter()
+        .map(|&r| {
+            let mut options: Vec<usize> = (0..givers.len())
+                .filter(|&g| allowed(givers[g], receivers[r]))
+                .collect();
+            options.shuffle(rng);
+            options
+        })
+        .collect();
+
+    let mut matching = Matching::new(&candidates, givers.len());
+    for seat in 0..required.len() {
+        if !matching.augment(seat) {
+            return None;
+        }
+    }
+    let mut filled = required.len();
+    for seat in required.len()..seats.len() {
+        if filled == givers.len() {
+            break;
+        }
+        if matching.augment(seat) {
+            filled += 1;
+        }
+    }
+    if filled < givers.len() {
+        return None;
+    }
+
+    let mut teams: Vec<Vec<usize>> = vec![Vec::new(); receivers.len()];
+    for (g, seat) in matching.owner.iter().enumerate() {
+        let seat = seat.expect("every giver has a seat");
+        teams[seats[seat]].push(givers[g]);
+    }
+    Some(teams.into_iter().zip(receivers.iter().copied()).collect())
+}
+
+/// Kuhn's augmenting path algorithm. `candidates[g]` lists the receiver
+/// slots giver `g` may take. Returns the size of the largest matching.
+pub(crate) fn maximum_matching(candidates: &[Vec<usize>], slots: usize) -> usize {
+    let mut matching = Matching::new(candidates, slots);
+    (0..candidates.len()).filter(|&g| matching.augment(g)).count()
+}
+
+/// A bipartite matching that grows one left-hand node at a time. Once a
+/// node is matched it stays matched, though its partner may change.
+struct Matching<'a> {
+    candidates: &'a [Vec<usize>],
+    /// Which left-hand node holds each right-hand node.
+    owner: Vec<Option<usize>>,
+}
+
+impl<'a> Matching<'a> {
+    fn new(candidates: &'a [Vec<usize>], slots: usize) -> Matching<'a> {
+        Matching {
+            candidates,
+            owner: vec![None; slots],
+        }
+    }
+
+    fn augment(&mut self, left: usize) -> bool {
+        let mut seen = vec![false; self.owner.len()];
+        self.visit(left, &mut seen)
+    }
+
+    fn visit(&mut self, left: usize, seen: &mut [bool]) -> bool {
+        for &right in &self.candidates[left] {
+            if seen[right] {
+                continue;
+            }
+            seen[right] = true;
+            let free = match self.owner[right] {
+                None => true,
+                Some(other) => self.visit(other, seen),
+            };
+            if free {
+                self.owner[right] = Some(left);
+                return true;
+            }
+        }
+        false
+    }
+}
+
+struct Search<'a> {
+    givers: &'a [usize],
+    receivers: &'a [usize],
+    candidates: &'a [Vec<usize>],
+    /// Whether each candidate in `candidates` is a repeated pair.
+    repeats: &'a [Vec<bool>],
+    order: &'a [usize],
+    /// Which giver (by position) has taken each receiver slot.
+    taken: Vec<Option<usize>>,
+    /// Which receiver slot each giver (by position) has taken.
+    chosen: Vec<Option<usize>>,
+    repeat_count: usize,
+    /// The matching with the fewest repeats found so far.
+    best: Option<Vec<Option<usize>>>,
+    /// How many repeats `best` has. Only matchings with fewer are wanted.
+    best_repeats: usize,
+    steps: usize,
+}
+
+impl Search<'_> {
+    /// Returns true once there's no point looking further: the budget has
+    /// run out, or a matching without repeats has turned up.
+    fn run(&mut self, depth: usize) -> bool {
+        if depth == self.order.len() {
+            self.best = Some(self.chosen.clone());
+            self.best_repeats = self.repeat_count;
+            return self.repeat_count == 0;
+        }
+        let g = self.order[depth];
+        for (&r, &repeat) in self.candidates[g].iter().zip(self.repeats[g].iter()) {
+            self.steps += 1;
+            if self.steps > SEARCH_BUDGET {
+                return true;
+            }
+            if self.taken[r].is_some() || self.is_reciprocal(g, r) {
+                continue;
+            }
+            if self.repeat_count + usize::from(repeat) >= self.best_repeats {
+                continue;
+            }
+            self.taken[r] = Some(g);
+            self.chosen[g] = Some(r);
+            self.repeat_count += usize::from(repeat);
+            if self.run(depth + 1) {
+                return true;
+            }
+            self.repeat_count -= usize::from(repeat);
+            self.taken[r] = None;
+            self.chosen[g] = None;
+        }
+        false
+    }
+
+    /// Would giver `g` taking receiver slot `r` mean two people swap gifts?
+    fn is_reciprocal(&self, g: usize, r: usize) -> bool {
+        let giver = self.givers[g];
+        let receiver = self.receivers[r];
+        // Find the receiver among the givers and see whether they already
+        // give to our giver.
+        self.givers.iter().position(|&n| n == receiver).is_some_and(|other| {
+            self.chosen[other].is_some_and(|slot| self.receivers[slot] == giver)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maximum_matching_counts_augmenting_paths() {
+        // Giver 0 can only take slot 0, giver 1 can take either.
+        let candidates = vec![vec![0], vec![0, 1]];
+        assert_eq!(maximum_matching(&candidates, 2), 2);
+
+        let candidates = vec![vec![0], vec![0]];
+        assert_eq!(maximum_matching(&candidates, 2), 1);
+    }
+
+    #[test]
+    fn one_to_one_avoids_reciprocal_pairs() {
+        let nodes = [0, 1, 2];
+        let rng = &mut rand::thread_rng();
+        for _ in 0..50 {
+            let pairs = one_to_one(&nodes, &nodes, |g, r| g != r, |_, _| false, rng).unwrap();
+            for &(g, r) in &pairs {
+                assert!(!pairs.contains(&(r, g)));
+            }
+        }
+    }
+
+    #[test]
+    fn one_to_one_fails_when_only_reciprocal_pairs_remain() {
+        let nodes = [0, 1];
+        let rng = &mut rand::thread_rng();
+        assert_eq!(one_to_one(&nodes, &nodes, |g, r| g != r, |_, _| false, rng), Err(Unmatched::Impossible));
+    }
+
+    #[test]
+    fn one_to_one_finds_a_planted_matching_the_search_misses() {
+        // Everyone may give to the next person round a circle of 80, plus two
+        // others at random. The circle is always a valid draw, but the search
+        // alone runs out of steps before finding one.
+        let nodes: Vec<usize> = (0..80).collect();
+        let rng = &mut rand::thread_rng();
+        for _ in 0..5 {
+            let mut allowed = vec![vec![false; 80]; 80];
+            for (g, row) in allowed.iter_mut().enumerate() {
+                row[(g + 1) % 80] = true;
+                for _ in 0..2 {
+                    let r = rng.gen_range(0..80);
+                    row[r] = r != g;
+                }
+            }
+            let pairs = one_to_one(&nodes, &nodes, |g, r| allowed[g][r], |_, _| false, rng).unwrap();
+            assert_eq!(pairs.len(), 80);
+            for &(g, r) in &pairs {
+                assert!(allowed[g][r]);
+                assert!(!pairs.contains(&(r, g)));
+            }
+        }
+    }
+
+    #[test]
+    fn teams_are_balanced() {
+        let givers: Vec<usize> = (0..7).collect();
+        let receivers = [7, 8, 9];
+        let rng = &mut rand::thread_rng();
+        let teams = teams(&givers, &receivers, |_, _| true, rng).unwrap();
+        let mut sizes: Vec<usize> = teams.iter().map(|(team, _)| team.len()).collect();
+        sizes.sort();
+        assert_eq!(sizes, vec![2, 2, 3]);
+    }
+
+    #[test]
+    fn teams_respect_allowed_pairs() {
+        let givers = [0, 1, 2, 3];
+        let receivers = [0, 1];
+        let rng = &mut rand::thread_rng();
+        for _ in 0..20 {
+            let teams = teams(&givers, &receivers, |g, r| g != r, rng).unwrap();
+            for (team, receiver) in teams {
+                assert_eq!(team.len(), 2);
+                assert!(!team.contains(&receiver));
+            }
+        }
+    }
+
+    #[test]
+    fn one_to_one_prefers_fresh_pairs() {
+        let nodes = [0, 1, 2, 3];
+        let rng = &mut rand::thread_rng();
+        for _ in 0..20 {
+            let pairs = one_to_one(&nodes, &nodes, |g, r| g != r, |g, r| r == (g + 1) % 4, rng).unwrap();
+            for &(g, r) in &pairs {
+                assert_ne!(r, (g + 1) % 4);
+            }
+        }
+    }
+
+    #[test]
+    fn one_to_one_repeats_when_it_must() {
+        let nodes = [0, 1, 2];
+        let rng = &mut rand::thread_rng();
+        // Every pair has been used, so a repeat is unavoidable.
+        let pairs = one_to_one(&nodes, &nodes, |g, r| g != r, |_, _| true, rng);
+        assert_eq!(pairs.map(|pairs| pairs.len()), Ok(3));
+    }
+}
@@ -0,0 +1,458 @@
+//! Drawing from randomness everyone chips in, so nobody has to trust the
+//! organizer's device.
+//!
+//! Each person contributes a value. The seed is the SHA-256 of one
+//! `name: value` line per contribution, sorted by name, and it seeds
+//! ChaCha20 for the draw. Given the contributions anyone can work the seed
+//! out again, and with the list, the draw too.
+//!
+//! In server mode contributions go through commit-then-reveal: everyone first
+//! sends the hash of their value, and values are only revealed once nobody
+//! else can join, so nobody can pick theirs after seeing the others'. Each
+//! pledge comes with a secret that only its sender gets, and revealing needs
+//! it, so nobody can reveal for anyone else.
+use crate::commitment::hex;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Contribution {
+    pub name: String,
+    pub value: String,
+}
+
+/// A seed made from everyone's contributions.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Seed {
+    /// Sorted by name.
+    pub contributions: Vec<Contribution>,
+    /// The SHA-256 of the contributions' lines, in hex.
+    pub hash: String,
+    /// Those who pledged but never revealed, so their values aren't in it.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub left_out: Vec<String>,
+    /// From a round closed before everyone revealed.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub tainted: bool,
+}
+
+impl Seed {
+    pub fn derive(mut contributions: Vec<Contribution>) -> Seed {
+        contributions.sort_by(|a, b| a.name.cmp(&b.name));
+        let hash = hex(&Sha256::digest(preimage(&contributions).as_bytes()));
+        Seed {
+            contributions,
+            hash,
+            left_out: Vec::new(),
+            tainted: false,
+        }
+    }
+
+    /// The random number generator to draw with, for `draw_with_rng` or
+    /// `assign_participants_with_rng`.
+    pub fn rng(&self) -> ChaCha20Rng {
+        let mut seed = [0u8; 32];
+        for (byte, pair) in seed.iter_mut().zip(self.hash.as_bytes().chunks(2)) {
+            *byte = std::str::from_utf8(pair).ok().and_then(|pair| u8::from_str_radix(pair, 16).ok()).unwrap_or(0);
+        }
+        ChaCha20Rng::from_seed(seed)
+    }
+
+    /// How the seed was made, step by step, for anyone who wants to check.
+    pub fn derivation(&self) -> String {
+        let mut derivation = format!(
+            "Everyone's contribution, one per line, sorted by name:\n\n{}\n\nSHA-256 of those lines, joined with newlines:\n{}\n\nThat hash, read as 32 bytes, seeds ChaCha20 for the draw.",
+            preimage(&self.contributions),
+            self.hash
+        );
+        if !self.left_out.is_empty() {
+            derivation.push_str(&format!(
+                "\n\nLeft out for not revealing in time: {}.",
+                self.left_out.join(", ")
+            ));
+        }
+        if self.tainted {
+            derivation.push_str("\n\nThe organizer closed reveals before everyone had revealed, so this seed doesn't rest on everyone's value.");
+        }
+        derivation
+    }
+}
+
+fn preimage(contributions: &[Contribution]) -> String {
+    contributions
+        .iter()
+        .map(|contribution| format!("{}: {}", contribution.name, contribution.value))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The hash of `value` that's sent before the value itself.
+pub fn commit(value: &str) -> String {
+    hex(&Sha256::digest(value.as_bytes()))
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, Serialize)]
+pub enum Phase {
+    /// Taking commitments. Nothing can be revealed yet.
+    #[default]
+    Committing,
+    /// Commitments are closed, and values can be revealed.
+    Revealing,
+    /// The organizer stopped waiting. Anyone who hasn't revealed is left
+    /// out of the seed.
+    Closed,
+}
+
+/// Someone's place in a round, and the token in the link they contribute
+/// through.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Seat {
+    pub name: String,
+    pub token: String,
+}
+
+/// One person's commitment, and their value once they reveal it.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Pledge {
+    pub name: String,
+    pub commitment: String,
+    /// The hash of the secret handed back when the pledge was made.
+    #[serde(default)]
+    pub secret: String,
+    #[serde(default)]
+    pub revealed: bool,
+    /// Kept from the organizer until everyone has revealed or the round is
+    /// closed; see `Round::redacted`.
+    pub value: Option<String>,
+}
+
+/// An event's commit-then-reveal round.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct Round {
+    /// Tells this round from any started before it, so a device can tell
+    /// whether the contribution it kept is still in play.
+    #[serde(default)]
+    pub id: String,
+    pub phase: Phase,
+    /// Who can contribute, each through their own link.
+    #[serde(default)]
+    pub seats: Vec<Seat>,
+    pub pledges: Vec<Pledge>,
+    /// Closed before everyone revealed, so the seed can't be fully trusted.
+    #[serde(default)]
+    pub tainted: bool,
+}
+
+/// Where a round stands, as the person with one of its links sees it.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct RoundStatus {
+    pub id: String,
+    pub phase: Phase,
+    /// Whose link it is.
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EntropyError {
+    /// The link isn't one of this round's.
+    NotTakingPart,
+    /// The link has already been used to contribute.
+    AlreadyCommitted,
+    /// Contributions are closed, or were never opened.
+    NotCollecting,
+    /// Values can only be revealed after contributions close and before
+    /// reveals do.
+    NotRevealing,
+    /// Revealing a value that was never committed to.
+    NoCommitment,
+    /// The value doesn't match what was committed to.
+    Mismatch,
+    /// The secret isn't the one handed back with the pledge.
+    NotYours,
+}
+
+impl fmt::Display for EntropyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EntropyError::NotTakingPart => write!(
+                f,
+                "This link isn't part of the current round. Ask your organizer for your own contribution link."
+            ),
+            EntropyError::AlreadyCommitted => write!(f, "Someone has already contributed through this link."),
+            EntropyError::NotCollecting => write!(f, "Contributions aren't being taken right now."),
+            EntropyError::NotRevealing => write!(f, "Contributions can't be revealed right now."),
+            EntropyError::NoCommitment => write!(f, "There's no contribution from you to reveal."),
+            EntropyError::Mismatch => write!(f, "That isn't the value you contributed."),
+            EntropyError::NotYours => write!(f, "That contribution was added from another device."),
+        }
+    }
+}
+
+impl std::error::Error for EntropyError {}
+
+impl Round {
+    /// A fresh round, taking commitments from each of `names` through a link
+    /// of their own. Names are only seated once, ignoring case.
+    pub fn new<'a, R: Rng + ?Sized>(names: impl IntoIterator<Item = &'a str>, rng: &mut R) -> Round {
+        let mut seats: Vec<Seat> = Vec::new();
+        for name in names.into_iter().map(str::trim).filter(|name| !name.is_empty()) {
+            if !seats.iter().any(|seat| seat.name.eq_ignore_ascii_case(name)) {
+                seats.push(Seat {
+                    name: name.to_string(),
+                    token: hex(&rng.gen::<[u8; 16]>()),
+                });
+            }
+        }
+        Round {
+            id: hex(&rng.gen::<[u8; 8]>()),
+            seats,
+            ..Round::default()
+        }
+    }
+
+    /// Whose link `token` is, if it's one of this round's.
+    pub fn seat(&self, token: &str) -> Option<&Seat> {
+        self.seats.iter().find(|seat| seat.token == token)
+    }
+
+    /// The round as the holder of `token` sees it, without anyone's pledges.
+    pub fn status(&self, token: &str) -> Option<RoundStatus> {
+        Some(RoundStatus {
+            id: self.id.clone(),
+            phase: self.phase,
+            name: self.seat(token)?.name.clone(),
+        })
+    }
+
+    /// Records the commitment sent through `token`'s link, if nothing has
+    /// been sent through it yet. Returns the secret needed to reveal it.
+    pub fn commit<R: Rng + ?Sized>(&mut self, token: &str, commitment: &str, rng: &mut R) -> Result<String, EntropyError> {
+        if self.phase != Phase::Committing {
+            return Err(EntropyError::NotCollecting);
+        }
+        let name = self.seat(token).ok_or(EntropyError::NotTakingPart)?.name.clone();
+        if self.pledge(&name).is_some() {
+            return Err(EntropyError::AlreadyCommitted);
+        }
+        let secret = hex(&rng.gen::<[u8; 16]>());
+        self.pledges.push(Pledge {
+            name,
+            commitment: commitment.trim().to_ascii_lowercase(),
+            secret: commit(&secret),
+            revealed: false,
+            value: None,
+        });
+        Ok(secret)
+    }
+
+    /// Records the value committed to through `token`'s link, if it's what
+    /// was committed to and `secret` is the one the pledge handed back.
+    pub fn reveal(&mut self, token: &str, value: &str, secret: &str) -> Result<(), EntropyError> {
+        if self.phase != Phase::Revealing {
+            return Err(EntropyError::NotRevealing);
+        }
+        let name = self.seat(token).ok_or(EntropyError::NotTakingPart)?.name.clone();
+        let index = self.pledge(&name).ok_or(EntropyError::NoCommitment)?;
+        let pledge = &mut self.pledges[index];
+        if commit(secret.trim()) != pledge.secret {
+            return Err(EntropyError::NotYours);
+        }
+        if commit(value) != pledge.commitment {
+            return Err(EntropyError::Mismatch);
+        }
+        pledge.value = Some(value.to_string());
+        pledge.revealed = true;
+        Ok(())
+    }
+
+    fn pledge(&self, name: &str) -> Option<usize> {
+        self.pledges.iter().position(|pledge| pledge.name == name)
+    }
+
+    pub fn revealed(&self) -> Vec<Contribution> {
+        self.pledges
+            .iter()
+            .filter_map(|pledge| {
+                Some(Contribution {
+                    name: pledge.name.clone(),
+                    value: pledge.value.clone()?,
+                })
+            })
+            .collect()
+    }
+
+    /// Stops waiting for reveals, leaving out whoever hasn't. The round is
+    /// marked tainted, since it no longer rests on everyone's value.
+    pub fn close(&mut self) -> Result<(), EntropyError> {
+        if self.phase != Phase::Revealing {
+            return Err(EntropyError::NotRevealing);
+        }
+        self.phase = Phase::Closed;
+        self.tainted = !self.unrevealed().is_empty();
+        Ok(())
+    }
+
+    /// Those who pledged but haven't revealed.
+    pub fn unrevealed(&self) -> Vec<String> {
+        self.pledges.iter().filter(|pledge| !pledge.revealed).map(|pledge| pledge.name.clone()).collect()
+    }
+
+    fn ready(&self) -> bool {
+        match self.phase {
+            Phase::Committing => false,
+            Phase::Revealing => self.unrevealed().is_empty(),
+            Phase::Closed => true,
+        }
+    }
+
+    /// The round without any revealed values until the seed is ready, for
+    /// the organizer. Otherwise they could work out the seed while some are
+    /// still to reveal, and choose whether to close the round on them.
+    pub fn redacted(&self) -> Round {
+        let mut round = self.clone();
+        if !self.ready() {
+            for pledge in &mut round.pledges {
+                pledge.value = None;
+            }
+        }
+        round
+    }
+
+    /// The seed to draw with, once everyone has revealed or the round is
+    /// closed. Otherwise whoever reveals last could choose whether to.
+    pub fn seed(&self) -> Option<Seed> {
+        let revealed = self.revealed();
+        (self.ready() && !revealed.is_empty()).then(|| Seed {
+            left_out: self.unrevealed(),
+            tainted: self.tainted,
+            ..Seed::derive(revealed)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Participant, SecretSatan};
+    use rand::RngCore;
+
+    fn contribution(name: &str, value: &str) -> Contribution {
+        Contribution {
+            name: name.to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    #[test]
+    fn the_seed_does_not_depend_on_order() {
+        let seed = Seed::derive(vec![contribution("Bob", "sleigh"), contribution("Alice", "tinsel")]);
+        let again = Seed::derive(vec![contribution("Alice", "tinsel"), contribution("Bob", "sleigh")]);
+        assert_eq!(seed, again);
+        assert_eq!(seed.rng().next_u64(), again.rng().next_u64());
+        // The same as `printf 'Alice: tinsel\nBob: sleigh' | sha256sum`.
+        assert_eq!(seed.hash, "026865346592e735adb59b462096ec6bd8965278fc251246f834e7f19a5e8544");
+        assert!(seed.derivation().contains("Alice: tinsel\nBob: sleigh"));
+
+        let other = Seed::derive(vec![contribution("Alice", "tinsel"), contribution("Bob", "sleigh!")]);
+        assert_ne!(other.hash, seed.hash);
+    }
+
+    #[test]
+    fn the_same_contributions_draw_the_same_list() {
+        let mut list = SecretSatan::new();
+        for name in ["Alice", "Bob", "Carol", "Dave", "Erin"] {
+            list.add_participant(Participant::new(name.to_string()));
+        }
+        let seed = Seed::derive(vec![contribution("Alice", "tinsel")]);
+        assert_eq!(list.draw_with_rng(&mut seed.rng()), list.draw_with_rng(&mut seed.rng()));
+        assert_eq!(
+            list.clone().assign_participants_with_rng(&mut seed.rng()),
+            list.assign_participants_with_rng(&mut seed.rng())
+        );
+    }
+
+    const NAMES: [&str; 3] = ["Alice", "Bob", "Carol"];
+
+    /// A round with everyone in `NAMES` seated, and their link tokens.
+    fn round() -> (Round, [String; 3]) {
+        let round = Round::new(NAMES, &mut rand::thread_rng());
+        let tokens = NAMES.map(|name| round.seats.iter().find(|seat| seat.name == name).unwrap().token.clone());
+        (round, tokens)
+    }
+
+    #[test]
+    fn values_are_revealed_only_after_commitments_close() {
+        let rng = &mut rand::thread_rng();
+        let (mut round, [alice, bob, carol]) = round();
+        let secret = round.commit(&alice, &commit("tinsel"), rng).unwrap();
+        round.commit(&bob, &commit("sleigh"), rng).unwrap();
+        assert_eq!(round.reveal(&alice, "tinsel", &secret), Err(EntropyError::NotRevealing));
+
+        round.phase = Phase::Revealing;
+        assert_eq!(round.commit(&carol, &commit("star"), rng), Err(EntropyError::NotCollecting));
+        assert_eq!(round.reveal(&alice, "holly", &secret), Err(EntropyError::Mismatch));
+        assert_eq!(round.reveal(&carol, "star", &secret), Err(EntropyError::NoCommitment));
+        round.reveal(&alice, "tinsel", &secret).unwrap();
+    }
+
+    #[test]
+    fn the_organizer_sees_no_values_until_the_seed_is_ready() {
+        let rng = &mut rand::thread_rng();
+        let (mut round, [alice, bob, _]) = round();
+        let alice_secret = round.commit(&alice, &commit("tinsel"), rng).unwrap();
+        let bob_secret = round.commit(&bob, &commit("sleigh"), rng).unwrap();
+        round.phase = Phase::Revealing;
+        round.reveal(&alice, "tinsel", &alice_secret).unwrap();
+
+        // Bob could still choose whether to reveal after seeing the result.
+        assert_eq!(round.seed(), None);
+        let redacted = round.redacted();
+        assert!(redacted.pledges.iter().all(|pledge| pledge.value.is_none()));
+        assert!(redacted.pledges[0].revealed);
+
+        round.reveal(&bob, "sleigh", &bob_secret).unwrap();
+        assert_eq!(round.redacted(), round);
+        assert!(!round.seed().unwrap().tainted);
+    }
+
+    #[test]
+    fn closing_early_leaves_people_out_and_taints_the_seed() {
+        let rng = &mut rand::thread_rng();
+        let (mut round, [alice, bob, _]) = round();
+        let secret = round.commit(&alice, &commit("tinsel"), rng).unwrap();
+        round.commit(&bob, &commit("sleigh"), rng).unwrap();
+        assert_eq!(round.close(), Err(EntropyError::NotRevealing));
+        round.phase = Phase::Revealing;
+        round.reveal(&alice, "tinsel", &secret).unwrap();
+
+        round.close().unwrap();
+        assert!(round.tainted);
+        let seed = round.seed().unwrap();
+        assert_eq!(seed.hash, Seed::derive(vec![contribution("Alice", "tinsel")]).hash);
+        assert_eq!(seed.left_out, vec!["Bob".to_string()]);
+        assert!(seed.tainted);
+        assert!(seed.derivation().contains("Left out for not revealing in time: Bob."));
+        assert_eq!(round.reveal(&bob, "sleigh", "late"), Err(EntropyError::NotRevealing));
+    }
+
+    #[test]
+    fn each_link_pledges_once_for_its_own_name() {
+        let rng = &mut rand::thread_rng();
+        let (mut round, [_, bob, _]) = round();
+        assert_eq!(Round::new(["Bob", "bob", " "], rng).seats.len(), 1);
+        assert_eq!(round.status(&bob).unwrap().name, "Bob");
+
+        let secret = round.commit(&bob, &commit("sleigh"), rng).unwrap();
+        assert_eq!(round.pledges[0].name, "Bob");
+        assert_eq!(round.commit(&bob, &commit("coal"), rng), Err(EntropyError::AlreadyCommitted));
+        assert_eq!(round.commit("guess", &commit("coal"), rng), Err(EntropyError::NotTakingPart));
+
+        // Knowing the value isn't enough to reveal it for someone else.
+        round.phase = Phase::Revealing;
+        assert_eq!(round.reveal(&bob, "sleigh", "guess"), Err(EntropyError::NotYours));
+        round.reveal(&bob, "sleigh", &secret).unwrap();
+    }
+}
@@ -0,0 +1,180 @@
+//! Undo and redo for edits to the list, and a record of every draw's
+//! commitment.
+use crate::commitment::Commitment;
+use crate::entropy::Seed;
+use crate::{Assignment, SecretSatan};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// How many edits can be undone. Every step is a whole copy of the list, and
+/// they're all saved, so this stays small.
+pub const HISTORY_LIMIT: usize = 20;
+
+/// Snapshots of the list from before each edit, and from before each undo.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct History {
+    undo: Vec<SecretSatan>,
+    redo: Vec<SecretSatan>,
+    /// One per draw, oldest first. Undo doesn't touch these, so a draw that
+    /// was thrown away is still on record.
+    commitments: Vec<Commitment>,
+}
+
+impl History {
+    /// Remembers the list as it was before an edit. Anything that had been
+    /// undone can't be redone after this.
+    pub fn record(&mut self, before: SecretSatan) {
+        if self.undo.last() == Some(&before) {
+            return;
+        }
+        self.undo.push(before);
+        if self.undo.len() > HISTORY_LIMIT {
+            self.undo.remove(0);
+        }
+        self.redo.clear();
+    }
+
+    /// Steps back from `current`, returning the list to show instead.
+    pub fn undo(&mut self, current: SecretSatan) -> Option<SecretSatan> {
+        let previous = self.undo.pop()?;
+        self.redo.push(current);
+        Some(previous)
+    }
+
+    /// Reapplies the last undone edit to `current`.
+    pub fn redo(&mut self, current: SecretSatan) -> Option<SecretSatan> {
+        let next = self.redo.pop()?;
+        self.undo.push(current);
+        Some(next)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Commits to a new draw's results, and the seed they were drawn with if
+    /// everyone contributed to it, returning the hash to publish.
+    pub fn commit<R: Rng + ?Sized>(&mut self, results: Vec<Assignment>, seed: Option<Seed>, rng: &mut R) -> String {
+        let commitment = Commitment::new(results, seed, rng);
+        let hash = commitment.hash.clone();
+        self.commitments.push(commitment);
+        hash
+    }
+
+    pub fn commitments(&self) -> &[Commitment] {
+        &self.commitments
+    }
+
+    /// The commitment to `results`, if they were drawn while it was being
+    /// recorded.
+    pub fn commitment_to(&self, results: &[Assignment]) -> Option<&Commitment> {
+        self.commitments.iter().rev().find(|commitment| commitment.opening.results == results)
+    }
+
+    /// Records that the commitment with `hash` was shared `at`, unless it
+    /// already had been. Returns whether there's such a commitment.
+    pub fn publish(&mut self, hash: &str, at: impl Into<String>) -> bool {
+        let Some(commitment) = self.commitments.iter_mut().find(|commitment| commitment.hash == hash) else {
+            return false;
+        };
+        commitment.published.get_or_insert_with(|| at.into());
+        true
+    }
+
+    /// Whether `results` can be shown: their commitment has been shared, or
+    /// they were drawn before draws were committed to.
+    pub fn can_show(&self, results: &[Assignment]) -> bool {
+        self.commitment_to(results).is_none_or(|commitment| commitment.published.is_some())
+    }
+
+    /// Marks the commitment with `hash` as opened, returning its opening, as
+    /// long as the hash itself was shared first.
+    pub fn reveal(&mut self, hash: &str) -> Option<String> {
+        let commitment = self.commitments.iter_mut().find(|commitment| commitment.hash == hash)?;
+        commitment.published.as_ref()?;
+        commitment.revealed = true;
+        Some(commitment.opening.to_text())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Participant;
+
+    fn with(names: &[&str]) -> SecretSatan {
+        let mut list = SecretSatan::new();
+        for name in names {
+            list.add_participant(Participant::new(name.to_string()));
+        }
+        list
+    }
+
+    #[test]
+    fn undo_and_redo_walk_through_edits() {
+        let mut history = History::default();
+        history.record(with(&[]));
+        history.record(with(&["Alice"]));
+        let current = with(&["Alice", "Bob"]);
+
+        let previous = history.undo(current.clone()).unwrap();
+        assert_eq!(previous, with(&["Alice"]));
+        assert_eq!(history.redo(previous).unwrap(), current);
+        assert!(!history.can_redo());
+    }
+
+    #[test]
+    fn a_new_edit_drops_the_redo_steps() {
+        let mut history = History::default();
+        history.record(with(&[]));
+        history.undo(with(&["Alice"]));
+        assert!(history.can_redo());
+
+        history.record(with(&[]));
+        assert!(!history.can_redo());
+    }
+
+    #[test]
+    fn draws_stay_on_record_through_undo() {
+        let mut history = History::default();
+        let results = vec![Assignment {
+            givers: vec!["Alice".to_string()],
+            receivers: vec!["Bob".to_string()],
+        }];
+        history.record(with(&[]));
+        let hash = history.commit(results.clone(), None, &mut rand::thread_rng());
+        history.undo(with(&["Alice"]));
+
+        assert_eq!(history.commitment_to(&results).unwrap().hash, hash);
+        assert!(!history.can_show(&results));
+        assert_eq!(history.reveal(&hash), None);
+
+        assert!(history.publish(&hash, "2024-12-01T18:00:00Z"));
+        history.publish(&hash, "later");
+        assert_eq!(history.commitments()[0].published.as_deref(), Some("2024-12-01T18:00:00Z"));
+        assert!(history.can_show(&results));
+        let opening = history.reveal(&hash).unwrap();
+        assert_eq!(crate::commitment::verify(&hash, &opening), Ok(results));
+        assert!(history.commitments()[0].revealed);
+    }
+
+    #[test]
+    fn only_the_most_recent_edits_are_kept() {
+        let mut history = History::default();
+        for n in 0..HISTORY_LIMIT + 5 {
+            history.record(with(&[&n.to_string()]));
+        }
+        let mut undone = 0;
+        let mut current = SecretSatan::new();
+        while let Some(previous) = history.undo(current.clone()) {
+            current = previous;
+            undone += 1;
+        }
+        assert_eq!(undone, HISTORY_LIMIT);
+        assert_eq!(current, with(&["5"]));
+    }
+}
@@ -0,0 +1,1058 @@
+//! The draw engine behind Secret Satan: participants, exclusions, and the
+//! solver that pairs them up. It has no browser dependencies, so it can run
+//! anywhere Rust does.
+pub mod commitment;
+pub mod csv;
+pub mod delivery;
+mod draw;
+pub mod entropy;
+mod history;
+pub mod merge;
+pub mod reveal;
+mod schema;
+pub mod signup;
+pub mod transfer;
+pub mod version;
+
+pub use history::{History, HISTORY_LIMIT};
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+
+/// How many times `plan_rotation` starts over looking for a plan with fewer repeats.
+const ROTATION_ATTEMPTS: usize = 50;
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct SecretSatan {
+    pub participants: Vec<Participant>,
+    #[serde(default)]
+    pub units: Vec<Unit>,
+    #[serde(default)]
+    pub mode: DrawMode,
+    #[serde(default)]
+    pub rotation: Option<Rotation>,
+    /// The most recent gift giving list.
+    #[serde(default)]
+    pub results: Vec<Assignment>,
+    /// Givers who have already seen their result on this device.
+    #[serde(default)]
+    pub viewed: Vec<String>,
+    /// How much to spend, as the organizer wrote it, e.g. "$25".
+    #[serde(default)]
+    pub budget: Option<String>,
+    /// When gifts are exchanged, as the organizer wrote it.
+    #[serde(default)]
+    pub date: Option<String>,
+}
+
+impl SecretSatan {
+    pub fn new() -> SecretSatan {
+        SecretSatan {
+            participants: Vec::new(),
+            units: Vec::new(),
+            mode: DrawMode::OneToOne,
+            rotation: None,
+            results: Vec::new(),
+            viewed: Vec::new(),
+            budget: None,
+            date: None,
+        }
+    }
+
+    /// Replaces the gift giving list. Nobody has seen the new one yet.
+    pub fn set_results(&mut self, results: Vec<Assignment>) {
+        self.results = results;
+        self.viewed.clear();
+    }
+
+    /// Everyone giving a gift in the latest results, in list order.
+    pub fn result_givers(&self) -> Vec<String> {
+        self.results.iter().flat_map(|assignment| assignment.givers.iter().cloned()).collect()
+    }
+
+    /// Records that `name` has seen who they're giving to.
+    pub fn mark_viewed(&mut self, name: &str) {
+        if !self.has_viewed(name) {
+            self.viewed.push(name.to_string());
+        }
+    }
+
+    pub fn has_viewed(&self, name: &str) -> bool {
+        self.viewed.iter().any(|viewed| viewed == name)
+    }
+
+    /// Whether every giver has seen their result.
+    pub fn everyone_viewed(&self) -> bool {
+        !self.results.is_empty() && self.result_givers().iter().all(|giver| self.has_viewed(giver))
+    }
+
+    pub fn add_participant(&mut self, participant: Participant) {
+        self.participants.push(participant);
+    }
+
+    pub fn remove_participant(&mut self, participant: &Participant) {
+        self.participants.retain(|p| p != participant);
+        self.leave_unit(&participant.name);
+    }
+
+    /// Returns the unit the named participant belongs to, if any.
+    pub fn unit_of(&self, name: &str) -> Option<&Unit> {
+        self.units.iter().find(|unit| unit.contains(name))
+    }
+
+    /// Puts `name` in the same unit as `other`, creating the unit if neither is in one yet.
+    pub fn join_unit(&mut self, name: &str, other: &str) {
+        if name == other {
+            return;
+        }
+        if let Some(unit) = self.units.iter().position(|unit| unit.contains(other)) {
+            if self.units[unit].contains(name) {
+                return;
+            }
+            self.leave_unit(name);
+            let unit = self.units.iter_mut().find(|unit| unit.contains(other)).unwrap();
+            unit.members.push(name.to_string());
+        } else if let Some(unit) = self.units.iter_mut().find(|unit| unit.contains(name)) {
+            unit.members.push(other.to_string());
+        } else {
+            self.units.push(Unit::new(vec![name.to_string(), other.to_string()]));
+        }
+    }
+
+    /// Takes `name` out of their unit. A unit left with one member is dissolved.
+    pub fn leave_unit(&mut self, name: &str) {
+        for unit in self.units.iter_mut() {
+            unit.members.retain(|member| member != name);
+        }
+        self.units.retain(|unit| unit.members.len() > 1);
+    }
+
+    pub fn assign_participants(self) -> Result<Vec<Participant>, SecretSatanError> {
+        self.assign_participants_with_rng(&mut rand::thread_rng())
+    }
+
+    /// Same as `assign_participants`, but draws from the given random number generator.
+    pub fn assign_participants_with_rng<R: Rng + ?Sized>(
+        self,
+        rng: &mut R,
+    ) -> Result<Vec<Participant>, SecretSatanError> {
+        let assignments = self.draw_with_rng(rng)?;
+
+        let mut participants = self.participants;
+        // Start from a clean slate in case a previous draw was saved.
+        for participant in participants.iter_mut() {
+            participant.giving_to = None;
+            participant.receiving_from = None;
+            participant.drawn = false;
+        }
+
+        for assignment in assignments {
+            for participant in participants.iter_mut() {
+                if assignment.givers.contains(&participant.name) {
+                    participant.giving_to = Some(assignment.receiver_label());
+                }
+                if assignment.receivers.contains(&participant.name) {
+                    participant.receiving_from = Some(assignment.giver_label());
+                    participant.drawn = true;
+                }
+            }
+        }
+
+        Ok(participants)
+    }
+
+    /// Draws the gift giving list, with each unit giving and receiving as one.
+    pub fn draw(&self) -> Result<Vec<Assignment>, SecretSatanError> {
+        self.draw_with_rng(&mut rand::thread_rng())
+    }
+
+    /// Same as `draw`, but draws from the given random number generator.
+    pub fn draw_with_rng<R: Rng + ?Sized>(&self, rng: &mut R) -> Result<Vec<Assignment>, SecretSatanError> {
+        self.draw_avoiding(&HashSet::new(), rng)
+    }
+
+    /// Plans `years` draws in advance, starting with `first_year`, so that
+    /// nobody gives to the same person twice for as long as possible.
+    pub fn plan_rotation(&self, first_year: i32, years: usize) -> Result<Rotation, SecretSatanError> {
+        self.plan_rotation_with_rng(first_year, years, &mut rand::thread_rng())
+    }
+
+    /// Same as `plan_rotation`, but draws from the given random number generator.
+    pub fn plan_rotation_with_rng<R: Rng + ?Sized>(
+        &self,
+        first_year: i32,
+        years: usize,
+        rng: &mut R,
+    ) -> Result<Rotation, SecretSatanError> {
+        // Each year is drawn greedily, so an early year can box the later ones
+        // in. A few fresh attempts usually find a plan without repeats.
+        let mut best: Option<Rotation> = None;
+        for _ in 0..ROTATION_ATTEMPTS {
+            let mut used = HashSet::new();
+            let mut draws = Vec::with_capacity(years);
+            for _ in 0..years {
+                let assignments = self.draw_avoiding(&used, rng)?;
+                used.extend(assignments.iter().flat_map(Assignment::pairs));
+                draws.push(assignments);
+            }
+
+            let rotation = Rotation {
+                first_year,
+                years: draws,
+                next: 0,
+            };
+            if best.as_ref().is_none_or(|best| rotation.repeats() < best.repeats()) {
+                best = Some(rotation);
+            }
+            if best.as_ref().is_some_and(|best| best.repeats() == 0) {
+                break;
+            }
+        }
+
+        Ok(best.unwrap_or_default())
+    }
+
+    /// Hands out the rotation's next year, as long as it still fits the list.
+    /// Nothing is handed out, and the plan doesn't move on, when someone in it
+    /// has left or been renamed, someone new isn't in it, or it breaks an
+    /// exclusion or unit added since.
+    pub fn take_planned_year(&mut self) -> Result<Option<(i32, Vec<Assignment>)>, SecretSatanError> {
+        let Some(rotation) = &self.rotation else {
+            return Ok(None);
+        };
+        let Some(next) = rotation.years.get(rotation.next) else {
+            return Ok(None);
+        };
+        if !self.fits(next) {
+            return Err(SecretSatanError::RotationOutOfDate);
+        }
+        Ok(self.rotation.as_mut().and_then(Rotation::take_next))
+    }
+
+    /// Whether `assignments` are a draw of the list as it is now: everyone in
+    /// them is on it, everyone who gives or receives does so exactly once,
+    /// and nobody gives to someone they exclude or share a unit with.
+    pub fn fits(&self, assignments: &[Assignment]) -> bool {
+        let index = |name: &String| self.participants.iter().position(|participant| &participant.name == name);
+        let mut node_of = vec![0; self.participants.len()];
+        for (n, node) in self.nodes().iter().enumerate() {
+            for &i in node {
+                node_of[i] = n;
+            }
+        }
+        let mut gives = vec![0usize; self.participants.len()];
+        let mut receives = vec![0usize; self.participants.len()];
+        for assignment in assignments {
+            let (Some(givers), Some(receivers)) = (
+                assignment.givers.iter().map(index).collect::<Option<Vec<usize>>>(),
+                assignment.receivers.iter().map(index).collect::<Option<Vec<usize>>>(),
+            ) else {
+                return false;
+            };
+            let same_unit = givers.iter().any(|&g| receivers.iter().any(|&r| node_of[g] == node_of[r]));
+            if same_unit || !self.node_can_give_to(&givers, &receivers) {
+                return false;
+            }
+            givers.iter().for_each(|&g| gives[g] += 1);
+            receivers.iter().for_each(|&r| receives[r] += 1);
+        }
+        // Someone who only receives can still be listed with their unit's givers.
+        self.participants.iter().enumerate().all(|(i, participant)| {
+            gives[i] <= 1
+                && receives[i] <= 1
+                && (!participant.role.gives() || gives[i] == 1)
+                && (!participant.role.receives() || receives[i] == 1)
+        })
+    }
+
+    /// Draws the list, using giver→recipient pairs in `used` only when
+    /// there's no way around it.
+    fn draw_avoiding<R: Rng + ?Sized>(
+        &self,
+        used: &HashSet<(String, String)>,
+        rng: &mut R,
+    ) -> Result<Vec<Assignment>, SecretSatanError> {
+        let nodes = self.nodes();
+
+        let givers: Vec<usize> = (0..nodes.len())
+            .filter(|&n| nodes[n].iter().any(|&i| self.participants[i].role.gives()))
+            .collect();
+        let receivers: Vec<usize> = (0..nodes.len())
+            .filter(|&n| nodes[n].iter().any(|&i| self.participants[i].role.receives()))
+            .collect();
+        let names = |node: &[usize]| -> Vec<String> { node.iter().map(|&i| self.participants[i].name.clone()).collect() };
+        let allowed = |g: usize, r: usize| self.node_can_give_to(&nodes[g], &nodes[r]);
+        let repeated = |g: usize, r: usize| {
+            nodes[g].iter().any(|&a| {
+                nodes[r].iter().any(|&b| {
+                    used.contains(&(self.participants[a].name.clone(), self.participants[b].name.clone()))
+                })
+            })
+        };
+
+        match self.mode {
+            DrawMode::OneToOne => {
+                if givers.len() != receivers.len() {
+                    return Err(SecretSatanError::UnbalancedRoles {
+                        givers: givers.len(),
+                        receivers: receivers.len(),
+                    });
+                }
+
+                let pairs = draw::one_to_one(&givers, &receivers, allowed, repeated, rng).map_err(|unmatched| match unmatched {
+                    draw::Unmatched::Impossible => SecretSatanError::PairingFailed,
+                    draw::Unmatched::GaveUp => SecretSatanError::PairingGaveUp,
+                })?;
+
+                Ok(pairs
+                    .into_iter()
+                    .map(|(g, r)| Assignment {
+                        givers: names(&nodes[g]),
+                        receivers: names(&nodes[r]),
+                    })
+                    .collect())
+            }
+            DrawMode::GroupGift { min_team_size, max_team_size } => {
+                // Nodes, so each unit counts once towards a team's size.
+                if receivers.is_empty()
+                    || givers.len() / receivers.len() < min_team_size.max(1)
+                    || givers.len().div_ceil(receivers.len()) > max_team_size
+                {
+                    return Err(SecretSatanError::TeamSizeOutOfRange {
+                        givers: givers.len(),
+                        receivers: receivers.len(),
+                        min_team_size,
+                        max_team_size,
+                    });
+                }
+
+                // Try without any repeats first, then fall back to allowing them.
+                let teams = draw::teams(&givers, &receivers, |g, r| allowed(g, r) && !repeated(g, r), rng)
+                    .or_else(|| draw::teams(&givers, &receivers, allowed, rng))
+                    .ok_or(SecretSatanError::PairingFailed)?;
+
+                Ok(teams
+                    .into_iter()
+                    .map(|(team, r)| Assignment {
+                        givers: team.iter().flat_map(|&g| names(&nodes[g])).collect(),
+                        receivers: names(&nodes[r]),
+                    })
+                    .collect())
+            }
+        }
+    }
+
+    /// Groups participants into the nodes the draw works on: one per unit,
+    /// plus one for everyone who isn't in a unit.
+    fn nodes(&self) -> Vec<Vec<usize>> {
+        let mut placed = vec![false; self.participants.len()];
+        let mut nodes = Vec::new();
+        for unit in &self.units {
+            let members: Vec<usize> = unit
+                .members
+                .iter()
+                .filter_map(|name| self.participants.iter().position(|p| &p.name == name))
+                .filter(|&i| !placed[i])
+                .collect();
+            if members.is_empty() {
+                continue;
+            }
+            for &i in &members {
+                placed[i] = true;
+            }
+            nodes.push(members);
+        }
+        for (i, placed) in placed.into_iter().enumerate() {
+            if !placed {
+                nodes.push(vec![i]);
+            }
+        }
+        nodes
+    }
+
+    /// Whether one node may give to another. An exclusion on any member
+    /// applies to the whole unit.
+    fn node_can_give_to(&self, giver: &[usize], recipient: &[usize]) -> bool {
+        giver.iter().all(|&g| {
+            recipient.iter().all(|&r| {
+                let giver = &self.participants[g];
+                let recipient = &self.participants[r];
+                giver.name != recipient.name && !giver.excluding.contains(&recipient.name)
+            })
+        })
+    }
+}
+
+/// How gifts are handed out.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum DrawMode {
+    /// Everyone who gives buys one gift for one person.
+    #[default]
+    OneToOne,
+    /// Givers pool together into teams, and each team buys one gift for one
+    /// person. Team sizes differ by at most one, and count units rather than
+    /// people: a couple already gives as one, so they take a single place.
+    GroupGift { min_team_size: usize, max_team_size: usize },
+}
+
+/// Participants who give one gift together and receive one gift together,
+/// like a couple. Members are never drawn for each other.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct Unit {
+    pub members: Vec<String>,
+}
+
+impl Unit {
+    pub fn new(members: Vec<String>) -> Unit {
+        Unit { members }
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.members.iter().any(|member| member == name)
+    }
+
+    /// The unit's name for display, like "Ann & Tom".
+    pub fn label(&self) -> String {
+        self.members.join(" & ")
+    }
+}
+
+/// One line of the gift giving list: who is giving, and who they're giving to.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct Assignment {
+    pub givers: Vec<String>,
+    pub receivers: Vec<String>,
+}
+
+impl Assignment {
+    pub fn giver_label(&self) -> String {
+        self.givers.join(" & ")
+    }
+
+    pub fn receiver_label(&self) -> String {
+        self.receivers.join(" & ")
+    }
+
+    /// Every giver→recipient pair in this line of the list.
+    pub fn pairs(&self) -> impl Iterator<Item = (String, String)> + '_ {
+        self.givers
+            .iter()
+            .flat_map(|giver| self.receivers.iter().map(move |receiver| (giver.clone(), receiver.clone())))
+    }
+}
+
+/// A series of yearly draws planned ahead of time. Years are handed out one
+/// at a time with `take_next`.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct Rotation {
+    pub first_year: i32,
+    pub years: Vec<Vec<Assignment>>,
+    /// Index into `years` of the next draw to hand out.
+    pub next: usize,
+}
+
+impl Rotation {
+    /// The calendar year of the next draw, if any are left.
+    pub fn next_year(&self) -> Option<i32> {
+        (self.next < self.years.len()).then(|| self.first_year + self.next as i32)
+    }
+
+    /// Hands out the next year's draw and moves on to the one after.
+    pub fn take_next(&mut self) -> Option<(i32, Vec<Assignment>)> {
+        let year = self.next_year()?;
+        let assignments = self.years[self.next].clone();
+        self.next += 1;
+        Some((year, assignments))
+    }
+
+    /// How many giver→recipient pairs show up in more than one year.
+    pub fn repeats(&self) -> usize {
+        let mut seen = HashSet::new();
+        self.years
+            .iter()
+            .flatten()
+            .flat_map(Assignment::pairs)
+            .filter(|pair| !seen.insert(pair.clone()))
+            .count()
+    }
+}
+
+/// Which side of the exchange a participant takes part in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Role {
+    /// Gives one gift and receives one gift.
+    #[default]
+    Both,
+    /// Sponsors a gift but doesn't get one back.
+    GiverOnly,
+    /// Gets a gift but doesn't give one.
+    ReceiverOnly,
+}
+
+impl Role {
+    pub fn gives(&self) -> bool {
+        !matches!(self, Role::ReceiverOnly)
+    }
+
+    pub fn receives(&self) -> bool {
+        !matches!(self, Role::GiverOnly)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct Participant {
+    pub name: String,
+    pub giving_to: Option<String>,
+    pub receiving_from: Option<String>,
+    pub excluding: Vec<String>,
+    pub drawn: bool,
+    #[serde(default)]
+    pub role: Role,
+    #[serde(default)]
+    pub email: Option<String>,
+    /// Free-form labels like a household or team, used to sort and filter.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Gift ideas, in the participant's own words.
+    #[serde(default)]
+    pub wishlist: Option<String>,
+}
+
+impl Participant {
+    pub fn new(name: String) -> Participant {
+        Participant {
+            name,
+            giving_to: None,
+            receiving_from: None,
+            excluding: Vec::new(),
+            drawn: false,
+            role: Role::Both,
+            email: None,
+            tags: Vec::new(),
+            wishlist: None,
+        }
+    }
+
+    pub fn validate_giving_to(&self, recipient: &Participant) -> Result<(), SecretSatanError> {
+        // Cannot give to yourself
+        if self.name == recipient.name {
+            return Err(SecretSatanError::ParticipantCannotGiveToThemself);
+        }
+        // Cannot give if you only receive
+        if !self.role.gives() {
+            return Err(SecretSatanError::ParticipantDoesNotGive);
+        }
+        // Cannot give to someone who only gives
+        if !recipient.role.receives() {
+            return Err(SecretSatanError::ParticipantDoesNotReceive);
+        }
+        // Cannot give twice
+        if self.giving_to.is_some() {
+            return Err(SecretSatanError::ParticipantAlreadyGivingToSomeone);
+        }
+        // Cannot give to someone who is already receiving a gift
+        if recipient.receiving_from.is_some() {
+            return Err(SecretSatanError::ParticipantAlreadyReceivingFromSomeone);
+        }
+        // Cannot give to someone who is giving to you
+        if self.receiving_from == Some(recipient.name.clone()) {
+            return Err(SecretSatanError::ParticipantCannotGiveToSomeoneTheyAreReceivingFrom);
+        }
+        // Cannot give to someone you've excluded
+        if self.excluding.contains(&recipient.name) {
+            return Err(SecretSatanError::ParticipantCannotGiveToSomeoneTheyAreExcluding);
+        }
+        // Cannot give to someone who has already been drawn
+        if recipient.drawn {
+            return Err(SecretSatanError::ParticipantAlreadyDrawn);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SecretSatanError {
+    ParticipantAlreadyDrawn,
+    ParticipantDoesNotExist,
+    ParticipantAlreadyGivingToSomeone,
+    ParticipantAlreadyReceivingFromSomeone,
+    ParticipantCannotGiveToThemself,
+    ParticipantCannotReceiveFromThemself,
+    ParticipantCannotGiveToSomeoneTheyAreReceivingFrom,
+    ParticipantCannotReceiveFromSomeoneTheyAreGivingTo,
+    ParticipantCannotGiveToSomeoneTheyAreExcluding,
+    ParticipantCannotReceiveFromSomeoneTheyAreExcluding,
+    ParticipantDoesNotGive,
+    ParticipantDoesNotReceive,
+    UnbalancedRoles { givers: usize, receivers: usize },
+    TeamSizeOutOfRange { givers: usize, receivers: usize, min_team_size: usize, max_team_size: usize },
+    PairingFailed,
+    /// No pairing was found in time, though there may be one.
+    PairingGaveUp,
+    /// The list changed after the rotation was planned.
+    RotationOutOfDate,
+}
+
+impl fmt::Display for SecretSatanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SecretSatanError::ParticipantAlreadyDrawn => write!(f, "That participant has already been drawn."),
+            SecretSatanError::ParticipantDoesNotExist => write!(f, "That participant isn't on the list."),
+            SecretSatanError::ParticipantAlreadyGivingToSomeone => write!(f, "That participant is already giving to someone."),
+            SecretSatanError::ParticipantAlreadyReceivingFromSomeone => write!(f, "That participant is already receiving from someone."),
+            SecretSatanError::ParticipantCannotGiveToThemself => write!(f, "Participants can't give to themselves."),
+            SecretSatanError::ParticipantCannotReceiveFromThemself => write!(f, "Participants can't receive from themselves."),
+            SecretSatanError::ParticipantCannotGiveToSomeoneTheyAreReceivingFrom => write!(f, "Participants can't give to someone who is giving to them."),
+            SecretSatanError::ParticipantCannotReceiveFromSomeoneTheyAreGivingTo => write!(f, "Participants can't receive from someone they are giving to."),
+            SecretSatanError::ParticipantCannotGiveToSomeoneTheyAreExcluding => write!(f, "Participants can't give to someone they've excluded."),
+            SecretSatanError::ParticipantCannotReceiveFromSomeoneTheyAreExcluding => write!(f, "Participants can't receive from someone they've excluded."),
+            SecretSatanError::ParticipantDoesNotGive => write!(f, "That participant only receives gifts."),
+            SecretSatanError::ParticipantDoesNotReceive => write!(f, "That participant only gives gifts."),
+            SecretSatanError::UnbalancedRoles { givers, receivers } => write!(
+                f,
+                "There are {givers} people giving gifts but {receivers} people receiving them. Those numbers need to match."
+            ),
+            SecretSatanError::TeamSizeOutOfRange { givers, receivers, min_team_size, max_team_size } => write!(
+                f,
+                "Splitting {givers} givers between {receivers} people receiving gifts doesn't make teams of {min_team_size} to {max_team_size}. Each unit counts as one giver."
+            ),
+            SecretSatanError::PairingFailed => write!(f, "There's no way to pair everyone up with these exclusions."),
+            SecretSatanError::PairingGaveUp => write!(f, "Couldn't find a way to pair everyone up in time. Try drawing again."),
+            SecretSatanError::RotationOutOfDate => write!(f, "The list has changed since this plan was made. Plan the rotation again."),
+        }
+    }
+}
+
+impl std::error::Error for SecretSatanError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn secret_satan_new() {
+        let secret_satan = SecretSatan::new();
+        assert_eq!(secret_satan.participants.len(), 0);
+    }
+
+    #[test]
+    fn secret_satan_add_participant() {
+        let mut secret_satan = SecretSatan::new();
+        let participant = Participant::new("Alice".to_string());
+        secret_satan.add_participant(participant.clone());
+        assert_eq!(secret_satan.participants.len(), 1);
+    }
+
+    #[test]
+    fn participant_new() {
+        let participant = Participant::new("Alice".to_string());
+        assert_eq!(participant.name, "Alice");
+        assert_eq!(participant.giving_to, None);
+        assert_eq!(participant.receiving_from, None);
+        assert_eq!(participant.excluding.len(), 0);
+        assert!(!participant.drawn);
+        assert_eq!(participant.role, Role::Both);
+    }
+
+    #[test]
+    fn participant_cannot_give_to_self() {
+        let participant = Participant::new("Alice".to_string());
+        let recipient = Participant::new("Alice".to_string());
+        let result = participant.validate_giving_to(&recipient);
+        assert_eq!(result, Err(SecretSatanError::ParticipantCannotGiveToThemself));
+    }
+
+    #[test]
+    fn participant_cannot_give_twice() {
+        let mut participant = Participant::new("Alice".to_string());
+        let recipient = Participant::new("Bob".to_string());
+        participant.giving_to = Some(recipient.clone().name);
+        let result = participant.validate_giving_to(&recipient);
+        assert_eq!(result, Err(SecretSatanError::ParticipantAlreadyGivingToSomeone));
+    }
+
+    #[test]
+    fn participant_cannot_give_to_someone_already_receiving() {
+        let participant = Participant::new("Alice".to_string());
+        let mut recipient = Participant::new("Bob".to_string());
+        recipient.receiving_from = Some("Alice".to_string());
+        let result = participant.validate_giving_to(&recipient);
+        assert_eq!(result, Err(SecretSatanError::ParticipantAlreadyReceivingFromSomeone));
+    }
+
+    #[test]
+    fn participant_cannot_give_to_someone_giving_to_them() {
+        let mut participant = Participant::new("Alice".to_string());
+        let recipient = Participant::new("Bob".to_string());
+        participant.receiving_from = Some("Bob".to_string());
+        let result = participant.validate_giving_to(&recipient);
+        assert_eq!(result, Err(SecretSatanError::ParticipantCannotGiveToSomeoneTheyAreReceivingFrom));
+    }
+
+    #[test]
+    fn participant_cannot_give_to_someone_they_have_excluded() {
+        let mut participant = Participant::new("Alice".to_string());
+        let recipient = Participant::new("Bob".to_string());
+        participant.excluding.push("Bob".to_string());
+        let result = participant.validate_giving_to(&recipient);
+        assert_eq!(result, Err(SecretSatanError::ParticipantCannotGiveToSomeoneTheyAreExcluding));
+    }
+
+    #[test]
+    fn participant_cannot_give_to_someone_already_drawn() {
+        let participant = Participant::new("Alice".to_string());
+        let mut recipient = Participant::new("Bob".to_string());
+        recipient.drawn = true;
+        let result = participant.validate_giving_to(&recipient);
+        assert_eq!(result, Err(SecretSatanError::ParticipantAlreadyDrawn));
+    }
+
+    #[test]
+    fn three_participants_can_give_correctly() {
+        let alice = Participant::new("Alice".to_string());
+        let bob = Participant::new("Bob".to_string());
+        let charlie = Participant::new("Charlie".to_string());
+
+        let session = SecretSatan {
+            participants: vec![alice.clone(), bob.clone(), charlie.clone()],
+            ..SecretSatan::default()
+        };
+        let result = session.assign_participants();
+        if let Err(e) = result {
+            panic!("Error: {:?}", e);
+        }
+        let givers = result.ok().unwrap();
+        assert_eq!(givers.len(), 3);
+        assert_ne!(givers[0].giving_to, None);
+        assert_ne!(givers[0].giving_to, Some(givers[0].clone().name));
+        assert_ne!(givers[1].giving_to, None);
+        assert_ne!(givers[1].giving_to, Some(givers[1].clone().name));
+        assert_ne!(givers[2].giving_to, None);
+        assert_ne!(givers[2].giving_to, Some(givers[2].clone().name));
+    }
+
+    #[test]
+    fn five_participants_can_give_correctly() {
+        let alice = Participant::new("Alice".to_string());
+        let bob = Participant::new("Bob".to_string());
+        let charlie = Participant::new("Charlie".to_string());
+        let david = Participant::new("David".to_string());
+        let eve = Participant::new("Eve".to_string());
+
+        let session = SecretSatan {
+            participants: vec![alice.clone(), bob.clone(), charlie.clone(), david.clone(), eve.clone()],
+            ..SecretSatan::default()
+        };
+        let result = session.assign_participants();
+        if let Err(e) = result {
+            panic!("Error: {:?}", e);
+        }
+        let givers = result.ok().unwrap();
+        assert_eq!(givers.len(), 5);
+        assert_ne!(givers[0].giving_to, None);
+        assert_ne!(givers[0].giving_to, Some(givers[0].clone().name));
+        assert_ne!(givers[1].giving_to, None);
+        assert_ne!(givers[1].giving_to, Some(givers[1].clone().name));
+        assert_ne!(givers[2].giving_to, None);
+        assert_ne!(givers[2].giving_to, Some(givers[2].clone().name));
+        assert_ne!(givers[3].giving_to, None);
+        assert_ne!(givers[3].giving_to, Some(givers[3].clone().name));
+        assert_ne!(givers[4].giving_to, None);
+        assert_ne!(givers[4].giving_to, Some(givers[4].clone().name));
+    }
+
+    #[test]
+    fn exclusions_are_accounted_for() {
+        let mut alice = Participant::new("Alice".to_string());
+        alice.excluding.push("Bob".to_string());
+        let bob = Participant::new("Bob".to_string());
+        let charlie = Participant::new("Charlie".to_string());
+
+        let session = SecretSatan {
+            participants: vec![alice.clone(), bob.clone(), charlie.clone()],
+            ..SecretSatan::default()
+        };
+
+        let result = session.assign_participants();
+        if let Err(e) = result {
+            panic!("Error: {:?}", e);
+        }
+        let givers = result.ok().unwrap();
+        assert_eq!(givers.len(), 3);
+        assert_ne!(givers[0].giving_to, None);
+        assert_ne!(givers[0].giving_to, Some(givers[0].clone().name));
+        assert_ne!(givers[0].giving_to, Some(givers[0].excluding.first().unwrap().clone()));
+        assert_ne!(givers[1].giving_to, None);
+        assert_ne!(givers[1].giving_to, Some(givers[1].clone().name));
+        assert_ne!(givers[2].giving_to, None);
+        assert_ne!(givers[2].giving_to, Some(givers[2].clone().name));
+    }
+
+    #[test]
+    fn participant_cannot_give_if_they_only_receive() {
+        let mut participant = Participant::new("Alice".to_string());
+        participant.role = Role::ReceiverOnly;
+        let recipient = Participant::new("Bob".to_string());
+        let result = participant.validate_giving_to(&recipient);
+        assert_eq!(result, Err(SecretSatanError::ParticipantDoesNotGive));
+    }
+
+    #[test]
+    fn participant_cannot_give_to_someone_who_only_gives() {
+        let participant = Participant::new("Alice".to_string());
+        let mut recipient = Participant::new("Bob".to_string());
+        recipient.role = Role::GiverOnly;
+        let result = participant.validate_giving_to(&recipient);
+        assert_eq!(result, Err(SecretSatanError::ParticipantDoesNotReceive));
+    }
+
+    #[test]
+    fn givers_only_and_receivers_only_are_matched() {
+        let mut grandma = Participant::new("Grandma".to_string());
+        grandma.role = Role::GiverOnly;
+        let mut toddler = Participant::new("Toddler".to_string());
+        toddler.role = Role::ReceiverOnly;
+        let alice = Participant::new("Alice".to_string());
+        let bob = Participant::new("Bob".to_string());
+        let charlie = Participant::new("Charlie".to_string());
+
+        let session = SecretSatan {
+            participants: vec![grandma, toddler, alice, bob, charlie],
+            ..SecretSatan::default()
+        };
+        let givers = session.assign_participants().unwrap();
+        assert_eq!(givers.len(), 5);
+        assert_ne!(givers[0].giving_to, None);
+        assert_eq!(givers[0].receiving_from, None);
+        assert_eq!(givers[1].giving_to, None);
+        assert_ne!(givers[1].receiving_from, None);
+        for giver in givers.iter().filter(|p| p.role == Role::Both) {
+            assert_ne!(giver.giving_to, None);
+            assert_ne!(giver.receiving_from, None);
+        }
+    }
+
+    #[test]
+    fn unbalanced_roles_are_reported() {
+        let mut grandma = Participant::new("Grandma".to_string());
+        grandma.role = Role::GiverOnly;
+        let alice = Participant::new("Alice".to_string());
+        let bob = Participant::new("Bob".to_string());
+        let charlie = Participant::new("Charlie".to_string());
+
+        let session = SecretSatan {
+            participants: vec![grandma, alice, bob, charlie],
+            ..SecretSatan::default()
+        };
+        let result = session.assign_participants();
+        assert_eq!(result, Err(SecretSatanError::UnbalancedRoles { givers: 4, receivers: 3 }));
+    }
+
+    #[test]
+    fn impossible_exclusions_are_reported() {
+        let mut alice = Participant::new("Alice".to_string());
+        alice.excluding = vec!["Bob".to_string(), "Charlie".to_string()];
+        let bob = Participant::new("Bob".to_string());
+        let charlie = Participant::new("Charlie".to_string());
+
+        let session = SecretSatan {
+            participants: vec![alice, bob, charlie],
+            ..SecretSatan::default()
+        };
+        let result = session.assign_participants();
+        assert_eq!(result, Err(SecretSatanError::PairingFailed));
+    }
+
+    #[test]
+    fn join_unit_groups_participants() {
+        let mut secret_satan = SecretSatan::new();
+        secret_satan.join_unit("Ann", "Tom");
+        secret_satan.join_unit("Sue", "Tom");
+        assert_eq!(secret_satan.units, vec![Unit::new(vec!["Ann".to_string(), "Tom".to_string(), "Sue".to_string()])]);
+
+        secret_satan.leave_unit("Tom");
+        secret_satan.leave_unit("Sue");
+        assert!(secret_satan.units.is_empty());
+    }
+
+    #[test]
+    fn units_give_and_receive_together() {
+        let session = SecretSatan {
+            participants: ["Ann", "Tom", "Joe", "Sue", "Max"].iter().map(|name| Participant::new(name.to_string())).collect(),
+            units: vec![Unit::new(vec!["Ann".to_string(), "Tom".to_string()])],
+            ..SecretSatan::default()
+        };
+
+        for _ in 0..20 {
+            let assignments = session.draw().unwrap();
+            assert_eq!(assignments.len(), 4);
+            let couple = assignments.iter().find(|a| a.giver_label() == "Ann & Tom").unwrap();
+            assert!(!couple.receivers.contains(&"Ann".to_string()));
+            assert!(!couple.receivers.contains(&"Tom".to_string()));
+            assert!(assignments.iter().any(|a| a.receiver_label() == "Ann & Tom"));
+        }
+    }
+
+    #[test]
+    fn unit_exclusions_apply_to_every_member() {
+        let mut ann = Participant::new("Ann".to_string());
+        ann.excluding.push("Joe".to_string());
+        let session = SecretSatan {
+            participants: vec![ann, Participant::new("Tom".to_string()), Participant::new("Joe".to_string()), Participant::new("Sue".to_string())],
+            units: vec![Unit::new(vec!["Ann".to_string(), "Tom".to_string()])],
+            ..SecretSatan::default()
+        };
+
+        for _ in 0..20 {
+            let givers = session.clone().assign_participants().unwrap();
+            let tom = givers.iter().find(|p| p.name == "Tom").unwrap();
+            assert_eq!(tom.giving_to, Some("Sue".to_string()));
+        }
+    }
+
+    #[test]
+    fn group_gifts_pool_givers_into_balanced_teams() {
+        let mut participants: Vec<Participant> = ["Ann", "Bob", "Cat", "Dan"].iter().map(|name| Participant::new(name.to_string())).collect();
+        for name in ["Eve", "Fay", "Gus", "Hal", "Ivy"] {
+            let mut sponsor = Participant::new(name.to_string());
+            sponsor.role = Role::GiverOnly;
+            participants.push(sponsor);
+        }
+        let session = SecretSatan {
+            participants,
+            mode: DrawMode::GroupGift { min_team_size: 2, max_team_size: 3 },
+            ..SecretSatan::default()
+        };
+
+        let assignments = session.draw().unwrap();
+        assert_eq!(assignments.len(), 4);
+        let mut sizes: Vec<usize> = assignments.iter().map(|a| a.givers.len()).collect();
+        sizes.sort();
+        assert_eq!(sizes, vec![2, 2, 2, 3]);
+        for assignment in assignments {
+            assert!(!assignment.givers.contains(&assignment.receivers[0]));
+        }
+    }
+
+    #[test]
+    fn group_gifts_report_teams_that_do_not_fit() {
+        let session = SecretSatan {
+            participants: ["Ann", "Bob", "Cat"].iter().map(|name| Participant::new(name.to_string())).collect(),
+            mode: DrawMode::GroupGift { min_team_size: 2, max_team_size: 3 },
+            ..SecretSatan::default()
+        };
+        let result = session.draw();
+        assert_eq!(
+            result,
+            Err(SecretSatanError::TeamSizeOutOfRange { givers: 3, receivers: 3, min_team_size: 2, max_team_size: 3 })
+        );
+    }
+
+    #[test]
+    fn team_sizes_count_each_unit_once() {
+        let mut participants: Vec<Participant> = ["Ann", "Bob"].iter().map(|name| Participant::new(name.to_string())).collect();
+        for name in ["Cat", "Dan", "Eve", "Fay"] {
+            let mut sponsor = Participant::new(name.to_string());
+            sponsor.role = Role::GiverOnly;
+            participants.push(sponsor);
+        }
+        let session = SecretSatan {
+            participants,
+            units: vec![
+                Unit::new(vec!["Cat".to_string(), "Dan".to_string()]),
+                Unit::new(vec!["Eve".to_string(), "Fay".to_string()]),
+            ],
+            mode: DrawMode::GroupGift { min_team_size: 2, max_team_size: 2 },
+            ..SecretSatan::default()
+        };
+
+        // Each team is one person and one couple: two places, three people.
+        let assignments = session.draw().unwrap();
+        assert_eq!(assignments.len(), 2);
+        assert!(assignments.iter().all(|assignment| assignment.givers.len() == 3));
+    }
+
+    #[test]
+    fn rotation_does_not_repeat_pairs_while_it_can() {
+        let mut session = SecretSatan::new();
+        for name in ["Alice", "Bob", "Charlie", "David", "Eve"] {
+            session.add_participant(Participant::new(name.to_string()));
+        }
+
+        // Five people can each give to four others, so four years fit without repeats.
+        let rotation = session.plan_rotation(2026, 4).unwrap();
+        assert_eq!(rotation.years.len(), 4);
+        assert_eq!(rotation.repeats(), 0);
+    }
+
+    #[test]
+    fn rotation_hands_out_one_year_at_a_time() {
+        let mut session = SecretSatan::new();
+        for name in ["Alice", "Bob", "Charlie"] {
+            session.add_participant(Participant::new(name.to_string()));
+        }
+
+        let mut rotation = session.plan_rotation(2026, 3).unwrap();
+        assert_eq!(rotation.next_year(), Some(2026));
+        assert_eq!(rotation.take_next().map(|(year, _)| year), Some(2026));
+        assert_eq!(rotation.next_year(), Some(2027));
+        rotation.take_next();
+        rotation.take_next();
+        assert_eq!(rotation.next_year(), None);
+        assert_eq!(rotation.take_next(), None);
+        // Three people only have two possible cycles, so the third year repeats one.
+        assert_eq!(rotation.repeats(), 3);
+    }
+
+    #[test]
+    fn planned_years_are_only_used_while_they_fit_the_list() {
+        let mut session = SecretSatan::new();
+        for name in ["Alice", "Bob", "Charlie", "David"] {
+            session.add_participant(Participant::new(name.to_string()));
+        }
+        session.rotation = Some(session.plan_rotation(2026, 3).unwrap());
+        let planned = session.rotation.clone().unwrap().years;
+        assert_eq!(session.take_planned_year(), Ok(Some((2026, planned[0].clone()))));
+
+        // Someone new isn't in the plan.
+        let mut added = session.clone();
+        added.add_participant(Participant::new("Eve".to_string()));
+        assert_eq!(added.take_planned_year(), Err(SecretSatanError::RotationOutOfDate));
+        assert_eq!(added.rotation.as_ref().unwrap().next, 1);
+
+        // Someone in the plan has been renamed.
+        let mut renamed = session.clone();
+        renamed.participants[0].name = "Alicia".to_string();
+        assert_eq!(renamed.take_planned_year(), Err(SecretSatanError::RotationOutOfDate));
+
+        // The plan breaks an exclusion added since.
+        let mut excluded = session.clone();
+        let receiver = planned[1].iter().find(|assignment| assignment.givers == ["Alice"]).unwrap().receivers[0].clone();
+        excluded.participants[0].excluding.push(receiver);
+        assert_eq!(excluded.take_planned_year(), Err(SecretSatanError::RotationOutOfDate));
+
+        assert_eq!(session.take_planned_year(), Ok(Some((2027, planned[1].clone()))));
+    }
+
+    #[test]
+    fn viewing_is_tracked_until_the_next_draw() {
+        let mut session = SecretSatan::new();
+        for name in ["Alice", "Bob", "Charlie"] {
+            session.add_participant(Participant::new(name.to_string()));
+        }
+        let results = session.draw().unwrap();
+        session.set_results(results.clone());
+        assert!(!session.everyone_viewed());
+
+        for name in ["Alice", "Bob", "Charlie", "Alice"] {
+            session.mark_viewed(name);
+        }
+        assert_eq!(session.viewed.len(), 3);
+        assert!(session.everyone_viewed());
+
+        session.set_results(results);
+        assert!(!session.has_viewed("Alice"));
+    }
+}
@@ -0,0 +1,129 @@
+//! Folding changes saved by another tab into the list open in this one.
+use crate::{Participant, SecretSatan};
+
+/// What came out of `merge`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Merge {
+    /// Both sets of changes combined. Where they clash, this tab's version wins
+    /// until the conflict is resolved.
+    pub merged: SecretSatan,
+    pub conflicts: Vec<Conflict>,
+}
+
+/// A participant both tabs changed in different ways. `None` means that tab
+/// removed them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conflict {
+    pub name: String,
+    pub ours: Option<Participant>,
+    pub theirs: Option<Participant>,
+}
+
+/// Three-way merge of two edited copies of `base`, participant by participant.
+///
+/// Settings like units and the draw mode are taken from whichever side
+/// changed them, preferring ours when both did.
+pub fn merge(base: &SecretSatan, ours: &SecretSatan, theirs: &SecretSatan) -> Merge {
+    let find = |list: &SecretSatan, name: &str| list.participants.iter().find(|p| p.name == name).cloned();
+
+    // Everyone in our order, then anyone only the other tab knows about.
+    let mut names: Vec<String> = ours.participants.iter().map(|p| p.name.clone()).collect();
+    for participant in theirs.participants.iter().chain(base.participants.iter()) {
+        if !names.contains(&participant.name) {
+            names.push(participant.name.clone());
+        }
+    }
+
+    let mut participants = Vec::new();
+    let mut conflicts = Vec::new();
+    for name in names {
+        let (base, ours, theirs) = (find(base, &name), find(ours, &name), find(theirs, &name));
+        let chosen = if ours == theirs || theirs == base {
+            ours
+        } else if ours == base {
+            theirs
+        } else {
+            conflicts.push(Conflict {
+                name,
+                ours: ours.clone(),
+                theirs,
+            });
+            ours
+        };
+        participants.extend(chosen);
+    }
+
+    let mut merged = ours.clone();
+    merged.participants = participants;
+    merged.units = pick(&base.units, &ours.units, &theirs.units).clone();
+    merged.mode = *pick(&base.mode, &ours.mode, &theirs.mode);
+    merged.rotation = pick(&base.rotation, &ours.rotation, &theirs.rotation).clone();
+    merged.results = pick(&base.results, &ours.results, &theirs.results).clone();
+    merged.viewed = pick(&base.viewed, &ours.viewed, &theirs.viewed).clone();
+    merged.budget = pick(&base.budget, &ours.budget, &theirs.budget).clone();
+    merged.date = pick(&base.date, &ours.date, &theirs.date).clone();
+
+    Merge { merged, conflicts }
+}
+
+/// Whichever side changed `base`, preferring ours.
+fn pick<'a, T: PartialEq>(base: &T, ours: &'a T, theirs: &'a T) -> &'a T {
+    if ours == base {
+        theirs
+    } else {
+        ours
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list(names: &[&str]) -> SecretSatan {
+        let mut list = SecretSatan::new();
+        for name in names {
+            list.add_participant(Participant::new(name.to_string()));
+        }
+        list
+    }
+
+    #[test]
+    fn changes_to_different_participants_are_combined() {
+        let base = list(&["Alice", "Bob"]);
+        let mut ours = base.clone();
+        ours.participants[0].excluding.push("Bob".to_string());
+        let mut theirs = base.clone();
+        theirs.add_participant(Participant::new("Charlie".to_string()));
+
+        let merge = merge(&base, &ours, &theirs);
+        assert!(merge.conflicts.is_empty());
+        assert_eq!(merge.merged.participants.len(), 3);
+        assert_eq!(merge.merged.participants[0].excluding, vec!["Bob".to_string()]);
+    }
+
+    #[test]
+    fn removals_in_the_other_tab_are_kept() {
+        let base = list(&["Alice", "Bob"]);
+        let ours = base.clone();
+        let theirs = list(&["Alice"]);
+
+        let merge = merge(&base, &ours, &theirs);
+        assert_eq!(merge.merged, theirs);
+    }
+
+    #[test]
+    fn clashing_edits_are_reported() {
+        let base = list(&["Alice", "Bob"]);
+        let mut ours = base.clone();
+        ours.participants[0].excluding.push("Bob".to_string());
+        let mut theirs = base.clone();
+        theirs.participants.remove(0);
+
+        let merge = merge(&base, &ours, &theirs);
+        assert_eq!(merge.conflicts.len(), 1);
+        assert_eq!(merge.conflicts[0].name, "Alice");
+        assert_eq!(merge.conflicts[0].theirs, None);
+        // Ours stays put until someone decides.
+        assert_eq!(merge.merged, ours);
+    }
+}
@@ -0,0 +1,53 @@
+//! Each person's own result from a draw, ready to be handed to them without
+//! anyone else seeing it.
+use crate::Assignment;
+use serde::{Deserialize, Serialize};
+
+/// One person's result, as shown when they open their link.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Reveal {
+    pub event: String,
+    pub giver: String,
+    pub receiver: String,
+    /// Anyone buying the gift together with `giver`.
+    #[serde(default)]
+    pub sharing_with: Vec<String>,
+}
+
+/// Everyone's results from a draw, one per giver. Units and teams get a link
+/// for each member.
+pub fn reveals(event: &str, results: &[Assignment]) -> Vec<Reveal> {
+    results
+        .iter()
+        .flat_map(|assignment| {
+            assignment.givers.iter().map(|giver| Reveal {
+                event: event.to_string(),
+                giver: giver.clone(),
+                receiver: assignment.receiver_label(),
+                sharing_with: assignment.givers.iter().filter(|other| *other != giver).cloned().collect(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assignment(givers: &[&str], receiver: &str) -> Assignment {
+        Assignment {
+            givers: givers.iter().map(|giver| giver.to_string()).collect(),
+            receivers: vec![receiver.to_string()],
+        }
+    }
+
+    #[test]
+    fn every_giver_gets_their_own_result() {
+        let results = vec![assignment(&["Alice"], "Bob"), assignment(&["Bob", "Charlie"], "Alice")];
+        let reveals = reveals("Office", &results);
+        assert_eq!(reveals.len(), 3);
+        assert_eq!(reveals[2].giver, "Charlie");
+        assert_eq!(reveals[2].receiver, "Alice");
+        assert_eq!(reveals[2].sharing_with, vec!["Bob".to_string()]);
+    }
+}
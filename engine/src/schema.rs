@@ -0,0 +1,143 @@
+//! Schema versions for the engine's own types, and the migrations that
+//! bring older data up to date. Add a migration to the end of a type's list
+//! whenever its stored shape changes.
+use crate::version::{Migration, Versioned};
+use crate::{History, SecretSatan};
+use serde_json::{json, Value};
+
+impl Versioned for SecretSatan {
+    const MIGRATIONS: &'static [Migration] = &[secret_satan_v1, secret_satan_v2, secret_satan_v3, secret_satan_v4, secret_satan_v5];
+}
+
+impl Versioned for History {
+    const MIGRATIONS: &'static [Migration] = &[history_v1, history_v2, history_v3];
+}
+
+/// Lists saved before versioning may be missing everything added since
+/// names and exclusions, and can have blank exclusions left behind by the
+/// old form.
+fn secret_satan_v1(mut data: Value) -> Result<Value, String> {
+    let list = data.as_object_mut().ok_or("The saved list isn't an object.")?;
+    let participants = list
+        .get_mut("participants")
+        .and_then(Value::as_array_mut)
+        .ok_or("The saved list has no participants.")?;
+    for participant in participants.iter_mut() {
+        let participant = participant.as_object_mut().ok_or("A saved participant isn't an object.")?;
+        if !participant.get("name").is_some_and(Value::is_string) {
+            return Err("A saved participant has no name.".to_string());
+        }
+        participant.entry("giving_to").or_insert(Value::Null);
+        participant.entry("receiving_from").or_insert(Value::Null);
+        participant.entry("drawn").or_insert(json!(false));
+        participant.entry("role").or_insert(json!("Both"));
+        let excluding = participant.entry("excluding").or_insert(json!([]));
+        if let Some(excluding) = excluding.as_array_mut() {
+            excluding.retain(|name| name.as_str().is_some_and(|name| !name.trim().is_empty()));
+        }
+    }
+    list.entry("units").or_insert(json!([]));
+    list.entry("mode").or_insert(json!("OneToOne"));
+    list.entry("rotation").or_insert(Value::Null);
+    list.entry("results").or_insert(json!([]));
+    Ok(data)
+}
+
+/// Participants gained an email address and tags.
+fn secret_satan_v2(mut data: Value) -> Result<Value, String> {
+    let participants = data
+        .get_mut("participants")
+        .and_then(Value::as_array_mut)
+        .ok_or("The saved list has no participants.")?;
+    for participant in participants.iter_mut() {
+        let participant = participant.as_object_mut().ok_or("A saved participant isn't an object.")?;
+        participant.entry("email").or_insert(Value::Null);
+        participant.entry("tags").or_insert(json!([]));
+    }
+    Ok(data)
+}
+
+/// Who has seen their result started being tracked.
+fn secret_satan_v3(mut data: Value) -> Result<Value, String> {
+    let list = data.as_object_mut().ok_or("The saved list isn't an object.")?;
+    list.entry("viewed").or_insert(json!([]));
+    Ok(data)
+}
+
+/// Events gained a budget and a date.
+fn secret_satan_v4(mut data: Value) -> Result<Value, String> {
+    let list = data.as_object_mut().ok_or("The saved list isn't an object.")?;
+    list.entry("budget").or_insert(Value::Null);
+    list.entry("date").or_insert(Value::Null);
+    Ok(data)
+}
+
+/// Participants gained a wishlist.
+fn secret_satan_v5(mut data: Value) -> Result<Value, String> {
+    let participants = data
+        .get_mut("participants")
+        .and_then(Value::as_array_mut)
+        .ok_or("The saved list has no participants.")?;
+    for participant in participants.iter_mut() {
+        let participant = participant.as_object_mut().ok_or("A saved participant isn't an object.")?;
+        participant.entry("wishlist").or_insert(Value::Null);
+    }
+    Ok(data)
+}
+
+/// The undo history arrived with versioning, so there's nothing to change.
+fn history_v1(data: Value) -> Result<Value, String> {
+    Ok(data)
+}
+
+/// The history started keeping each draw's commitment.
+fn history_v2(mut data: Value) -> Result<Value, String> {
+    let history = data.as_object_mut().ok_or("The saved history isn't an object.")?;
+    history.entry("commitments").or_insert(json!([]));
+    Ok(data)
+}
+
+/// Commitments started recording the seed everyone contributed to.
+fn history_v3(mut data: Value) -> Result<Value, String> {
+    let commitments = data
+        .get_mut("commitments")
+        .and_then(Value::as_array_mut)
+        .ok_or("The saved history has no commitments.")?;
+    for commitment in commitments.iter_mut() {
+        let commitment = commitment.as_object_mut().ok_or("A saved commitment isn't an object.")?;
+        commitment.entry("seed").or_insert(Value::Null);
+    }
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::version::upgrade;
+    use crate::{History, Role, SecretSatan};
+
+    #[test]
+    fn unversioned_lists_are_migrated() {
+        let raw = r#"{"participants": [
+            {"name": "Alice", "giving_to": null, "receiving_from": null, "excluding": ["", "Bob"], "drawn": false},
+            {"name": "Bob", "giving_to": null, "receiving_from": null, "excluding": [""], "drawn": false}
+        ]}"#;
+        let secret_satan: SecretSatan = upgrade(raw).unwrap();
+        assert_eq!(secret_satan.participants.len(), 2);
+        assert_eq!(secret_satan.participants[0].excluding, vec!["Bob".to_string()]);
+        assert!(secret_satan.participants[1].excluding.is_empty());
+        assert_eq!(secret_satan.participants[1].role, Role::Both);
+        assert!(secret_satan.units.is_empty());
+    }
+
+    #[test]
+    fn histories_from_before_commitments_are_migrated() {
+        let raw = r#"{"version": 1, "data": {"undo": [], "redo": []}}"#;
+        assert!(upgrade::<History>(raw).unwrap().commitments().is_empty());
+    }
+
+    #[test]
+    fn lists_that_cannot_be_migrated_are_reported() {
+        let raw = r#"{"participants": [{"excluding": []}]}"#;
+        assert!(upgrade::<SecretSatan>(raw).is_err());
+    }
+}
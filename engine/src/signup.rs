@@ -0,0 +1,202 @@
+//! Participants signing themselves up through an invite link, for the
+//! organizer to approve before they join the list.
+use crate::{Participant, SecretSatan};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// An event's invite link. Anyone with the token can sign up while it's open.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Invite {
+    pub token: String,
+    /// The storage key of the event people are signing up for.
+    pub event: String,
+    /// The event's name, as shown to people signing up.
+    pub name: String,
+    pub open: bool,
+}
+
+/// What someone fills in when they sign up.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct SignupForm {
+    pub name: String,
+    pub email: Option<String>,
+    pub wishlist: Option<String>,
+    /// People they'd rather not be drawn for.
+    pub avoid: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum SignupStatus {
+    #[default]
+    Pending,
+    Approved,
+    Rejected,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Signup {
+    pub id: i64,
+    pub form: SignupForm,
+    pub status: SignupStatus,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SignupError {
+    MissingName,
+    /// Someone already on the list or signed up has this name.
+    NameTaken(String),
+    Closed,
+    UnknownInvite,
+}
+
+impl fmt::Display for SignupError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SignupError::MissingName => write!(f, "Please enter your name."),
+            SignupError::NameTaken(name) => write!(f, "Someone called {name} has already signed up. Try adding your last name."),
+            SignupError::Closed => write!(f, "Sign-ups for this event have closed."),
+            SignupError::UnknownInvite => write!(f, "This invite link doesn't exist. Ask your organizer for a new one."),
+        }
+    }
+}
+
+impl std::error::Error for SignupError {}
+
+impl SignupForm {
+    /// Tidies the form up, dropping blank fields and checking the name isn't
+    /// one of `taken`.
+    pub fn validate<'a>(self, taken: impl IntoIterator<Item = &'a str>) -> Result<SignupForm, SignupError> {
+        let name = self.name.trim().to_string();
+        if name.is_empty() {
+            return Err(SignupError::MissingName);
+        }
+        if taken.into_iter().any(|other| other.trim().eq_ignore_ascii_case(&name)) {
+            return Err(SignupError::NameTaken(name));
+        }
+        let filled = |field: Option<String>| field.map(|field| field.trim().to_string()).filter(|field| !field.is_empty());
+        let mut avoid: Vec<String> = Vec::new();
+        for other in self.avoid.iter().map(|other| other.trim()) {
+            if !other.is_empty()
+                && !other.eq_ignore_ascii_case(&name)
+                && !avoid.iter().any(|seen| seen.eq_ignore_ascii_case(other))
+            {
+                avoid.push(other.to_string());
+            }
+        }
+        Ok(SignupForm {
+            name,
+            email: filled(self.email),
+            wishlist: filled(self.wishlist),
+            avoid,
+        })
+    }
+
+    /// The participant to add to `list` once the sign-up is approved. The
+    /// people they'd rather avoid are matched to the list ignoring case,
+    /// since the draw only knows names as the list spells them. Anyone not
+    /// on it is left out; see `unknown_avoid`.
+    pub fn participant(&self, list: &SecretSatan) -> Participant {
+        let mut participant = Participant::new(self.name.clone());
+        participant.email = self.email.clone();
+        participant.wishlist = self.wishlist.clone();
+        participant.excluding = self.avoid.iter().filter_map(|other| find(list, other)).map(|p| p.name.clone()).collect();
+        participant
+    }
+
+    /// The people they'd rather avoid who aren't on `list`.
+    pub fn unknown_avoid(&self, list: &SecretSatan) -> Vec<String> {
+        self.avoid.iter().filter(|other| find(list, other).is_none()).cloned().collect()
+    }
+}
+
+fn find<'a>(list: &'a SecretSatan, name: &str) -> Option<&'a Participant> {
+    list.participants.iter().find(|participant| participant.name.trim().eq_ignore_ascii_case(name.trim()))
+}
+
+impl Signup {
+    /// Adds the sign-up to `list`, unless someone with the same name is
+    /// already on it.
+    pub fn approve(&mut self, list: &mut SecretSatan) -> Result<(), SignupError> {
+        if find(list, &self.form.name).is_some() {
+            return Err(SignupError::NameTaken(self.form.name.clone()));
+        }
+        list.add_participant(self.form.participant(list));
+        self.status = SignupStatus::Approved;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn form(name: &str) -> SignupForm {
+        SignupForm {
+            name: name.to_string(),
+            email: Some("  ".to_string()),
+            wishlist: Some(" Socks ".to_string()),
+            avoid: vec!["Bob".to_string(), " ".to_string(), name.trim().to_string()],
+        }
+    }
+
+    #[test]
+    fn forms_are_tidied_up() {
+        let form = form(" Alice ").validate(["Bob"]).unwrap();
+        assert_eq!(form.name, "Alice");
+        assert_eq!(form.email, None);
+        assert_eq!(form.wishlist.as_deref(), Some("Socks"));
+        assert_eq!(form.avoid, vec!["Bob".to_string()]);
+    }
+
+    #[test]
+    fn names_must_be_new() {
+        assert_eq!(form("alice").validate(["Alice"]), Err(SignupError::NameTaken("alice".to_string())));
+        assert_eq!(form(" ").validate([]), Err(SignupError::MissingName));
+    }
+
+    #[test]
+    fn avoided_names_are_only_kept_once() {
+        let mut form = form("Alice");
+        form.avoid = vec!["Bob".to_string(), "Carol".to_string(), "bob".to_string(), "ALICE".to_string()];
+        let form = form.validate([]).unwrap();
+        assert_eq!(form.avoid, vec!["Bob".to_string(), "Carol".to_string()]);
+    }
+
+    #[test]
+    fn approving_adds_a_participant() {
+        let mut list = SecretSatan::new();
+        list.add_participant(Participant::new("Bob".to_string()));
+        let mut signup = Signup {
+            id: 1,
+            form: form("Alice").validate([]).unwrap(),
+            status: SignupStatus::Pending,
+        };
+        signup.approve(&mut list).unwrap();
+        assert_eq!(signup.status, SignupStatus::Approved);
+        assert_eq!(list.participants[1].excluding, vec!["Bob".to_string()]);
+        assert_eq!(list.participants[1].wishlist.as_deref(), Some("Socks"));
+        assert!(signup.approve(&mut list).is_err());
+    }
+
+    #[test]
+    fn avoided_names_are_matched_to_the_list() {
+        let mut list = SecretSatan::new();
+        list.add_participant(Participant::new("Bob".to_string()));
+        let mut signup = Signup {
+            id: 1,
+            form: SignupForm {
+                avoid: vec!["bob".to_string(), "Zed".to_string()],
+                ..form("Alice")
+            }
+            .validate([])
+            .unwrap(),
+            status: SignupStatus::Pending,
+        };
+        assert_eq!(signup.form.unknown_avoid(&list), vec!["Zed".to_string()]);
+        signup.approve(&mut list).unwrap();
+        assert_eq!(list.participants[1].excluding, vec!["Bob".to_string()]);
+
+        signup.form.name = "alice".to_string();
+        assert_eq!(signup.approve(&mut list), Err(SignupError::NameTaken("alice".to_string())));
+    }
+}
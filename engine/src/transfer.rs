@@ -0,0 +1,179 @@
+//! Moving a whole event in and out of the app as a JSON file.
+use crate::version::{self, Versioned};
+use crate::SecretSatan;
+use serde_json::{json, Value};
+use std::collections::HashSet;
+
+/// An event read back from an exported file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedEvent {
+    /// The event's name, if the file has one.
+    pub name: Option<String>,
+    pub list: SecretSatan,
+}
+
+/// How an imported list differs from the current one, by participant name.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ListDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<String>,
+    /// Whether units, the draw mode, the rotation, the results, the budget or
+    /// the date differ.
+    pub settings_changed: bool,
+}
+
+impl ListDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty() && !self.settings_changed
+    }
+}
+
+/// The file contents for an event. It's laid out like stored data, with the
+/// schema version, so older exports can be migrated when they're imported.
+pub fn export_event(name: &str, list: &SecretSatan) -> String {
+    serde_json::to_string_pretty(&event_file(name, list)).unwrap_or_default()
+}
+
+/// The exported event before it's written out, for other formats to wrap.
+pub fn event_file(name: &str, list: &SecretSatan) -> Value {
+    json!({
+        "version": SecretSatan::version(),
+        "name": name,
+        "data": list,
+    })
+}
+
+/// Reads an exported event, checking it makes sense before anything is
+/// changed. Plain saved lists without a name are accepted too.
+pub fn import_event(raw: &str) -> Result<ImportedEvent, String> {
+    let list: SecretSatan = version::upgrade(raw).map_err(|error| format!("This isn't a Secret Satan file. {error}"))?;
+
+    let mut names = HashSet::new();
+    for participant in &list.participants {
+        if participant.name.trim().is_empty() {
+            return Err("The file has a participant with no name.".to_string());
+        }
+        if !names.insert(participant.name.as_str()) {
+            return Err(format!("The file lists {} more than once.", participant.name));
+        }
+    }
+    for unit in &list.units {
+        if let Some(member) = unit.members.iter().find(|member| !names.contains(member.as_str())) {
+            return Err(format!("The file puts {member} in a group, but they aren't on the list."));
+        }
+    }
+
+    let name = serde_json::from_str::<Value>(raw)
+        .ok()
+        .and_then(|file| file.get("name")?.as_str().map(str::to_string))
+        .filter(|name| !name.trim().is_empty());
+    Ok(ImportedEvent { name, list })
+}
+
+/// What replacing `current` with `imported` would change.
+pub fn diff(current: &SecretSatan, imported: &SecretSatan) -> ListDiff {
+    let mut diff = ListDiff::default();
+    for participant in &imported.participants {
+        match current.participants.iter().find(|p| p.name == participant.name) {
+            None => diff.added.push(participant.name.clone()),
+            Some(existing) if existing != participant => diff.changed.push(participant.name.clone()),
+            Some(_) => {}
+        }
+    }
+    for participant in &current.participants {
+        if !imported.participants.iter().any(|p| p.name == participant.name) {
+            diff.removed.push(participant.name.clone());
+        }
+    }
+    diff.settings_changed = current.units != imported.units
+        || current.mode != imported.mode
+        || current.rotation != imported.rotation
+        || current.results != imported.results
+        || current.budget != imported.budget
+        || current.date != imported.date;
+    diff
+}
+
+/// Adds an imported list to `current` without losing anything: new people are
+/// added, exclusions are combined for people on both lists, and groups are
+/// brought over when none of their members are in a group already. Settings
+/// and results stay as they are.
+pub fn merge_import(current: &SecretSatan, imported: &SecretSatan) -> SecretSatan {
+    let mut merged = current.clone();
+    for participant in &imported.participants {
+        match merged.participants.iter_mut().find(|p| p.name == participant.name) {
+            Some(existing) => {
+                for name in &participant.excluding {
+                    if !existing.excluding.contains(name) {
+                        existing.excluding.push(name.clone());
+                    }
+                }
+            }
+            None => merged.add_participant(participant.clone()),
+        }
+    }
+    for unit in &imported.units {
+        if unit.members.iter().all(|member| merged.unit_of(member).is_none()) {
+            merged.units.push(unit.clone());
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Participant, Unit};
+
+    fn list(names: &[&str]) -> SecretSatan {
+        let mut list = SecretSatan::new();
+        for name in names {
+            list.add_participant(Participant::new(name.to_string()));
+        }
+        list
+    }
+
+    #[test]
+    fn exported_events_import_unchanged() {
+        let mut original = list(&["Alice", "Bob", "Charlie"]);
+        original.participants[0].excluding.push("Bob".to_string());
+        original.units.push(Unit::new(vec!["Bob".to_string(), "Charlie".to_string()]));
+
+        let imported = import_event(&export_event("Office", &original)).unwrap();
+        assert_eq!(imported.name.as_deref(), Some("Office"));
+        assert_eq!(imported.list, original);
+    }
+
+    #[test]
+    fn broken_files_are_rejected() {
+        assert!(import_event("not json").is_err());
+        let duplicate = export_event("Office", &list(&["Alice", "Alice"]));
+        assert!(import_event(&duplicate).is_err());
+    }
+
+    #[test]
+    fn diff_lists_who_changes() {
+        let current = list(&["Alice", "Bob"]);
+        let mut imported = list(&["Alice", "Charlie"]);
+        imported.participants[0].excluding.push("Charlie".to_string());
+
+        let diff = diff(&current, &imported);
+        assert_eq!(diff.added, vec!["Charlie".to_string()]);
+        assert_eq!(diff.removed, vec!["Bob".to_string()]);
+        assert_eq!(diff.changed, vec!["Alice".to_string()]);
+        assert!(!diff.settings_changed);
+    }
+
+    #[test]
+    fn merging_keeps_everyone_and_combines_exclusions() {
+        let mut current = list(&["Alice", "Bob"]);
+        current.participants[0].excluding.push("Bob".to_string());
+        let mut imported = list(&["Alice", "Charlie"]);
+        imported.participants[0].excluding.push("Charlie".to_string());
+
+        let merged = merge_import(&current, &imported);
+        assert_eq!(merged.participants.len(), 3);
+        assert_eq!(merged.participants[0].excluding, vec!["Bob".to_string(), "Charlie".to_string()]);
+    }
+}
@@ -0,0 +1,52 @@
+//! Schema versions for stored and exported data, so anything saved by an
+//! older release can be brought up to date when it's read back.
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+
+/// Upgrades stored data by one schema version.
+pub type Migration = fn(Value) -> Result<Value, String>;
+
+/// A type that's stored with a schema version.
+pub trait Versioned: Serialize + DeserializeOwned {
+    /// `MIGRATIONS[n]` upgrades data saved at version `n` to version `n + 1`,
+    /// so the current version is `MIGRATIONS.len()`. Data saved before
+    /// versions existed counts as version 0.
+    const MIGRATIONS: &'static [Migration];
+
+    fn version() -> u32 {
+        Self::MIGRATIONS.len() as u32
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+struct Envelope<T> {
+    version: u32,
+    data: T,
+}
+
+/// Parses `raw` and runs it through whatever migrations it needs.
+pub fn upgrade<T: Versioned>(raw: &str) -> Result<T, String> {
+    let value: Value = serde_json::from_str(raw).map_err(|error| error.to_string())?;
+    let (version, mut data) = match serde_json::from_value::<Envelope<Value>>(value.clone()) {
+        Ok(envelope) => (envelope.version, envelope.data),
+        Err(_) => (0, value),
+    };
+    if version > T::version() {
+        return Err(format!(
+            "This data was saved by a newer version of the app (version {version}, this is version {}).",
+            T::version()
+        ));
+    }
+    for migration in &T::MIGRATIONS[version as usize..] {
+        data = migration(data)?;
+    }
+    serde_json::from_value(data).map_err(|error| error.to_string())
+}
+
+/// Serializes `value` along with its current version.
+pub fn to_string<T: Versioned>(value: &T) -> Result<String, serde_json::Error> {
+    serde_json::to_string(&Envelope {
+        version: T::version(),
+        data: value,
+    })
+}
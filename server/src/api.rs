@@ -0,0 +1,598 @@
+//! The HTTP API the web app stores its data through.
+//!
+//! Entries mirror the app's `StorageBackend`: the whole store is read once
+//! when the app starts, then entries are written and removed one key at a
+//! time. Invites let participants sign themselves up to an event, and the
+//! organizer can send everyone their result and reminders through whatever
+//! the event is set up to use. Participants can also contribute to the seed
+//! the draw uses, committing to a value first and revealing it later.
+//!
+//! Everything but the `/api/invites/` routes is the organizer's, and needs
+//! their key as a bearer token. Invite routes only need the invite's token.
+use crate::db::{Database, DbError};
+use crate::notify::{self, Audience, Notice, Notifiers};
+use axum::body::Bytes;
+use axum::extract::{Path, Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post, put};
+use axum::{Json, Router};
+use secret_satan_engine::delivery::{
+    address, render, render_for_everyone, result_subject, Delivery, DeliveryStatus, NotificationOptions,
+    NotificationSettings, DEFAULT_TEMPLATE, DRAWN_ANNOUNCEMENT,
+};
+use secret_satan_engine::entropy::{EntropyError, Phase, Round};
+use secret_satan_engine::reveal::reveals;
+use secret_satan_engine::signup::{Signup, SignupError, SignupForm, SignupStatus};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+pub type Shared = Arc<Mutex<Database>>;
+
+#[derive(Clone)]
+pub struct AppState {
+    db: Shared,
+    notifiers: Notifiers,
+    organizer_key: Arc<str>,
+}
+
+impl axum::extract::FromRef<AppState> for Shared {
+    fn from_ref(state: &AppState) -> Shared {
+        state.db.clone()
+    }
+}
+
+pub fn router(db: Database, notifiers: Notifiers, organizer_key: &str) -> Router {
+    let state = AppState {
+        db: Arc::new(Mutex::new(db)),
+        notifiers,
+        organizer_key: organizer_key.into(),
+    };
+    let organizer = Router::new()
+        .route("/api/entries", get(entries))
+        .route("/api/entries/{key}", get(entry).put(set).delete(remove))
+        .route("/api/events/{event}/invite", get(invite).put(save_invite))
+        .route("/api/events/{event}/signups", get(signups))
+        .route("/api/signups/{id}/status", put(set_signup_status))
+        .route("/api/events/{event}/notifications", get(notifications).put(save_notifications))
+        .route("/api/events/{event}/deliveries", get(deliveries).post(deliver))
+        .route("/api/events/{event}/announcements", post(announce))
+        .route("/api/events/{event}/entropy", get(entropy_round).put(set_entropy_phase))
+        .route_layer(middleware::from_fn_with_state(state.clone(), organizer_only));
+    let invites = Router::new()
+        .route("/api/invites/{token}", get(public_invite))
+        .route("/api/invites/{token}/signups", post(sign_up))
+        .route("/api/contributions/{token}", get(contribution_status))
+        .route("/api/contributions/{token}/commitments", post(contribute))
+        .route("/api/contributions/{token}/reveals", post(reveal_contribution));
+    organizer.merge(invites).with_state(state)
+}
+
+/// Turns away requests that don't carry the organizer's key.
+async fn organizer_only(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    let key = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    match key.is_some_and(|key| same_key(key, &state.organizer_key)) {
+        true => next.run(request).await,
+        false => (StatusCode::UNAUTHORIZED, "Only the organizer can do that. Open the organizer link the server printed when it started.").into_response(),
+    }
+}
+
+/// Compares keys without stopping at the first difference, so how long it
+/// takes doesn't give away how much of a guess was right.
+fn same_key(given: &str, expected: &str) -> bool {
+    given.len() == expected.len() && given.bytes().zip(expected.bytes()).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+impl IntoResponse for DbError {
+    fn into_response(self) -> Response {
+        let status = match &self {
+            DbError::Signup(SignupError::MissingName) => StatusCode::BAD_REQUEST,
+            DbError::Signup(SignupError::NameTaken(_)) => StatusCode::CONFLICT,
+            DbError::Signup(SignupError::Closed) => StatusCode::FORBIDDEN,
+            DbError::Signup(SignupError::UnknownInvite) => StatusCode::NOT_FOUND,
+            DbError::Entropy(EntropyError::Mismatch) | DbError::NotAList(_) => StatusCode::BAD_REQUEST,
+            DbError::Entropy(
+                EntropyError::NotCollecting | EntropyError::NotRevealing | EntropyError::NotTakingPart | EntropyError::NotYours,
+            ) => StatusCode::FORBIDDEN,
+            DbError::Entropy(EntropyError::AlreadyCommitted) => StatusCode::CONFLICT,
+            DbError::Entropy(EntropyError::NoCommitment) => StatusCode::NOT_FOUND,
+            DbError::Sqlite(_) | DbError::Corrupt(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        (status, self.to_string()).into_response()
+    }
+}
+
+async fn entries(State(db): State<Shared>) -> Result<Json<BTreeMap<String, String>>, DbError> {
+    Ok(Json(db.lock().unwrap().entries()?))
+}
+
+async fn entry(State(db): State<Shared>, Path(key): Path<String>) -> Result<Response, DbError> {
+    Ok(match db.lock().unwrap().get(&key)? {
+        Some(value) => value.into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    })
+}
+
+async fn set(State(db): State<Shared>, Path(key): Path<String>, body: Bytes) -> Result<StatusCode, Response> {
+    let value = String::from_utf8(body.to_vec())
+        .map_err(|_| (StatusCode::BAD_REQUEST, "Entries must be text.").into_response())?;
+    db.lock().unwrap().set(&key, &value).map_err(IntoResponse::into_response)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn remove(State(db): State<Shared>, Path(key): Path<String>) -> Result<StatusCode, DbError> {
+    db.lock().unwrap().remove(&key)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Deserialize)]
+struct InviteSettings {
+    name: String,
+    open: bool,
+}
+
+async fn invite(State(db): State<Shared>, Path(event): Path<String>) -> Result<Response, DbError> {
+    Ok(match db.lock().unwrap().invite(&event)? {
+        Some(invite) => Json(invite).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    })
+}
+
+async fn save_invite(
+    State(db): State<Shared>,
+    Path(event): Path<String>,
+    Json(settings): Json<InviteSettings>,
+) -> Result<Response, DbError> {
+    Ok(Json(db.lock().unwrap().save_invite(&event, &settings.name, settings.open)?).into_response())
+}
+
+async fn signups(State(db): State<Shared>, Path(event): Path<String>) -> Result<Json<Vec<Signup>>, DbError> {
+    Ok(Json(db.lock().unwrap().signups(&event)?))
+}
+
+async fn set_signup_status(
+    State(db): State<Shared>,
+    Path(id): Path<i64>,
+    Json(status): Json<SignupStatus>,
+) -> Result<StatusCode, DbError> {
+    Ok(match db.lock().unwrap().set_signup_status(id, status)? {
+        true => StatusCode::NO_CONTENT,
+        false => StatusCode::NOT_FOUND,
+    })
+}
+
+/// What someone opening an invite link needs to know, without the rest.
+async fn public_invite(State(db): State<Shared>, Path(token): Path<String>) -> Result<Json<Value>, DbError> {
+    let invite = db.lock().unwrap().invite_by_token(&token)?.ok_or(SignupError::UnknownInvite)?;
+    Ok(Json(json!({ "name": invite.name, "open": invite.open })))
+}
+
+async fn sign_up(
+    State(db): State<Shared>,
+    Path(token): Path<String>,
+    Json(form): Json<SignupForm>,
+) -> Result<(StatusCode, Json<Signup>), DbError> {
+    let signup = db.lock().unwrap().sign_up(&token, form)?;
+    Ok((StatusCode::CREATED, Json(signup)))
+}
+
+async fn notifications(
+    State(state): State<AppState>,
+    Path(event): Path<String>,
+) -> Result<Json<NotificationOptions>, DbError> {
+    Ok(Json(NotificationOptions {
+        settings: state.db.lock().unwrap().notification_settings(&event)?,
+        email_available: state.notifiers.mailer.is_some(),
+        files_available: state.notifiers.outbox.is_some(),
+    }))
+}
+
+async fn save_notifications(
+    State(db): State<Shared>,
+    Path(event): Path<String>,
+    Json(settings): Json<NotificationSettings>,
+) -> Result<StatusCode, DbError> {
+    db.lock().unwrap().save_notification_settings(&event, &settings)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn deliveries(State(db): State<Shared>, Path(event): Path<String>) -> Result<Json<Vec<Delivery>>, DbError> {
+    Ok(Json(db.lock().unwrap().deliveries(&event)?))
+}
+
+#[derive(Deserialize)]
+struct DeliveryRequest {
+    /// The event's name, for the message.
+    name: String,
+    /// The message to send, if not the default one.
+    template: Option<String>,
+    /// Send to just this giver, even if they've been sent theirs already.
+    /// Otherwise everyone who hasn't been sent theirs is.
+    giver: Option<String>,
+}
+
+/// Sends givers their results, then reports how it went for everyone. Once
+/// results have gone out, anywhere that isn't private, like a chat, is told
+/// the names have been drawn.
+async fn deliver(
+    State(state): State<AppState>,
+    Path(event): Path<String>,
+    Json(request): Json<DeliveryRequest>,
+) -> Result<Response, DbError> {
+    let settings = state.db.lock().unwrap().notification_settings(&event)?;
+    let providers = state.notifiers.providers(&settings);
+    if !providers.iter().any(|provider| provider.private()) {
+        let message = "Nothing is set up to send results for this event. Turn on email or files, and start the server with --smtp-host and --smtp-from or --outbox.";
+        return Ok((StatusCode::SERVICE_UNAVAILABLE, message).into_response());
+    }
+    let template = request.template.filter(|template| !template.trim().is_empty());
+    let template = template.as_deref().unwrap_or(DEFAULT_TEMPLATE);
+
+    // Work out what to send first, so the database isn't held while sending.
+    let (list, outbox) = {
+        let db = state.db.lock().unwrap();
+        let list = db.list(&event)?.unwrap_or_default();
+        let sent: Vec<String> = db
+            .deliveries(&event)?
+            .into_iter()
+            .filter(|delivery| delivery.status == DeliveryStatus::Sent)
+            .map(|delivery| delivery.giver)
+            .collect();
+        let outbox: Vec<_> = reveals(&request.name, &list.results)
+            .into_iter()
+            .filter(|reveal| match &request.giver {
+                Some(giver) => reveal.giver == *giver,
+                None => !sent.contains(&reveal.giver),
+            })
+            .map(|reveal| {
+                let notice = Notice {
+                    event: event.clone(),
+                    audience: Audience::Giver {
+                        name: reveal.giver.clone(),
+                        email: address(&list, &reveal.giver),
+                    },
+                    subject: result_subject(&request.name),
+                    body: render(template, &reveal, &list),
+                };
+                (notice, reveal)
+            })
+            .collect();
+        (list, outbox)
+    };
+
+    let mut delivered = false;
+    for (notice, reveal) in outbox {
+        let status = notify::deliver(&providers, &notice).await;
+        delivered |= status == DeliveryStatus::Sent;
+        let email = address(&list, &reveal.giver);
+        state.db.lock().unwrap().record_delivery(&event, &reveal, email.as_deref(), &status)?;
+    }
+
+    if delivered && request.giver.is_none() {
+        let public: Vec<_> = providers.into_iter().filter(|provider| !provider.private()).collect();
+        let notice = Notice {
+            event: event.clone(),
+            audience: Audience::Everyone { emails: Vec::new() },
+            subject: format!("{}: names have been drawn", request.name),
+            body: render_for_everyone(DRAWN_ANNOUNCEMENT, &request.name, &list),
+        };
+        for failure in notify::announce(&public, &notice).await {
+            eprintln!("couldn't announce the draw for {event}: {failure}");
+        }
+    }
+    Ok(Json(state.db.lock().unwrap().deliveries(&event)?).into_response())
+}
+
+#[derive(Deserialize)]
+struct Announcement {
+    /// The event's name, for the message.
+    name: String,
+    subject: Option<String>,
+    /// Filled in with `render_for_everyone`.
+    message: String,
+}
+
+/// Sends a message, like a reminder, to everyone through every way the event
+/// is set up to send. Returns what went wrong, if anything.
+async fn announce(
+    State(state): State<AppState>,
+    Path(event): Path<String>,
+    Json(announcement): Json<Announcement>,
+) -> Result<Response, DbError> {
+    let (settings, list) = {
+        let db = state.db.lock().unwrap();
+        (db.notification_settings(&event)?, db.list(&event)?.unwrap_or_default())
+    };
+    let providers = state.notifiers.providers(&settings);
+    if providers.is_empty() {
+        let message = "Nothing is set up to send messages for this event. Add a webhook, or turn on email or files.";
+        return Ok((StatusCode::SERVICE_UNAVAILABLE, message).into_response());
+    }
+    let emails = list.participants.iter().filter_map(|participant| address(&list, &participant.name)).collect();
+    let subject = announcement.subject.filter(|subject| !subject.trim().is_empty());
+    let notice = Notice {
+        event,
+        audience: Audience::Everyone { emails },
+        subject: subject.unwrap_or_else(|| format!("A reminder about {}", announcement.name)),
+        body: render_for_everyone(&announcement.message, &announcement.name, &list),
+    };
+    Ok(Json(notify::announce(&providers, &notice).await).into_response())
+}
+
+/// Values stay hidden until the seed is ready, even from the organizer.
+async fn entropy_round(State(db): State<Shared>, Path(event): Path<String>) -> Result<Response, DbError> {
+    Ok(match db.lock().unwrap().entropy_round(&event)? {
+        Some(round) => Json(round.redacted()).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    })
+}
+
+async fn set_entropy_phase(
+    State(db): State<Shared>,
+    Path(event): Path<String>,
+    Json(phase): Json<Phase>,
+) -> Result<Json<Round>, DbError> {
+    Ok(Json(db.lock().unwrap().set_entropy_phase(&event, phase)?.redacted()))
+}
+
+/// Only the round's id and phase and whose link it is, so participants can't
+/// see who else is taking part.
+async fn contribution_status(State(db): State<Shared>, Path(token): Path<String>) -> Result<Response, DbError> {
+    Ok(match db.lock().unwrap().contribution_status(&token)? {
+        Some(status) => Json(status).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    })
+}
+
+#[derive(Deserialize)]
+struct Pledge {
+    commitment: String,
+}
+
+/// Hands back the secret the contributor needs to reveal their value.
+async fn contribute(
+    State(db): State<Shared>,
+    Path(token): Path<String>,
+    Json(pledge): Json<Pledge>,
+) -> Result<(StatusCode, Json<Value>), DbError> {
+    let secret = db.lock().unwrap().contribute(&token, &pledge.commitment)?;
+    Ok((StatusCode::CREATED, Json(json!({ "secret": secret }))))
+}
+
+#[derive(Deserialize)]
+struct Reveal {
+    value: String,
+    secret: String,
+}
+
+async fn reveal_contribution(
+    State(db): State<Shared>,
+    Path(token): Path<String>,
+    Json(reveal): Json<Reveal>,
+) -> Result<StatusCode, DbError> {
+    db.lock().unwrap().reveal_contribution(&token, &reveal.value, &reveal.secret)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    const LIST: &str = r#"{"version": 5, "data": {"participants": [
+        {"name": "Alice", "giving_to": null, "receiving_from": null, "excluding": [], "drawn": false, "email": "alice@example.com"},
+        {"name": "Bob", "giving_to": null, "receiving_from": null, "excluding": [], "drawn": false}
+    ], "results": [{"givers": ["Alice"], "receivers": ["Bob"]}, {"givers": ["Bob"], "receivers": ["Alice"]}]}}"#;
+
+    const KEY: &str = "organizer-key";
+
+    fn app(notifiers: Notifiers) -> Router {
+        router(Database::in_memory().unwrap(), notifiers, KEY)
+    }
+
+    /// Sends a request as the organizer.
+    async fn send(app: &Router, method: &str, uri: &str, body: &str) -> (StatusCode, String) {
+        send_with_key(app, method, uri, body, Some(KEY)).await
+    }
+
+    async fn send_with_key(app: &Router, method: &str, uri: &str, body: &str, key: Option<&str>) -> (StatusCode, String) {
+        let mut request = Request::builder().method(method).uri(uri).header("content-type", "application/json");
+        if let Some(key) = key {
+            request = request.header("authorization", format!("Bearer {key}"));
+        }
+        let request = request.body(Body::from(body.to_string())).unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn entries_round_trip_through_the_api() {
+        let app = app(Notifiers::default());
+        let index = r#"{"version":1,"data":{"events":[],"current":"satan","next_id":2}}"#;
+
+        assert_eq!(send(&app, "PUT", "/api/entries/events", index).await.0, StatusCode::NO_CONTENT);
+        assert_eq!(send(&app, "GET", "/api/entries/events", "").await, (StatusCode::OK, index.to_string()));
+
+        let (status, body) = send(&app, "GET", "/api/entries", "").await;
+        assert_eq!(status, StatusCode::OK);
+        let entries: BTreeMap<String, String> = serde_json::from_str(&body).unwrap();
+        assert_eq!(entries["events"], index);
+
+        assert_eq!(send(&app, "DELETE", "/api/entries/events", "").await.0, StatusCode::NO_CONTENT);
+        assert_eq!(send(&app, "GET", "/api/entries/events", "").await.0, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn only_the_organizer_sees_their_events() {
+        let app = app(Notifiers::default());
+        send(&app, "PUT", "/api/entries/satan", LIST).await;
+        for (method, uri) in [
+            ("GET", "/api/entries"),
+            ("PUT", "/api/entries/satan"),
+            ("GET", "/api/events/satan/signups"),
+            ("GET", "/api/events/satan/deliveries"),
+            ("PUT", "/api/events/satan/notifications"),
+            ("GET", "/api/events/satan/entropy"),
+        ] {
+            assert_eq!(send_with_key(&app, method, uri, "", None).await.0, StatusCode::UNAUTHORIZED);
+            assert_eq!(send_with_key(&app, method, uri, "", Some("guess")).await.0, StatusCode::UNAUTHORIZED);
+        }
+        assert_eq!(send(&app, "GET", "/api/entries/satan", "").await.0, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn people_can_sign_up_until_the_invite_closes() {
+        let app = app(Notifiers::default());
+        assert_eq!(send(&app, "GET", "/api/events/satan/invite", "").await.0, StatusCode::NOT_FOUND);
+
+        let (_, body) = send(&app, "PUT", "/api/events/satan/invite", r#"{"name": "Office", "open": true}"#).await;
+        let token = serde_json::from_str::<Value>(&body).unwrap()["token"].as_str().unwrap().to_string();
+        // Invite links work without the organizer's key.
+        let (status, body) = send_with_key(&app, "GET", &format!("/api/invites/{token}"), "", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(!body.contains("satan"));
+
+        let form = r#"{"name": "Dana", "email": null, "wishlist": "Books", "avoid": []}"#;
+        let signups = format!("/api/invites/{token}/signups");
+        assert_eq!(send_with_key(&app, "POST", &signups, form, None).await.0, StatusCode::CREATED);
+        assert_eq!(send_with_key(&app, "POST", &signups, form, None).await.0, StatusCode::CONFLICT);
+
+        let (_, body) = send(&app, "GET", "/api/events/satan/signups", "").await;
+        let id = serde_json::from_str::<Value>(&body).unwrap()[0]["id"].as_i64().unwrap();
+        let status = format!("/api/signups/{id}/status");
+        assert_eq!(send(&app, "PUT", &status, r#""Approved""#).await.0, StatusCode::NO_CONTENT);
+
+        send(&app, "PUT", "/api/events/satan/invite", r#"{"name": "Office", "open": false}"#).await;
+        let form = r#"{"name": "Erin", "email": null, "wishlist": null, "avoid": []}"#;
+        assert_eq!(send(&app, "POST", &signups, form).await.0, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn results_are_emailed_to_givers() {
+        let (port, received) = notify::catcher::start();
+        let mailer = notify::Mailer::new(&notify::SmtpSettings {
+            host: "127.0.0.1".to_string(),
+            port,
+            from: "santa@example.com".to_string(),
+            username: None,
+            password: None,
+            tls: false,
+        })
+        .unwrap();
+        let notifiers = Notifiers {
+            mailer: Some(Arc::new(mailer)),
+            ..Notifiers::default()
+        };
+        let app = app(notifiers);
+        send(&app, "PUT", "/api/entries/satan", LIST).await;
+
+        let (status, body) = send(&app, "POST", "/api/events/satan/deliveries", r#"{"name": "Office"}"#).await;
+        assert_eq!(status, StatusCode::OK);
+        let deliveries: Vec<Delivery> = serde_json::from_str(&body).unwrap();
+        assert_eq!(deliveries[0].status, DeliveryStatus::Sent);
+        assert_eq!(deliveries[1].status, DeliveryStatus::NoAddress);
+        assert!(received.lock().unwrap()[0].contains("Secret Santa for Bob in Office"));
+
+        // Sending again skips anyone who already has theirs, unless asked.
+        send(&app, "POST", "/api/events/satan/deliveries", r#"{"name": "Office"}"#).await;
+        assert_eq!(received.lock().unwrap().len(), 1);
+        let (_, body) = send(&app, "POST", "/api/events/satan/deliveries", r#"{"name": "Office", "giver": "Alice"}"#).await;
+        assert_eq!(received.lock().unwrap().len(), 2);
+        assert!(body.contains(r#""attempts":2"#));
+    }
+
+    #[tokio::test]
+    async fn results_need_somewhere_private_to_go() {
+        let app = app(Notifiers::default());
+        let (status, _) = send(&app, "POST", "/api/events/satan/deliveries", r#"{"name": "Office"}"#).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+
+        // A webhook alone won't do, since everyone in the chat would see.
+        let settings = r#"{"email": false, "webhook": "http://localhost/hook", "files": false}"#;
+        send(&app, "PUT", "/api/events/satan/notifications", settings).await;
+        let (status, _) = send(&app, "POST", "/api/events/satan/deliveries", r#"{"name": "Office"}"#).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn chats_hear_about_the_draw_and_reminders() {
+        let (base, posted) = notify::webhook_catcher::start().await;
+        let outbox = std::env::temp_dir().join(format!("secret-satan-api-outbox-{}", std::process::id()));
+        let notifiers = Notifiers {
+            outbox: Some(outbox.clone()),
+            ..Notifiers::default()
+        };
+        let app = app(notifiers);
+        send(&app, "PUT", "/api/entries/satan", LIST).await;
+        let (_, body) = send(&app, "GET", "/api/events/satan/notifications", "").await;
+        let options: NotificationOptions = serde_json::from_str(&body).unwrap();
+        assert_eq!((options.settings, options.email_available, options.files_available), (NotificationSettings::default(), false, true));
+
+        let settings = format!(r#"{{"email": true, "webhook": "{base}/hook", "files": true}}"#);
+        assert_eq!(send(&app, "PUT", "/api/events/satan/notifications", &settings).await.0, StatusCode::NO_CONTENT);
+        let (_, body) = send(&app, "POST", "/api/events/satan/deliveries", r#"{"name": "Office"}"#).await;
+        let deliveries: Vec<Delivery> = serde_json::from_str(&body).unwrap();
+        assert!(deliveries.iter().all(|delivery| delivery.status == DeliveryStatus::Sent));
+        assert!(outbox.join("satan").join("Bob.txt").exists());
+        // The chat is told the draw happened, never who got whom.
+        let text = posted.lock().unwrap()[0]["text"].as_str().unwrap().to_string();
+        assert!(text.contains("Names have been drawn for Office"));
+        assert!(!text.contains("Bob"));
+
+        let reminder = r#"{"name": "Office", "message": "Don't forget {event}!"}"#;
+        let (status, body) = send(&app, "POST", "/api/events/satan/announcements", reminder).await;
+        assert_eq!((status, body.as_str()), (StatusCode::OK, "[]"));
+        assert!(posted.lock().unwrap()[1]["text"].as_str().unwrap().ends_with("Don't forget Office!"));
+        assert!(outbox.join("satan").join("everyone-1.txt").exists());
+        std::fs::remove_dir_all(outbox).unwrap();
+    }
+
+    #[tokio::test]
+    async fn contributions_are_committed_before_they_are_revealed() {
+        let app = app(Notifiers::default());
+        send(&app, "PUT", "/api/entries/satan", LIST).await;
+        let (_, body) = send(&app, "PUT", "/api/events/satan/entropy", r#""Committing""#).await;
+        let round: Round = serde_json::from_str(&body).unwrap();
+        let links: Vec<String> = round.seats.iter().map(|seat| format!("/api/contributions/{}", seat.token)).collect();
+        let pledge = |value: &str| format!(r#"{{"commitment": "{}"}}"#, secret_satan_engine::entropy::commit(value));
+
+        let (status, body) = send_with_key(&app, "POST", &format!("{}/commitments", links[0]), &pledge("tinsel"), None).await;
+        assert_eq!(status, StatusCode::CREATED);
+        let alice = serde_json::from_str::<Value>(&body).unwrap()["secret"].as_str().unwrap().to_string();
+        assert_eq!(send(&app, "POST", &format!("{}/commitments", links[0]), &pledge("coal")).await.0, StatusCode::CONFLICT);
+        assert_eq!(send(&app, "POST", "/api/contributions/guess/commitments", &pledge("coal")).await.0, StatusCode::FORBIDDEN);
+        let (_, body) = send(&app, "POST", &format!("{}/commitments", links[1]), &pledge("sleigh")).await;
+        let bob = serde_json::from_str::<Value>(&body).unwrap()["secret"].as_str().unwrap().to_string();
+        let reveal = |value: &str, secret: &str| format!(r#"{{"value": "{value}", "secret": "{secret}"}}"#);
+        assert_eq!(send(&app, "POST", &format!("{}/reveals", links[0]), &reveal("tinsel", &alice)).await.0, StatusCode::FORBIDDEN);
+
+        send(&app, "PUT", "/api/events/satan/entropy", r#""Revealing""#).await;
+        let (_, body) = send_with_key(&app, "GET", &links[0], "", None).await;
+        let status = serde_json::from_str::<Value>(&body).unwrap();
+        assert_eq!((status["phase"].as_str(), status["name"].as_str()), (Some("Revealing"), Some("Alice")));
+        assert_eq!(send(&app, "POST", &format!("{}/reveals", links[0]), &reveal("holly", &alice)).await.0, StatusCode::BAD_REQUEST);
+        assert_eq!(send(&app, "POST", &format!("{}/reveals", links[0]), &reveal("tinsel", "guess")).await.0, StatusCode::FORBIDDEN);
+        assert_eq!(send(&app, "POST", &format!("{}/reveals", links[0]), &reveal("tinsel", &alice)).await.0, StatusCode::NO_CONTENT);
+
+        // The organizer can't see Alice's value while Bob could still hold his back.
+        let (_, body) = send(&app, "GET", "/api/events/satan/entropy", "").await;
+        let round: Round = serde_json::from_str(&body).unwrap();
+        assert!(round.revealed().is_empty() && round.pledges[0].revealed);
+
+        send(&app, "POST", &format!("{}/reveals", links[1]), &reveal("sleigh", &bob)).await;
+        let (_, body) = send(&app, "GET", "/api/events/satan/entropy", "").await;
+        let round: Round = serde_json::from_str(&body).unwrap();
+        assert_eq!(round.seed().unwrap().contributions[0].value, "tinsel");
+    }
+}
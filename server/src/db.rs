@@ -0,0 +1,897 @@
+//! The server's SQLite database.
+//!
+//! The web app reads and writes whole entries by key, the same as it does
+//! with localStorage. Lists are taken apart into events, participants,
+//! exclusions and results so they can be queried, and everything else, like
+//! the event index and undo history, is kept as-is.
+use rusqlite::{params, Connection, OptionalExtension};
+use secret_satan_engine::version::{self, upgrade};
+use rand::RngCore;
+use secret_satan_engine::delivery::{Delivery, DeliveryStatus, NotificationSettings};
+use secret_satan_engine::entropy::{EntropyError, Phase, Round, RoundStatus};
+use secret_satan_engine::reveal::{reveals, Reveal};
+use secret_satan_engine::signup::{Invite, Signup, SignupError, SignupForm, SignupStatus};
+use secret_satan_engine::{Assignment, Participant, SecretSatan};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+
+/// `MIGRATIONS[n]` brings a database at `PRAGMA user_version` `n` up to
+/// `n + 1`. Add to the end whenever the tables change. The first one is safe
+/// to rerun, since databases made before versioning are at 0.
+const MIGRATIONS: &[&str] = &[
+    "
+CREATE TABLE IF NOT EXISTS entries (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS events (
+    key TEXT PRIMARY KEY,
+    mode TEXT NOT NULL,
+    units TEXT NOT NULL,
+    rotation TEXT,
+    viewed TEXT NOT NULL,
+    budget TEXT,
+    date TEXT
+);
+CREATE TABLE IF NOT EXISTS participants (
+    event TEXT NOT NULL REFERENCES events(key) ON DELETE CASCADE,
+    position INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    email TEXT,
+    role TEXT NOT NULL,
+    tags TEXT NOT NULL,
+    drawn INTEGER NOT NULL,
+    giving_to TEXT,
+    receiving_from TEXT,
+    PRIMARY KEY (event, position)
+);
+CREATE TABLE IF NOT EXISTS exclusions (
+    event TEXT NOT NULL REFERENCES events(key) ON DELETE CASCADE,
+    participant TEXT NOT NULL,
+    excluding TEXT NOT NULL,
+    PRIMARY KEY (event, participant, excluding)
+);
+CREATE TABLE IF NOT EXISTS results (
+    event TEXT NOT NULL REFERENCES events(key) ON DELETE CASCADE,
+    position INTEGER NOT NULL,
+    givers TEXT NOT NULL,
+    receivers TEXT NOT NULL,
+    PRIMARY KEY (event, position)
+);
+",
+    "
+ALTER TABLE participants ADD COLUMN wishlist TEXT;
+CREATE TABLE invites (
+    token TEXT PRIMARY KEY,
+    event TEXT NOT NULL UNIQUE,
+    name TEXT NOT NULL,
+    open INTEGER NOT NULL
+);
+CREATE TABLE signups (
+    id INTEGER PRIMARY KEY,
+    invite TEXT NOT NULL REFERENCES invites(token) ON DELETE CASCADE,
+    form TEXT NOT NULL,
+    status TEXT NOT NULL
+);
+",
+    "
+CREATE TABLE deliveries (
+    event TEXT NOT NULL,
+    giver TEXT NOT NULL,
+    email TEXT,
+    receiver TEXT NOT NULL,
+    status TEXT NOT NULL,
+    attempts INTEGER NOT NULL,
+    PRIMARY KEY (event, giver)
+);
+",
+    "
+CREATE TABLE notifications (
+    event TEXT PRIMARY KEY,
+    settings TEXT NOT NULL
+);
+",
+    "
+CREATE TABLE entropy (
+    event TEXT PRIMARY KEY,
+    round TEXT NOT NULL
+);
+",
+    "
+CREATE TABLE settings (
+    name TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+",
+    "
+CREATE TABLE participant_exclusions (
+    event TEXT NOT NULL,
+    position INTEGER NOT NULL,
+    excluding TEXT NOT NULL,
+    PRIMARY KEY (event, position, excluding),
+    FOREIGN KEY (event, position) REFERENCES participants(event, position) ON DELETE CASCADE
+);
+INSERT OR IGNORE INTO participant_exclusions (event, position, excluding)
+    SELECT exclusions.event, participants.position, exclusions.excluding
+    FROM exclusions JOIN participants ON participants.event = exclusions.event AND participants.name = exclusions.participant
+    ORDER BY exclusions.rowid;
+DROP TABLE exclusions;
+ALTER TABLE participant_exclusions RENAME TO exclusions;
+",
+];
+
+#[derive(Debug)]
+pub enum DbError {
+    Sqlite(rusqlite::Error),
+    /// A stored column didn't hold what it should.
+    Corrupt(String),
+    /// Something saved under a list's key that doesn't read as a list.
+    NotAList(String),
+    Signup(SignupError),
+    Entropy(EntropyError),
+}
+
+impl fmt::Display for DbError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DbError::Sqlite(error) => write!(f, "{error}"),
+            DbError::Corrupt(message) => write!(f, "The database is damaged: {message}"),
+            DbError::NotAList(message) => write!(f, "That isn't a list: {message}"),
+            DbError::Signup(error) => write!(f, "{error}"),
+            DbError::Entropy(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl std::error::Error for DbError {}
+
+impl From<rusqlite::Error> for DbError {
+    fn from(error: rusqlite::Error) -> DbError {
+        DbError::Sqlite(error)
+    }
+}
+
+impl From<SignupError> for DbError {
+    fn from(error: SignupError) -> DbError {
+        DbError::Signup(error)
+    }
+}
+
+impl From<EntropyError> for DbError {
+    fn from(error: EntropyError) -> DbError {
+        DbError::Entropy(error)
+    }
+}
+
+impl From<serde_json::Error> for DbError {
+    fn from(error: serde_json::Error) -> DbError {
+        DbError::Corrupt(error.to_string())
+    }
+}
+
+pub struct Database {
+    conn: Connection,
+}
+
+impl Database {
+    /// Opens the database at `path`, creating it if needed.
+    pub fn open(path: impl AsRef<Path>) -> Result<Database, DbError> {
+        Database::init(Connection::open(path)?)
+    }
+
+    /// A database that only lives as long as it's open.
+    #[cfg(test)]
+    pub fn in_memory() -> Result<Database, DbError> {
+        Database::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Database, DbError> {
+        conn.execute_batch("PRAGMA foreign_keys = ON;")?;
+        let version: i64 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        for (version, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+            conn.execute_batch(migration)?;
+            conn.execute_batch(&format!("PRAGMA user_version = {}", version + 1))?;
+        }
+        Ok(Database { conn })
+    }
+
+    /// The key the organizer's requests must carry, made the first time it's
+    /// asked for and kept from then on.
+    pub fn organizer_key(&self) -> Result<String, DbError> {
+        let key = new_token();
+        self.conn.execute(
+            "INSERT INTO settings (name, value) VALUES ('organizer_key', ?1) ON CONFLICT (name) DO NOTHING",
+            [&key],
+        )?;
+        Ok(self.conn.query_row("SELECT value FROM settings WHERE name = 'organizer_key'", [], |row| row.get(0))?)
+    }
+
+    /// Every entry, serialized the way the web app stores them.
+    pub fn entries(&self) -> Result<BTreeMap<String, String>, DbError> {
+        let mut entries = BTreeMap::new();
+        let mut statement = self.conn.prepare("SELECT key, value FROM entries")?;
+        for row in statement.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))? {
+            let (key, value) = row?;
+            entries.insert(key, value);
+        }
+        let keys: Vec<String> = self
+            .conn
+            .prepare("SELECT key FROM events")?
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<_, _>>()?;
+        for key in keys {
+            if let Some(list) = self.list(&key)? {
+                entries.insert(key, version::to_string(&list)?);
+            }
+        }
+        Ok(entries)
+    }
+
+    pub fn get(&self, key: &str) -> Result<Option<String>, DbError> {
+        if let Some(list) = self.list(key)? {
+            return Ok(Some(version::to_string(&list)?));
+        }
+        Ok(self
+            .conn
+            .query_row("SELECT value FROM entries WHERE key = ?1", [key], |row| row.get(0))
+            .optional()?)
+    }
+
+    /// Stores `value` under `key`, replacing whatever was there. Lists,
+    /// told apart by their key, are taken apart into their own tables.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), DbError> {
+        let transaction = self.conn.transaction()?;
+        delete(&transaction, key)?;
+        if is_list_key(key) {
+            let list = upgrade::<SecretSatan>(value).map_err(DbError::NotAList)?;
+            insert_list(&transaction, key, &list)?;
+        } else {
+            transaction.execute("INSERT INTO entries (key, value) VALUES (?1, ?2)", params![key, value])?;
+        }
+        transaction.commit()?;
+        Ok(())
+    }
+
+    /// Removes the entry under `key`, along with any invite to it, record of
+    /// results sent about it, where it sends them and contributions to its
+    /// seed.
+    pub fn remove(&self, key: &str) -> Result<(), DbError> {
+        delete(&self.conn, key)?;
+        self.conn.execute("DELETE FROM invites WHERE event = ?1", [key])?;
+        self.conn.execute("DELETE FROM deliveries WHERE event = ?1", [key])?;
+        self.conn.execute("DELETE FROM notifications WHERE event = ?1", [key])?;
+        self.conn.execute("DELETE FROM entropy WHERE event = ?1", [key])?;
+        Ok(())
+    }
+
+    /// The list stored under `key`, if there is one.
+    pub fn list(&self, key: &str) -> Result<Option<SecretSatan>, DbError> {
+        let event = self
+            .conn
+            .query_row(
+                "SELECT mode, units, rotation, viewed, budget, date FROM events WHERE key = ?1",
+                [key],
+                |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, Option<String>>(2)?,
+                        row.get::<_, String>(3)?,
+                        row.get::<_, Option<String>>(4)?,
+                        row.get::<_, Option<String>>(5)?,
+                    ))
+                },
+            )
+            .optional()?;
+        let Some((mode, units, rotation, viewed, budget, date)) = event else {
+            return Ok(None);
+        };
+
+        let mut participants = Vec::new();
+        let mut statement = self.conn.prepare(
+            "SELECT position, name, email, role, tags, drawn, giving_to, receiving_from, wishlist
+             FROM participants WHERE event = ?1 ORDER BY position",
+        )?;
+        let rows = statement.query_map([key], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, String>(4)?,
+                row.get::<_, bool>(5)?,
+                row.get::<_, Option<String>>(6)?,
+                row.get::<_, Option<String>>(7)?,
+                row.get::<_, Option<String>>(8)?,
+            ))
+        })?;
+        for row in rows {
+            let (position, name, email, role, tags, drawn, giving_to, receiving_from, wishlist) = row?;
+            let excluding = self
+                .conn
+                .prepare("SELECT excluding FROM exclusions WHERE event = ?1 AND position = ?2 ORDER BY rowid")?
+                .query_map(params![key, position], |row| row.get(0))?
+                .collect::<Result<_, _>>()?;
+            participants.push(Participant {
+                name,
+                giving_to,
+                receiving_from,
+                excluding,
+                drawn,
+                role: serde_json::from_str(&role)?,
+                email,
+                tags: serde_json::from_str(&tags)?,
+                wishlist,
+            });
+        }
+
+        let mut results = Vec::new();
+        let mut statement = self
+            .conn
+            .prepare("SELECT givers, receivers FROM results WHERE event = ?1 ORDER BY position")?;
+        for row in statement.query_map([key], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))? {
+            let (givers, receivers) = row?;
+            results.push(Assignment {
+                givers: serde_json::from_str(&givers)?,
+                receivers: serde_json::from_str(&receivers)?,
+            });
+        }
+
+        Ok(Some(SecretSatan {
+            participants,
+            units: serde_json::from_str(&units)?,
+            mode: serde_json::from_str(&mode)?,
+            rotation: rotation.as_deref().map(serde_json::from_str).transpose()?,
+            results,
+            viewed: serde_json::from_str(&viewed)?,
+            budget,
+            date,
+        }))
+    }
+    /// The invite link for the event stored under `event`, if it has one.
+    pub fn invite(&self, event: &str) -> Result<Option<Invite>, DbError> {
+        self.find_invite("event", event)
+    }
+
+    pub fn invite_by_token(&self, token: &str) -> Result<Option<Invite>, DbError> {
+        self.find_invite("token", token)
+    }
+
+    fn find_invite(&self, column: &str, value: &str) -> Result<Option<Invite>, DbError> {
+        Ok(self
+            .conn
+            .query_row(
+                &format!("SELECT token, event, name, open FROM invites WHERE {column} = ?1"),
+                [value],
+                |row| {
+                    Ok(Invite {
+                        token: row.get(0)?,
+                        event: row.get(1)?,
+                        name: row.get(2)?,
+                        open: row.get(3)?,
+                    })
+                },
+            )
+            .optional()?)
+    }
+
+    /// Names and opens or closes an event's invite, creating it the first time.
+    pub fn save_invite(&self, event: &str, name: &str, open: bool) -> Result<Invite, DbError> {
+        let token = match self.invite(event)? {
+            Some(invite) => invite.token,
+            None => new_token(),
+        };
+        self.conn.execute(
+            "INSERT INTO invites (token, event, name, open) VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT (token) DO UPDATE SET name = excluded.name, open = excluded.open",
+            params![token, event, name, open],
+        )?;
+        Ok(Invite {
+            token,
+            event: event.to_string(),
+            name: name.to_string(),
+            open,
+        })
+    }
+
+    /// Everyone who has signed up to the event stored under `event`, in the
+    /// order they did.
+    pub fn signups(&self, event: &str) -> Result<Vec<Signup>, DbError> {
+        let mut statement = self.conn.prepare(
+            "SELECT signups.id, signups.form, signups.status FROM signups
+             JOIN invites ON invites.token = signups.invite
+             WHERE invites.event = ?1 ORDER BY signups.id",
+        )?;
+        let rows = statement.query_map([event], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?))
+        })?;
+        let mut signups = Vec::new();
+        for row in rows {
+            let (id, form, status) = row?;
+            signups.push(Signup {
+                id,
+                form: serde_json::from_str(&form)?,
+                status: serde_json::from_str(&status)?,
+            });
+        }
+        Ok(signups)
+    }
+
+    /// Signs someone up through the invite with `token`, as long as it's open
+    /// and nobody on the list or already signed up has their name.
+    pub fn sign_up(&self, token: &str, form: SignupForm) -> Result<Signup, DbError> {
+        let invite = self.invite_by_token(token)?.ok_or(SignupError::UnknownInvite)?;
+        if !invite.open {
+            return Err(SignupError::Closed.into());
+        }
+        let mut taken: Vec<String> = self
+            .list(&invite.event)?
+            .map(|list| list.participants.into_iter().map(|participant| participant.name).collect())
+            .unwrap_or_default();
+        taken.extend(
+            self.signups(&invite.event)?
+                .into_iter()
+                .filter(|signup| signup.status != SignupStatus::Rejected)
+                .map(|signup| signup.form.name),
+        );
+        let form = form.validate(taken.iter().map(String::as_str))?;
+
+        let status = SignupStatus::Pending;
+        self.conn.execute(
+            "INSERT INTO signups (invite, form, status) VALUES (?1, ?2, ?3)",
+            params![token, serde_json::to_string(&form)?, serde_json::to_string(&status)?],
+        )?;
+        Ok(Signup {
+            id: self.conn.last_insert_rowid(),
+            form,
+            status,
+        })
+    }
+
+    /// Records the organizer's decision. Returns false if there's no such sign-up.
+    pub fn set_signup_status(&self, id: i64, status: SignupStatus) -> Result<bool, DbError> {
+        let changed = self.conn.execute(
+            "UPDATE signups SET status = ?1 WHERE id = ?2",
+            params![serde_json::to_string(&status)?, id],
+        )?;
+        Ok(changed > 0)
+    }
+
+    /// How sending each giver in the latest results of the event stored
+    /// under `event` their result has gone. Anyone whose result changed since
+    /// it was sent counts as not sent.
+    pub fn deliveries(&self, event: &str) -> Result<Vec<Delivery>, DbError> {
+        let Some(list) = self.list(event)? else {
+            return Ok(Vec::new());
+        };
+        let mut deliveries = Vec::new();
+        for reveal in reveals("", &list.results) {
+            let email = list
+                .participants
+                .iter()
+                .find(|participant| participant.name == reveal.giver)
+                .and_then(|participant| participant.email.clone());
+            let sent = self
+                .conn
+                .query_row(
+                    "SELECT receiver, status, attempts FROM deliveries WHERE event = ?1 AND giver = ?2",
+                    [event, reveal.giver.as_str()],
+                    |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, u32>(2)?)),
+                )
+                .optional()?
+                .filter(|(receiver, _, _)| *receiver == reveal.receiver);
+            let (status, attempts) = match sent {
+                Some((_, status, attempts)) => (serde_json::from_str(&status)?, attempts),
+                None => (DeliveryStatus::NotSent, 0),
+            };
+            deliveries.push(Delivery {
+                giver: reveal.giver,
+                email,
+                status,
+                attempts,
+            });
+        }
+        Ok(deliveries)
+    }
+
+    /// Records an attempt to send `reveal` to its giver.
+    pub fn record_delivery(
+        &self,
+        event: &str,
+        reveal: &Reveal,
+        email: Option<&str>,
+        status: &DeliveryStatus,
+    ) -> Result<(), DbError> {
+        self.conn.execute(
+            "INSERT INTO deliveries (event, giver, email, receiver, status, attempts) VALUES (?1, ?2, ?3, ?4, ?5, 1)
+             ON CONFLICT (event, giver) DO UPDATE SET
+                 email = excluded.email,
+                 attempts = CASE WHEN receiver = excluded.receiver THEN attempts + 1 ELSE 1 END,
+                 receiver = excluded.receiver,
+                 status = excluded.status",
+            params![event, reveal.giver, email, reveal.receiver, serde_json::to_string(status)?],
+        )?;
+        Ok(())
+    }
+
+    /// Where the event stored under `event` sends results and reminders.
+    pub fn notification_settings(&self, event: &str) -> Result<NotificationSettings, DbError> {
+        let settings = self
+            .conn
+            .query_row("SELECT settings FROM notifications WHERE event = ?1", [event], |row| row.get::<_, String>(0))
+            .optional()?;
+        Ok(match settings {
+            Some(settings) => serde_json::from_str(&settings)?,
+            None => NotificationSettings::default(),
+        })
+    }
+
+    pub fn save_notification_settings(&self, event: &str, settings: &NotificationSettings) -> Result<(), DbError> {
+        self.conn.execute(
+            "INSERT INTO notifications (event, settings) VALUES (?1, ?2)
+             ON CONFLICT (event) DO UPDATE SET settings = excluded.settings",
+            params![event, serde_json::to_string(settings)?],
+        )?;
+        Ok(())
+    }
+
+    /// The commit-then-reveal round for the event stored under `event`, if
+    /// one has been started.
+    pub fn entropy_round(&self, event: &str) -> Result<Option<Round>, DbError> {
+        let round = self
+            .conn
+            .query_row("SELECT round FROM entropy WHERE event = ?1", [event], |row| row.get::<_, String>(0))
+            .optional()?;
+        Ok(round.map(|round| serde_json::from_str(&round)).transpose()?)
+    }
+
+    /// Moves the event's round on to `phase`. Committing starts a new round,
+    /// dropping any earlier contributions and giving everyone on the list, or
+    /// with an approved sign-up, a link of their own. Only a round that's
+    /// revealing can be closed.
+    pub fn set_entropy_phase(&self, event: &str, phase: Phase) -> Result<Round, DbError> {
+        let round = match phase {
+            Phase::Committing => {
+                let names = self.taking_part(event)?;
+                Round::new(names.iter().map(String::as_str), &mut rand::thread_rng())
+            }
+            Phase::Revealing => Round {
+                phase,
+                ..self.entropy_round(event)?.ok_or(EntropyError::NotCollecting)?
+            },
+            Phase::Closed => {
+                let mut round = self.entropy_round(event)?.ok_or(EntropyError::NotRevealing)?;
+                round.close()?;
+                round
+            }
+        };
+        self.save_entropy_round(event, &round)?;
+        Ok(round)
+    }
+
+    /// Everyone on the event's list, and anyone whose sign-up was approved.
+    fn taking_part(&self, event: &str) -> Result<Vec<String>, DbError> {
+        let mut names: Vec<String> = self
+            .list(event)?
+            .unwrap_or_default()
+            .participants
+            .into_iter()
+            .map(|participant| participant.name)
+            .collect();
+        names.extend(
+            self.signups(event)?
+                .into_iter()
+                .filter(|signup| signup.status == SignupStatus::Approved)
+                .map(|signup| signup.form.name),
+        );
+        Ok(names)
+    }
+
+    /// Where the round stands for whoever holds the contribution link with
+    /// `token`, if it's one of a current round's.
+    pub fn contribution_status(&self, token: &str) -> Result<Option<RoundStatus>, DbError> {
+        Ok(self.round_by_seat(token)?.and_then(|(_, round)| round.status(token)))
+    }
+
+    /// Records the commitment sent through the contribution link with
+    /// `token`. Returns the secret needed to reveal it.
+    pub fn contribute(&self, token: &str, commitment: &str) -> Result<String, DbError> {
+        self.update_round(token, |round| round.commit(token, commitment, &mut rand::thread_rng()))
+    }
+
+    /// Records the value committed to through the link with `token`.
+    pub fn reveal_contribution(&self, token: &str, value: &str, secret: &str) -> Result<(), DbError> {
+        self.update_round(token, |round| round.reveal(token, value, secret))
+    }
+
+    fn update_round<T>(&self, token: &str, f: impl FnOnce(&mut Round) -> Result<T, EntropyError>) -> Result<T, DbError> {
+        let (event, mut round) = self.round_by_seat(token)?.ok_or(EntropyError::NotTakingPart)?;
+        let result = f(&mut round)?;
+        self.save_entropy_round(&event, &round)?;
+        Ok(result)
+    }
+
+    /// The event and round that the contribution link with `token` belongs to.
+    fn round_by_seat(&self, token: &str) -> Result<Option<(String, Round)>, DbError> {
+        let mut statement = self.conn.prepare("SELECT event, round FROM entropy")?;
+        let rows = statement.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?;
+        for row in rows {
+            let (event, round) = row?;
+            let round: Round = serde_json::from_str(&round)?;
+            if round.seat(token).is_some() {
+                return Ok(Some((event, round)));
+            }
+        }
+        Ok(None)
+    }
+
+    fn save_entropy_round(&self, event: &str, round: &Round) -> Result<(), DbError> {
+        self.conn.execute(
+            "INSERT INTO entropy (event, round) VALUES (?1, ?2)
+             ON CONFLICT (event) DO UPDATE SET round = excluded.round",
+            params![event, serde_json::to_string(round)?],
+        )?;
+        Ok(())
+    }
+}
+
+/// A token nobody can guess, for invite links and the organizer key.
+fn new_token() -> String {
+    let mut bytes = [0u8; 16];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Whether `key` is where the web app keeps an event's list: `satan` for
+/// the first one and `satan-<n>` for the rest. Their undo histories and the
+/// event index are kept as-is.
+fn is_list_key(key: &str) -> bool {
+    key == "satan" || key.strip_prefix("satan-").is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+}
+
+fn delete(conn: &Connection, key: &str) -> Result<(), DbError> {
+    conn.execute("DELETE FROM entries WHERE key = ?1", [key])?;
+    conn.execute("DELETE FROM events WHERE key = ?1", [key])?;
+    Ok(())
+}
+
+fn insert_list(conn: &Connection, key: &str, list: &SecretSatan) -> Result<(), DbError> {
+    conn.execute(
+        "INSERT INTO events (key, mode, units, rotation, viewed, budget, date) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            key,
+            serde_json::to_string(&list.mode)?,
+            serde_json::to_string(&list.units)?,
+            list.rotation.as_ref().map(serde_json::to_string).transpose()?,
+            serde_json::to_string(&list.viewed)?,
+            list.budget,
+            list.date,
+        ],
+    )?;
+    for (position, participant) in list.participants.iter().enumerate() {
+        conn.execute(
+            "INSERT INTO participants (event, position, name, email, role, tags, drawn, giving_to, receiving_from, wishlist)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![
+                key,
+                position as i64,
+                participant.name,
+                participant.email,
+                serde_json::to_string(&participant.role)?,
+                serde_json::to_string(&participant.tags)?,
+                participant.drawn,
+                participant.giving_to,
+                participant.receiving_from,
+                participant.wishlist,
+            ],
+        )?;
+        for excluding in &participant.excluding {
+            conn.execute(
+                "INSERT OR IGNORE INTO exclusions (event, position, excluding) VALUES (?1, ?2, ?3)",
+                params![key, position as i64, excluding],
+            )?;
+        }
+    }
+    for (position, assignment) in list.results.iter().enumerate() {
+        conn.execute(
+            "INSERT INTO results (event, position, givers, receivers) VALUES (?1, ?2, ?3, ?4)",
+            params![
+                key,
+                position as i64,
+                serde_json::to_string(&assignment.givers)?,
+                serde_json::to_string(&assignment.receivers)?,
+            ],
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use secret_satan_engine::entropy::commit;
+    use secret_satan_engine::{DrawMode, Role};
+
+    fn list() -> SecretSatan {
+        let mut list = SecretSatan::new();
+        let mut alice = Participant::new("Alice".to_string());
+        alice.excluding = vec!["Bob".to_string()];
+        alice.email = Some("alice@example.com".to_string());
+        alice.tags = vec!["office".to_string()];
+        list.add_participant(alice);
+        let mut bob = Participant::new("Bob".to_string());
+        bob.role = Role::GiverOnly;
+        list.add_participant(bob);
+        list.add_participant(Participant::new("Charlie".to_string()));
+        list.mode = DrawMode::GroupGift { min_team_size: 1, max_team_size: 2 };
+        list.set_results(vec![Assignment {
+            givers: vec!["Alice".to_string(), "Bob".to_string()],
+            receivers: vec!["Charlie".to_string()],
+        }]);
+        list.mark_viewed("Alice");
+        list.budget = Some("$20".to_string());
+        list
+    }
+
+    #[test]
+    fn the_organizer_key_is_kept() {
+        let db = Database::in_memory().unwrap();
+        let key = db.organizer_key().unwrap();
+        assert_eq!(key.len(), 32);
+        assert_eq!(db.organizer_key().unwrap(), key);
+    }
+
+    #[test]
+    fn lists_are_stored_in_their_own_tables() {
+        let mut db = Database::in_memory().unwrap();
+        db.set("satan", &version::to_string(&list()).unwrap()).unwrap();
+
+        let exclusions: i64 = db.conn.query_row("SELECT COUNT(*) FROM exclusions", [], |row| row.get(0)).unwrap();
+        let entries: i64 = db.conn.query_row("SELECT COUNT(*) FROM entries", [], |row| row.get(0)).unwrap();
+        assert_eq!(exclusions, 1);
+        assert_eq!(entries, 0);
+        assert_eq!(db.list("satan").unwrap(), Some(list()));
+        assert_eq!(upgrade::<SecretSatan>(&db.get("satan").unwrap().unwrap()), Ok(list()));
+    }
+
+    #[test]
+    fn other_entries_are_kept_as_is() {
+        let mut db = Database::in_memory().unwrap();
+        let index = r#"{"version":1,"data":{"events":[],"current":"satan","next_id":2}}"#;
+        db.set("events", index).unwrap();
+        db.set("satan", &version::to_string(&list()).unwrap()).unwrap();
+
+        let entries = db.entries().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries["events"], index);
+    }
+
+    #[test]
+    fn participants_sharing_a_name_keep_their_own_exclusions() {
+        let mut db = Database::in_memory().unwrap();
+        let mut list = SecretSatan::new();
+        let mut first = Participant::new("Ann".to_string());
+        first.excluding = vec!["Bob".to_string()];
+        let mut second = Participant::new("Ann".to_string());
+        second.excluding = vec!["Charlie".to_string()];
+        list.participants = vec![first, second];
+        db.set("satan", &version::to_string(&list).unwrap()).unwrap();
+        assert_eq!(db.list("satan").unwrap(), Some(list));
+    }
+
+    #[test]
+    fn entries_are_routed_by_their_key() {
+        let mut db = Database::in_memory().unwrap();
+        let empty = version::to_string(&SecretSatan::new()).unwrap();
+        db.set("satan-2-history", &empty).unwrap();
+        db.set("satan-2", &empty).unwrap();
+        assert_eq!(db.list("satan-2-history").unwrap(), None);
+        assert_eq!(db.get("satan-2-history").unwrap(), Some(empty));
+        assert_eq!(db.list("satan-2").unwrap(), Some(SecretSatan::new()));
+        assert!(matches!(db.set("satan", "[]"), Err(DbError::NotAList(_))));
+    }
+
+    #[test]
+    fn exclusions_move_over_to_participant_rows() {
+        let conn = Connection::open_in_memory().unwrap();
+        for migration in &MIGRATIONS[..6] {
+            conn.execute_batch(migration).unwrap();
+        }
+        conn.execute_batch(
+            "PRAGMA user_version = 6;
+             INSERT INTO events (key, mode, units, viewed) VALUES ('satan', '\"OneToOne\"', '[]', '[]');
+             INSERT INTO participants (event, position, name, role, tags, drawn) VALUES ('satan', 0, 'Alice', '\"Both\"', '[]', 0);
+             INSERT INTO exclusions (event, participant, excluding) VALUES ('satan', 'Alice', 'Bob');",
+        )
+        .unwrap();
+        let db = Database::init(conn).unwrap();
+        assert_eq!(db.list("satan").unwrap().unwrap().participants[0].excluding, vec!["Bob".to_string()]);
+    }
+
+    #[test]
+    fn saving_again_replaces_the_list() {
+        let mut db = Database::in_memory().unwrap();
+        db.set("satan", &version::to_string(&list()).unwrap()).unwrap();
+        db.set("satan", &version::to_string(&SecretSatan::new()).unwrap()).unwrap();
+        assert_eq!(db.list("satan").unwrap(), Some(SecretSatan::new()));
+
+        let participants: i64 = db.conn.query_row("SELECT COUNT(*) FROM participants", [], |row| row.get(0)).unwrap();
+        assert_eq!(participants, 0);
+
+        db.remove("satan").unwrap();
+        assert_eq!(db.get("satan").unwrap(), None);
+    }
+
+    #[test]
+    fn sign_ups_go_through_the_invite() {
+        let mut db = Database::in_memory().unwrap();
+        db.set("satan", &version::to_string(&list()).unwrap()).unwrap();
+        let invite = db.save_invite("satan", "Office", true).unwrap();
+        assert_eq!(db.save_invite("satan", "Office party", true).unwrap().token, invite.token);
+
+        let form = |name: &str| SignupForm {
+            name: name.to_string(),
+            ..SignupForm::default()
+        };
+        let dana = db.sign_up(&invite.token, form("Dana")).unwrap();
+        assert!(matches!(db.sign_up(&invite.token, form("alice")), Err(DbError::Signup(SignupError::NameTaken(_)))));
+        assert!(matches!(db.sign_up(&invite.token, form("Dana")), Err(DbError::Signup(SignupError::NameTaken(_)))));
+        assert!(matches!(db.sign_up("nope", form("Erin")), Err(DbError::Signup(SignupError::UnknownInvite))));
+
+        assert!(db.set_signup_status(dana.id, SignupStatus::Approved).unwrap());
+        assert_eq!(db.signups("satan").unwrap()[0].status, SignupStatus::Approved);
+
+        db.save_invite("satan", "Office party", false).unwrap();
+        assert!(matches!(db.sign_up(&invite.token, form("Erin")), Err(DbError::Signup(SignupError::Closed))));
+
+        db.remove("satan").unwrap();
+        assert_eq!(db.invite("satan").unwrap(), None);
+    }
+
+    #[test]
+    fn deliveries_follow_the_latest_results() {
+        let mut db = Database::in_memory().unwrap();
+        db.set("satan", &version::to_string(&list()).unwrap()).unwrap();
+        let reveal = reveals("Office", &list().results).remove(0);
+        assert_eq!(db.deliveries("satan").unwrap()[0].status, DeliveryStatus::NotSent);
+
+        db.record_delivery("satan", &reveal, Some("alice@example.com"), &DeliveryStatus::Failed("busy".to_string()))
+            .unwrap();
+        db.record_delivery("satan", &reveal, Some("alice@example.com"), &DeliveryStatus::Sent).unwrap();
+        let delivery = db.deliveries("satan").unwrap().remove(0);
+        assert_eq!((delivery.status, delivery.attempts), (DeliveryStatus::Sent, 2));
+
+        // A new draw means everyone needs telling again.
+        let mut redrawn = list();
+        redrawn.results[0].receivers = vec!["Bob".to_string()];
+        db.set("satan", &version::to_string(&redrawn).unwrap()).unwrap();
+        assert_eq!(db.deliveries("satan").unwrap()[0].status, DeliveryStatus::NotSent);
+    }
+
+    #[test]
+    fn contributions_go_through_each_persons_link() {
+        let mut db = Database::in_memory().unwrap();
+        db.set("satan", &version::to_string(&list()).unwrap()).unwrap();
+        let round = db.set_entropy_phase("satan", Phase::Committing).unwrap();
+        let alice = round.seats.iter().find(|seat| seat.name == "Alice").unwrap().token.clone();
+        assert!(matches!(db.contribute("guess", &commit("tinsel")), Err(DbError::Entropy(EntropyError::NotTakingPart))));
+
+        let secret = db.contribute(&alice, &commit("tinsel")).unwrap();
+        assert!(matches!(db.contribute(&alice, &commit("coal")), Err(DbError::Entropy(EntropyError::AlreadyCommitted))));
+        let status = db.contribution_status(&alice).unwrap().unwrap();
+        assert_eq!((status.phase, status.name.as_str()), (Phase::Committing, "Alice"));
+        db.set_entropy_phase("satan", Phase::Revealing).unwrap();
+        db.reveal_contribution(&alice, "tinsel", &secret).unwrap();
+
+        let round = db.entropy_round("satan").unwrap().unwrap();
+        assert_eq!(round.revealed()[0].value, "tinsel");
+        assert_eq!(round.id, status.id);
+        assert_eq!(db.set_entropy_phase("satan", Phase::Closed).unwrap().phase, Phase::Closed);
+
+        // Starting over is a new round with new links.
+        db.set_entropy_phase("satan", Phase::Committing).unwrap();
+        assert_eq!(db.contribution_status(&alice).unwrap(), None);
+        assert!(matches!(db.set_entropy_phase("satan", Phase::Closed), Err(DbError::Entropy(EntropyError::NotRevealing))));
+    }
+}
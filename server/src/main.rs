@@ -0,0 +1,217 @@
+//! Runs Secret Satan with its data kept in SQLite instead of the browser, so
+//! lists aren't stuck on one device.
+//!
+//! Serves the API the web app stores its data through and, optionally, the
+//! built site itself. Given an SMTP server or an outbox folder, it can also
+//! send everyone their result.
+//!
+//! Only the organizer can see and change events. Their key is made the first
+//! time the server runs and kept in the database; the server prints a link
+//! that hands it to the app.
+mod api;
+mod db;
+mod notify;
+
+use db::Database;
+use notify::{Mailer, Notifiers, SmtpSettings};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use tower_http::services::{ServeDir, ServeFile};
+
+/// What comes before the organizer's key in the link the server prints. The
+/// app reads it the same way.
+const ORGANIZER_PREFIX: &str = "organizer=";
+
+const USAGE: &str = "Usage: secret-satan-server [OPTIONS]
+
+Options:
+  --db <PATH>     SQLite database to keep everything in [default: secret-satan.db]
+  --host <ADDR>   Address to listen on [default: 127.0.0.1]. Use 0.0.0.0 so
+                  invite links work from other devices
+  --port <PORT>   Port to listen on [default: 3000]
+  --site <DIR>    Also serve the built site from DIR, e.g. dist
+  -h, --help      Show this message
+
+Email:
+  --smtp-host <HOST>   SMTP server to send results through, e.g. localhost for MailHog
+  --smtp-port <PORT>   Its port [default: 25]
+  --smtp-from <EMAIL>  Address the email comes from
+  --smtp-user <NAME>   Log in as NAME, with the password from SECRET_SATAN_SMTP_PASSWORD
+  --smtp-tls           Secure the connection with STARTTLS
+
+Files:
+  --outbox <DIR>  Let events write their messages to DIR, to send by hand";
+
+#[derive(Debug, PartialEq)]
+struct Options {
+    db: PathBuf,
+    host: IpAddr,
+    port: u16,
+    site: Option<PathBuf>,
+    smtp: Option<SmtpSettings>,
+    outbox: Option<PathBuf>,
+    help: bool,
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            db: PathBuf::from("secret-satan.db"),
+            host: IpAddr::V4(Ipv4Addr::LOCALHOST),
+            port: 3000,
+            site: None,
+            smtp: None,
+            outbox: None,
+            help: false,
+        }
+    }
+}
+
+fn parse_args(args: Vec<String>, password: Option<String>) -> Result<Options, String> {
+    let mut options = Options::default();
+    let (mut host, mut from, mut username) = (None, None, None);
+    let (mut smtp_port, mut tls) = (25, false);
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = |flag: &str| args.next().ok_or(format!("{flag} needs a value"));
+        match arg.as_str() {
+            "-h" | "--help" => options.help = true,
+            "--db" => options.db = PathBuf::from(value("--db")?),
+            "--host" => {
+                let host = value("--host")?;
+                options.host = host.parse().map_err(|_| format!("{host} isn't an IP address"))?;
+            }
+            "--port" => {
+                let port = value("--port")?;
+                options.port = port.parse().map_err(|_| format!("{port} isn't a port number"))?;
+            }
+            "--site" => options.site = Some(PathBuf::from(value("--site")?)),
+            "--smtp-host" => host = Some(value("--smtp-host")?),
+            "--smtp-port" => {
+                let port = value("--smtp-port")?;
+                smtp_port = port.parse().map_err(|_| format!("{port} isn't a port number"))?;
+            }
+            "--smtp-from" => from = Some(value("--smtp-from")?),
+            "--smtp-user" => username = Some(value("--smtp-user")?),
+            "--smtp-tls" => tls = true,
+            "--outbox" => options.outbox = Some(PathBuf::from(value("--outbox")?)),
+            other => return Err(format!("unexpected argument {other}")),
+        }
+    }
+    options.smtp = match (host, from) {
+        (Some(host), Some(from)) => Some(SmtpSettings {
+            host,
+            port: smtp_port,
+            from,
+            password: username.as_ref().and(password),
+            username,
+            tls,
+        }),
+        (None, None) => None,
+        _ => return Err("--smtp-host and --smtp-from go together".to_string()),
+    };
+    Ok(options)
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let password = std::env::var("SECRET_SATAN_SMTP_PASSWORD").ok();
+    let options = match parse_args(std::env::args().skip(1).collect(), password) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("error: {message}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    if options.help {
+        println!("{USAGE}");
+        return ExitCode::SUCCESS;
+    }
+
+    let db = match Database::open(&options.db) {
+        Ok(db) => db,
+        Err(error) => {
+            eprintln!("error: couldn't open {}: {error}", options.db.display());
+            return ExitCode::FAILURE;
+        }
+    };
+    let organizer_key = match db.organizer_key() {
+        Ok(key) => key,
+        Err(error) => {
+            eprintln!("error: couldn't read the organizer key: {error}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let mailer = match options.smtp.as_ref().map(Mailer::new).transpose() {
+        Ok(mailer) => mailer,
+        Err(error) => {
+            eprintln!("error: {error}");
+            return ExitCode::from(2);
+        }
+    };
+    let notifiers = Notifiers {
+        mailer: mailer.map(Arc::new),
+        outbox: options.outbox.clone(),
+        client: reqwest::Client::new(),
+    };
+    let mut app = api::router(db, notifiers, &organizer_key);
+    if let Some(site) = &options.site {
+        // Anything that isn't a file gets the app, which reads the URL itself.
+        app = app.fallback_service(ServeDir::new(site).fallback(ServeFile::new(site.join("index.html"))));
+    }
+
+    let address = SocketAddr::new(options.host, options.port);
+    let listener = match tokio::net::TcpListener::bind(address).await {
+        Ok(listener) => listener,
+        Err(error) => {
+            eprintln!("error: couldn't listen on {address}: {error}");
+            return ExitCode::FAILURE;
+        }
+    };
+    println!("Secret Satan is running at http://{address}");
+    println!("Organizer link, keep it to yourself: http://{address}/#{ORGANIZER_PREFIX}{organizer_key}");
+    if let Err(error) = axum::serve(listener, app).await {
+        eprintln!("error: {error}");
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn options_have_local_defaults() {
+        assert_eq!(parse_args(args(&[]), None), Ok(Options::default()));
+        let options = parse_args(args(&["--db", "party.db", "--port", "8000", "--site", "dist"]), None).unwrap();
+        assert_eq!(options.db, PathBuf::from("party.db"));
+        assert_eq!(options.host, IpAddr::V4(Ipv4Addr::LOCALHOST));
+        assert_eq!(options.port, 8000);
+        assert_eq!(options.site, Some(PathBuf::from("dist")));
+        assert_eq!(parse_args(args(&["--outbox", "mail"]), None).unwrap().outbox, Some(PathBuf::from("mail")));
+        assert!(parse_args(args(&["--port", "lots"]), None).is_err());
+        assert_eq!(parse_args(args(&["--host", "0.0.0.0"]), None).unwrap().host, IpAddr::V4(Ipv4Addr::UNSPECIFIED));
+        assert!(parse_args(args(&["--host", "anywhere"]), None).is_err());
+    }
+
+    #[test]
+    fn email_needs_a_host_and_sender() {
+        let options = parse_args(
+            args(&["--smtp-host", "localhost", "--smtp-port", "1025", "--smtp-from", "santa@example.com"]),
+            Some("secret".to_string()),
+        )
+        .unwrap();
+        let smtp = options.smtp.unwrap();
+        assert_eq!((smtp.host.as_str(), smtp.port), ("localhost", 1025));
+        // The password is only for logging in.
+        assert_eq!(smtp.password, None);
+        assert!(parse_args(args(&["--smtp-host", "localhost"]), None).is_err());
+    }
+}
@@ -0,0 +1,93 @@
+//! Writing messages to a folder, for the organizer to send by hand.
+use super::{Audience, Notice, Provider, SendError, Sending};
+use std::path::{Path, PathBuf};
+
+/// Writes each message to `{dir}/{event}/`, one file per giver plus one per
+/// announcement.
+pub struct FileSink {
+    dir: PathBuf,
+}
+
+impl FileSink {
+    pub fn new(dir: PathBuf) -> FileSink {
+        FileSink { dir }
+    }
+
+    fn write(&self, notice: &Notice) -> std::io::Result<()> {
+        let folder = self.dir.join(file_name(&notice.event));
+        std::fs::create_dir_all(&folder)?;
+        let (path, to) = match &notice.audience {
+            Audience::Giver { name, email } => (folder.join(format!("{}.txt", file_name(name))), email.clone()),
+            Audience::Everyone { emails } => (unused(&folder), Some(emails.join(", "))),
+        };
+        let to = to.filter(|to| !to.is_empty()).map(|to| format!("To: {to}\n")).unwrap_or_default();
+        std::fs::write(path, format!("{to}Subject: {}\n\n{}\n", notice.subject, notice.body))
+    }
+}
+
+/// Keeps names from reaching outside the folder or tripping up file systems.
+fn file_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| match c.is_alphanumeric() || c == ' ' || c == '-' || c == '_' {
+            true => c,
+            false => '_',
+        })
+        .collect();
+    match name.trim() {
+        "" => "_".to_string(),
+        name => name.to_string(),
+    }
+}
+
+/// The next `everyone-N.txt` that hasn't been written yet.
+fn unused(folder: &Path) -> PathBuf {
+    (1..)
+        .map(|n| folder.join(format!("everyone-{n}.txt")))
+        .find(|path| !path.exists())
+        .unwrap()
+}
+
+impl Provider for FileSink {
+    fn name(&self) -> &'static str {
+        "files"
+    }
+
+    fn private(&self) -> bool {
+        true
+    }
+
+    fn send<'a>(&'a self, notice: &'a Notice) -> Sending<'a> {
+        Box::pin(async move { self.write(notice).map_err(|error| SendError::Failed(error.to_string())) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn each_message_gets_its_own_file() {
+        let dir = std::env::temp_dir().join(format!("secret-satan-outbox-{}", std::process::id()));
+        let sink = FileSink::new(dir.clone());
+        let mut notice = Notice {
+            event: "../satan".to_string(),
+            audience: Audience::Giver {
+                name: "Alice/Bob".to_string(),
+                email: Some("alice@example.com".to_string()),
+            },
+            subject: "Your match".to_string(),
+            body: "You're giving to Carol".to_string(),
+        };
+        sink.send(&notice).await.unwrap();
+        notice.audience = Audience::Everyone { emails: Vec::new() };
+        sink.send(&notice).await.unwrap();
+        sink.send(&notice).await.unwrap();
+
+        let folder = dir.join("___satan");
+        let written = std::fs::read_to_string(folder.join("Alice_Bob.txt")).unwrap();
+        assert_eq!(written, "To: alice@example.com\nSubject: Your match\n\nYou're giving to Carol\n");
+        assert!(folder.join("everyone-2.txt").exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,202 @@
+//! Sending results and reminders out through whatever an event is set up to
+//! use: email, a chat webhook, or files for sending by hand.
+//!
+//! Each way of sending is a `Provider`. Results only ever go through private
+//! ones, where nobody but the giver sees them.
+mod files;
+mod smtp;
+mod webhook;
+
+#[cfg(test)]
+pub use smtp::catcher;
+#[cfg(test)]
+pub use webhook::catcher as webhook_catcher;
+pub use files::FileSink;
+pub use smtp::{Mailer, SmtpSettings};
+pub use webhook::Webhook;
+
+use secret_satan_engine::delivery::{DeliveryStatus, NotificationSettings};
+use std::future::Future;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::Arc;
+
+/// Who a notice is for.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Audience {
+    /// One giver, about their own result.
+    Giver { name: String, email: Option<String> },
+    /// Everyone taking part, e.g. for a reminder.
+    Everyone { emails: Vec<String> },
+}
+
+/// A message to send.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notice {
+    /// The storage key of the event it's about.
+    pub event: String,
+    pub audience: Audience,
+    pub subject: String,
+    pub body: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SendError {
+    /// The provider has no way to reach this audience, like email for
+    /// someone without an address. Not a failure in itself.
+    Unreachable,
+    Failed(String),
+}
+
+pub type Sending<'a> = Pin<Box<dyn Future<Output = Result<(), SendError>> + Send + 'a>>;
+
+/// A way of sending notices.
+pub trait Provider: Send + Sync {
+    /// What to call it when something goes wrong.
+    fn name(&self) -> &'static str;
+
+    /// Whether only the people a notice is for can see it, so results can go
+    /// through it.
+    fn private(&self) -> bool;
+
+    fn send<'a>(&'a self, notice: &'a Notice) -> Sending<'a>;
+}
+
+impl<P: Provider + ?Sized> Provider for Arc<P> {
+    fn name(&self) -> &'static str {
+        (**self).name()
+    }
+
+    fn private(&self) -> bool {
+        (**self).private()
+    }
+
+    fn send<'a>(&'a self, notice: &'a Notice) -> Sending<'a> {
+        (**self).send(notice)
+    }
+}
+
+/// Everything the server has been set up to send through. Each event picks
+/// from these with its `NotificationSettings`.
+#[derive(Clone, Default)]
+pub struct Notifiers {
+    pub mailer: Option<Arc<Mailer>>,
+    /// Where the file sink writes, if it's allowed to.
+    pub outbox: Option<PathBuf>,
+    pub client: reqwest::Client,
+}
+
+impl Notifiers {
+    /// The providers an event with `settings` sends through, leaving out any
+    /// the server isn't set up for.
+    pub fn providers(&self, settings: &NotificationSettings) -> Vec<Box<dyn Provider>> {
+        let mut providers: Vec<Box<dyn Provider>> = Vec::new();
+        if let Some(mailer) = self.mailer.clone().filter(|_| settings.email) {
+            providers.push(Box::new(mailer));
+        }
+        if let Some(outbox) = self.outbox.clone().filter(|_| settings.files) {
+            providers.push(Box::new(FileSink::new(outbox)));
+        }
+        if let Some(url) = settings.webhook.clone().filter(|url| !url.trim().is_empty()) {
+            providers.push(Box::new(Webhook::new(self.client.clone(), url)));
+        }
+        providers
+    }
+}
+
+/// Sends one giver their result through every private provider.
+pub async fn deliver(providers: &[Box<dyn Provider>], notice: &Notice) -> DeliveryStatus {
+    let mut delivered = false;
+    let mut failures = Vec::new();
+    for provider in providers.iter().filter(|provider| provider.private()) {
+        match provider.send(notice).await {
+            Ok(()) => delivered = true,
+            Err(SendError::Unreachable) => {}
+            Err(SendError::Failed(reason)) => failures.push(format!("{}: {reason}", provider.name())),
+        }
+    }
+    match (failures.is_empty(), delivered) {
+        (false, _) => DeliveryStatus::Failed(failures.join("; ")),
+        (true, true) => DeliveryStatus::Sent,
+        (true, false) => DeliveryStatus::NoAddress,
+    }
+}
+
+/// Sends a notice for everyone through every provider, returning what went
+/// wrong.
+pub async fn announce(providers: &[Box<dyn Provider>], notice: &Notice) -> Vec<String> {
+    let mut failures = Vec::new();
+    for provider in providers {
+        if let Err(SendError::Failed(reason)) = provider.send(notice).await {
+            failures.push(format!("{}: {reason}", provider.name()));
+        }
+    }
+    failures
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Remembers what it's sent, and fails when told to.
+    struct Recorder {
+        private: bool,
+        fail: bool,
+        sent: Mutex<Vec<String>>,
+    }
+
+    impl Provider for Recorder {
+        fn name(&self) -> &'static str {
+            "recorder"
+        }
+
+        fn private(&self) -> bool {
+            self.private
+        }
+
+        fn send<'a>(&'a self, notice: &'a Notice) -> Sending<'a> {
+            Box::pin(async move {
+                if self.fail {
+                    return Err(SendError::Failed("down".to_string()));
+                }
+                self.sent.lock().unwrap().push(notice.subject.clone());
+                Ok(())
+            })
+        }
+    }
+
+    fn recorder(private: bool, fail: bool) -> Box<dyn Provider> {
+        Box::new(Recorder {
+            private,
+            fail,
+            sent: Mutex::new(Vec::new()),
+        })
+    }
+
+    fn notice(audience: Audience) -> Notice {
+        Notice {
+            event: "satan".to_string(),
+            audience,
+            subject: "Hello".to_string(),
+            body: String::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn results_only_go_through_private_providers() {
+        let giver = notice(Audience::Giver {
+            name: "Alice".to_string(),
+            email: None,
+        });
+        assert_eq!(deliver(&[recorder(false, false)], &giver).await, DeliveryStatus::NoAddress);
+        assert_eq!(deliver(&[recorder(true, false)], &giver).await, DeliveryStatus::Sent);
+        assert_eq!(
+            deliver(&[recorder(true, false), recorder(true, true)], &giver).await,
+            DeliveryStatus::Failed("recorder: down".to_string())
+        );
+
+        let everyone = notice(Audience::Everyone { emails: Vec::new() });
+        assert_eq!(announce(&[recorder(false, false), recorder(true, true)], &everyone).await.len(), 1);
+    }
+}
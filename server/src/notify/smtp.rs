@@ -0,0 +1,190 @@
+//! Sending email over SMTP.
+use lettre::message::header::ContentType;
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use secret_satan_engine::delivery::Email;
+
+use super::{Audience, Notice, Provider, SendError, Sending};
+
+/// Where and how to send mail. A local catcher like MailHog needs only the
+/// host, port and sender.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SmtpSettings {
+    pub host: String,
+    pub port: u16,
+    pub from: String,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    /// Upgrade the connection with STARTTLS, as real mail servers require.
+    pub tls: bool,
+}
+
+pub struct Mailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl Mailer {
+    pub fn new(settings: &SmtpSettings) -> Result<Mailer, String> {
+        let from = settings
+            .from
+            .parse()
+            .map_err(|_| format!("{} isn't an email address", settings.from))?;
+        let mut builder = match settings.tls {
+            true => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&settings.host).map_err(|error| error.to_string())?,
+            false => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&settings.host),
+        };
+        builder = builder.port(settings.port);
+        if let (Some(username), Some(password)) = (&settings.username, &settings.password) {
+            builder = builder.credentials(Credentials::new(username.clone(), password.clone()));
+        }
+        Ok(Mailer {
+            transport: builder.build(),
+            from,
+        })
+    }
+
+    pub async fn send(&self, email: &Email) -> Result<(), String> {
+        let to = mailbox(&email.to)?;
+        let message = Message::builder().from(self.from.clone()).to(to);
+        self.deliver(message, email).await
+    }
+
+    /// Sends one message to everyone in `bcc`, so nobody sees the others'
+    /// addresses. It's addressed to the sender.
+    pub async fn send_hidden(&self, bcc: &[String], email: &Email) -> Result<(), String> {
+        let mut message = Message::builder().from(self.from.clone()).to(self.from.clone());
+        for address in bcc {
+            message = message.bcc(mailbox(address)?);
+        }
+        self.deliver(message, email).await
+    }
+
+    async fn deliver(&self, message: lettre::message::MessageBuilder, email: &Email) -> Result<(), String> {
+        let message = message
+            .subject(&email.subject)
+            .header(ContentType::TEXT_PLAIN)
+            .body(email.body.clone())
+            .map_err(|error| error.to_string())?;
+        self.transport.send(message).await.map_err(|error| error.to_string())?;
+        Ok(())
+    }
+}
+
+fn mailbox(address: &str) -> Result<Mailbox, String> {
+    address.parse().map_err(|_| format!("{address} isn't an email address"))
+}
+
+impl Provider for Mailer {
+    fn name(&self) -> &'static str {
+        "email"
+    }
+
+    fn private(&self) -> bool {
+        true
+    }
+
+    fn send<'a>(&'a self, notice: &'a Notice) -> Sending<'a> {
+        Box::pin(async move {
+            let email = |to: &str| Email {
+                to: to.to_string(),
+                subject: notice.subject.clone(),
+                body: notice.body.clone(),
+            };
+            let sent = match &notice.audience {
+                Audience::Giver { email: Some(to), .. } => Mailer::send(self, &email(to)).await,
+                Audience::Everyone { emails } if !emails.is_empty() => self.send_hidden(emails, &email("")).await,
+                _ => return Err(SendError::Unreachable),
+            };
+            sent.map_err(SendError::Failed)
+        })
+    }
+}
+
+/// A stand-in SMTP server that accepts everything and hands back what it was
+/// sent, so mail can be tested without a real one.
+#[cfg(test)]
+pub mod catcher {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+
+    /// Starts listening on a free port. Returns the port and the raw
+    /// messages received so far.
+    pub fn start() -> (u16, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let inbox = received.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else {
+                    return;
+                };
+                let inbox = inbox.clone();
+                std::thread::spawn(move || {
+                    let mut reader = BufReader::new(stream.try_clone().unwrap());
+                    let _ = stream.write_all(b"220 catcher ready\r\n");
+                    let mut line = String::new();
+                    while reader.read_line(&mut line).unwrap_or(0) > 0 {
+                        let command = line.trim_end().to_ascii_uppercase();
+                        let reply: &[u8] = if command.starts_with("EHLO") || command.starts_with("HELO") {
+                            b"250 catcher\r\n"
+                        } else if command == "DATA" {
+                            let _ = stream.write_all(b"354 go ahead\r\n");
+                            let mut message = String::new();
+                            let mut data = String::new();
+                            while reader.read_line(&mut data).unwrap_or(0) > 0 && data != ".\r\n" {
+                                message.push_str(&data);
+                                data.clear();
+                            }
+                            inbox.lock().unwrap().push(message);
+                            b"250 queued\r\n"
+                        } else if command == "QUIT" {
+                            let _ = stream.write_all(b"221 bye\r\n");
+                            return;
+                        } else {
+                            b"250 ok\r\n"
+                        };
+                        let _ = stream.write_all(reply);
+                        line.clear();
+                    }
+                });
+            }
+        });
+        (port, received)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn mail_reaches_the_server() {
+        let (port, received) = catcher::start();
+        let mailer = Mailer::new(&SmtpSettings {
+            host: "127.0.0.1".to_string(),
+            port,
+            from: "santa@example.com".to_string(),
+            username: None,
+            password: None,
+            tls: false,
+        })
+        .unwrap();
+        mailer
+            .send(&Email {
+                to: "alice@example.com".to_string(),
+                subject: "Hello".to_string(),
+                body: "You're giving to Bob".to_string(),
+            })
+            .await
+            .unwrap();
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 1);
+        assert!(received[0].contains("To: alice@example.com"));
+        assert!(received[0].contains("You're giving to Bob"));
+    }
+}
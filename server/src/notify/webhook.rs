@@ -0,0 +1,108 @@
+//! Posting to a chat webhook. Slack, Mattermost, Discord (through its
+//! `/slack` endpoint) and Matrix hookshot all take a JSON body with `text`.
+use super::{Audience, Notice, Provider, SendError, Sending};
+use serde_json::json;
+
+pub struct Webhook {
+    client: reqwest::Client,
+    url: String,
+}
+
+impl Webhook {
+    pub fn new(client: reqwest::Client, url: String) -> Webhook {
+        Webhook { client, url }
+    }
+}
+
+impl Provider for Webhook {
+    fn name(&self) -> &'static str {
+        "webhook"
+    }
+
+    /// Everyone in the channel sees what's posted.
+    fn private(&self) -> bool {
+        false
+    }
+
+    fn send<'a>(&'a self, notice: &'a Notice) -> Sending<'a> {
+        Box::pin(async move {
+            if let Audience::Giver { .. } = notice.audience {
+                return Err(SendError::Unreachable);
+            }
+            let text = format!("{}\n\n{}", notice.subject, notice.body);
+            let response = self
+                .client
+                .post(&self.url)
+                .json(&json!({ "text": text }))
+                .send()
+                .await
+                .map_err(|error| SendError::Failed(error.to_string()))?;
+            match response.status().is_success() {
+                true => Ok(()),
+                false => Err(SendError::Failed(format!("the webhook answered {}", response.status()))),
+            }
+        })
+    }
+}
+
+/// A stand-in chat service that accepts every post and hands back what it
+/// was sent.
+#[cfg(test)]
+pub mod catcher {
+    use axum::extract::State;
+    use axum::http::StatusCode;
+    use axum::routing::post;
+    use axum::{Json, Router};
+    use serde_json::Value;
+    use std::sync::{Arc, Mutex};
+
+    /// Starts listening on a free port. Returns the URL to post to and the
+    /// bodies received so far.
+    pub async fn start() -> (String, Arc<Mutex<Vec<Value>>>) {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let app = Router::new()
+            .route(
+                "/hook",
+                post(|State(inbox): State<Arc<Mutex<Vec<Value>>>>, Json(body): Json<Value>| async move {
+                    inbox.lock().unwrap().push(body);
+                    StatusCode::OK
+                }),
+            )
+            .route("/broken", post(|| async { StatusCode::INTERNAL_SERVER_ERROR }))
+            .with_state(received.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        (format!("http://{address}"), received)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn announcements_are_posted_but_results_are_not() {
+        let (base, received) = catcher::start().await;
+        let webhook = Webhook::new(reqwest::Client::new(), format!("{base}/hook"));
+        let mut notice = Notice {
+            event: "satan".to_string(),
+            audience: Audience::Everyone { emails: Vec::new() },
+            subject: "Names have been drawn".to_string(),
+            body: "Check your email.".to_string(),
+        };
+        webhook.send(&notice).await.unwrap();
+        assert_eq!(received.lock().unwrap()[0]["text"], "Names have been drawn\n\nCheck your email.");
+
+        notice.audience = Audience::Giver {
+            name: "Alice".to_string(),
+            email: None,
+        };
+        assert_eq!(webhook.send(&notice).await, Err(SendError::Unreachable));
+        assert_eq!(received.lock().unwrap().len(), 1);
+
+        notice.audience = Audience::Everyone { emails: Vec::new() };
+        let broken = Webhook::new(reqwest::Client::new(), format!("{base}/broken"));
+        assert!(matches!(broken.send(&notice).await, Err(SendError::Failed(_))));
+    }
+}
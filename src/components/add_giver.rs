@@ -1,10 +1,10 @@
 #![allow(non_snake_case)]
 
-use crate::{Participant, SecretSatan};
+use crate::Role;
 use dioxus::prelude::*;
 
 #[component]
-pub fn AddGiver(name_signal: Signal<String>, excluding_signal: Signal<String>) -> Element {
+pub fn AddGiver(name_signal: Signal<String>, excluding_signal: Signal<String>, role_signal: Signal<Role>) -> Element {
     rsx! {
         div {
             input {
@@ -26,6 +26,32 @@ pub fn AddGiver(name_signal: Signal<String>, excluding_signal: Signal<String>) -
                     excluding_signal.set(event.value().clone());
                 }
             }
+            select {
+                name: "role",
+                class: "block w-full bg-white text-gray-800 px-3 py-2 rounded-lg focus:border-red-900 mb-2",
+                onchange: move |event| {
+                    role_signal.set(match event.value().as_str() {
+                        "giver" => Role::GiverOnly,
+                        "receiver" => Role::ReceiverOnly,
+                        _ => Role::Both,
+                    });
+                },
+                option {
+                    value: "both",
+                    selected: *role_signal.read() == Role::Both,
+                    "Gives and receives a gift"
+                }
+                option {
+                    value: "giver",
+                    selected: *role_signal.read() == Role::GiverOnly,
+                    "Only gives a gift"
+                }
+                option {
+                    value: "receiver",
+                    selected: *role_signal.read() == Role::ReceiverOnly,
+                    "Only receives a gift"
+                }
+            }
         }
     }
 }
@@ -2,25 +2,72 @@
 
 use dioxus::prelude::*;
 
-use crate::components::{AddGiver, ClearListModal, DeleteParticipantModal, GuestForm, GuestList, GuestListItem, ListOutput};
-use crate::{use_persistent, Participant, SecretSatan, UsePersistent};
+use crate::components::{ClearListModal, Contribute, DeleteParticipantModal, EventDetails, EventSwitcher, GuestForm, GuestList, ImportExport, ListOutput, OrganizerLogin, RecoveryScreen, Registrations, RevealScreen, RotationPlan, SaveErrorBanner, SharedSeed, SignupScreen, SyncWatcher, UndoToolbar, VerifyDraw};
+use crate::reveal::{self, Reveal, RevealError};
+use crate::server::{self, Server};
+use crate::{use_persistent, EventIndex, History, Participant, SecretSatan, UsePersistent};
 
+/// Whatever was saved, even with nobody on it yet, so the budget, date,
+/// mode and units aren't lost.
 fn get_saved_state(storage: UsePersistent<SecretSatan>) -> Signal<SecretSatan> {
-    let mut state = SecretSatan::default();
-    if !storage.get().participants.is_empty() {
-        state = storage.get();
-    }
-    Signal::new(state)
+    Signal::new(storage.get())
+}
+
+/// The result in the page's URL fragment, if this is someone's reveal link.
+fn opened_reveal() -> Option<Result<Reveal, RevealError>> {
+    let hash = web_sys::window().and_then(|window| window.location().hash().ok()).unwrap_or_default();
+    reveal::open(&hash).transpose()
+}
+
+/// The token in the page's URL fragment, if this is someone's contribution
+/// link.
+fn opened_contribution() -> Option<String> {
+    let hash = web_sys::window().and_then(|window| window.location().hash().ok()).unwrap_or_default();
+    server::contribution_token(&hash)
+}
+
+/// The token in the page's URL fragment, if this is an invite link.
+fn opened_invite() -> Option<String> {
+    let hash = web_sys::window().and_then(|window| window.location().hash().ok()).unwrap_or_default();
+    server::invite_token(&hash)
 }
 
 #[component]
 pub fn App() -> Element {
-    let storage = use_persistent("satan", || SecretSatan::default());
+    let events = use_persistent("events", EventIndex::default);
+    let storage = use_persistent(events.get().current().storage_key(), SecretSatan::default);
+    let history = use_persistent(events.get().current().history_key(), History::default);
+    use_context_provider(|| events);
+    use_context_provider(|| storage);
+    use_context_provider(|| history);
     use_context_provider(|| get_saved_state(storage));
-    use_context_provider(|| Signal::<Vec<Participant>>::new(vec![]));
     use_context_provider(|| Signal::<Option<Participant>>::new(None));
     use_context_provider(|| Signal::<bool>::new(false));
-    let selected_participant = use_context::<Signal<Option<Participant>>>();
+    let server = use_context::<Option<Server>>();
+
+    // Someone opening their reveal link sees only their own result.
+    if let Some(reveal) = use_hook(opened_reveal) {
+        return rsx! { RevealScreen { reveal } };
+    }
+
+    // Someone opening an invite link signs themselves up.
+    if let Some(token) = use_hook(opened_invite) {
+        return rsx! { SignupScreen { token } };
+    }
+
+    // Someone opening their contribution link adds to the seed for the draw.
+    if let Some(token) = use_hook(opened_contribution) {
+        return rsx! { Contribute { token } };
+    }
+
+    // On the server, everything else is the organizer's.
+    if server.is_some_and(|server| !server.is_organizer()) {
+        return rsx! { OrganizerLogin {} };
+    }
+
+    if let Some(unreadable) = events.unreadable().or_else(|| storage.unreadable()) {
+        return rsx! { RecoveryScreen { unreadable } };
+    }
 
     rsx! {
         div {
@@ -36,12 +83,22 @@ pub fn App() -> Element {
                         class: "size-8 inline-block ml-2"
                     }
                 }
+                SaveErrorBanner {}
+                SyncWatcher {}
+                EventSwitcher {}
+                UndoToolbar {}
+                ImportExport {}
+                Registrations {}
+                EventDetails {}
                 div {
                     class: "flex flex-col gap-4 flex-wrap justify-stretch items-start",
                     GuestForm {}
                     GuestList {}
+                    SharedSeed {}
+                    RotationPlan {}
                 }
                 ListOutput {}
+                VerifyDraw {}
 
                 p {
                     class: "text-white text-sm my-4 text-center",
@@ -0,0 +1,142 @@
+use crate::entropy::{commit, Contribution, Phase, RoundStatus};
+use crate::server::{Server, ServerError};
+use crate::storage::{LocalStorageBackend, StorageBackend};
+use dioxus::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// What this device keeps until it's time to reveal.
+#[derive(Clone, PartialEq, Deserialize, Serialize)]
+struct Saved {
+    contribution: Contribution,
+    /// Handed back by the server; revealing needs it.
+    secret: String,
+    /// The round it went into. Once the organizer starts over it's no use.
+    #[serde(default)]
+    round: String,
+}
+
+/// What someone sees when they open their own contribution link: a way to
+/// add a random value to the seed for the draw, then reveal it once the
+/// organizer asks. The value stays on this device until then.
+#[component]
+pub fn Contribute(token: String) -> Element {
+    let server = use_context::<Option<Server>>();
+    let mut status = use_signal(|| None::<Result<Option<RoundStatus>, ServerError>>);
+    let mut message = use_signal(|| None::<String>);
+    let key = format!("contribution-{token}");
+    let mut saved = use_signal({
+        let key = key.clone();
+        move || LocalStorageBackend.get(&key).and_then(|raw| serde_json::from_str::<Saved>(&raw).ok())
+    });
+
+    use_hook({
+        let (server, token, key) = (server.clone(), token.clone(), key.clone());
+        move || {
+            spawn(async move {
+                let loaded = match server {
+                    Some(server) => server.contribution_status(&token).await,
+                    None => Err(ServerError::Unreachable),
+                };
+                if let Ok(loaded) = &loaded {
+                    // Forget a contribution made to an earlier round.
+                    let stale = saved.read().as_ref().is_some_and(|kept| loaded.as_ref().is_none_or(|round| round.id != kept.round));
+                    if stale {
+                        LocalStorageBackend.remove(&key);
+                        saved.set(None);
+                    }
+                }
+                status.set(Some(loaded));
+            });
+        }
+    });
+
+    let server = server?;
+
+    let add = {
+        let (server, token) = (server.clone(), token.clone());
+        move |current: RoundStatus| {
+            let bytes: [u8; 16] = rand::thread_rng().gen();
+            let contribution = Contribution {
+                name: current.name,
+                value: bytes.iter().map(|byte| format!("{byte:02x}")).collect(),
+            };
+            let (server, token, key) = (server.clone(), token.clone(), key.clone());
+            spawn(async move {
+                match server.contribute(&token, &commit(&contribution.value)).await {
+                    Ok(secret) => {
+                        let kept = Saved {
+                            contribution,
+                            secret,
+                            round: current.id,
+                        };
+                        let _ = LocalStorageBackend.set(&key, &serde_json::to_string(&kept).unwrap_or_default());
+                        saved.set(Some(kept));
+                        message.set(None);
+                    }
+                    Err(reason) => message.set(Some(reason.to_string())),
+                }
+            });
+        }
+    };
+
+    let reveal = move |Saved { contribution, secret, .. }: Saved| {
+        let (server, token) = (server.clone(), token.clone());
+        spawn(async move {
+            message.set(Some(match server.reveal_contribution(&token, &contribution.value, &secret).await {
+                Ok(()) => "Revealed. Thanks for helping make the draw fair!".to_string(),
+                Err(reason) => reason.to_string(),
+            }));
+        });
+    };
+
+    rsx! {
+        div {
+            class: "lg:w-2/3 mx-auto p-4",
+            div {
+                class: "candy-cane-striped p-2 rounded-xl",
+                div {
+                    class: "bg-white border border-red-400 rounded-xl p-4 text-gray-900 text-sm",
+                    h1 { class: "text-2xl font-bold mb-2", "Help make the draw fair" }
+                    match (status.read().clone(), saved.read().clone()) {
+                        (None, _) => rsx! { p { "Loading…" } },
+                        (Some(Err(reason)), _) => rsx! { p { "{reason}" } },
+                        (Some(Ok(None)), _) => rsx! {
+                            p { "This link isn't part of a current round. Ask your organizer for a new one." }
+                        },
+                        (Some(Ok(Some(current))), None) if current.phase == Phase::Committing => rsx! {
+                            p { class: "mb-2", "Add a random value to the seed the draw uses as {current.name}, so nobody can rig it. It's kept on this device until it's time to reveal it." }
+                            button {
+                                r#type: "button",
+                                class: "bg-red-900 text-white px-3 py-2 rounded-lg border-red-700 border-2 cursor-pointer hover:bg-red-600",
+                                onclick: move |_| add(current.clone()),
+                                "Add my contribution"
+                            }
+                        },
+                        (Some(Ok(Some(current))), Some(kept)) if current.phase == Phase::Committing => rsx! {
+                            p { "You've added your contribution as {kept.contribution.name}. Come back to this link to reveal it when your organizer asks." }
+                        },
+                        (Some(Ok(Some(current))), Some(kept)) if current.phase == Phase::Revealing => rsx! {
+                            p { class: "mb-2", "It's time to reveal your contribution as {kept.contribution.name}." }
+                            button {
+                                r#type: "button",
+                                class: "bg-red-900 text-white px-3 py-2 rounded-lg border-red-700 border-2 cursor-pointer hover:bg-red-600",
+                                onclick: move |_| reveal(kept.clone()),
+                                "Reveal my contribution"
+                            }
+                        },
+                        (Some(Ok(Some(current))), _) if current.phase == Phase::Revealing => rsx! {
+                            p { "Contributions have closed." }
+                        },
+                        (Some(Ok(Some(_))), _) => rsx! {
+                            p { "Reveals have closed, so the draw can go ahead." }
+                        },
+                    }
+                    if let Some(message) = message.read().clone() {
+                        p { class: "mt-2 text-gray-700", "{message}" }
+                    }
+                }
+            }
+        }
+    }
+}
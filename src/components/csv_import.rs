@@ -0,0 +1,168 @@
+use crate::components::download;
+use crate::csv::{self, ColumnMapping};
+use crate::{EventIndex, History, SecretSatan, UsePersistent};
+use dioxus::prelude::*;
+
+/// Adds participants from a spreadsheet. The columns are matched up and the
+/// rows checked before anything is added.
+#[component]
+pub fn CsvImport() -> Element {
+    let events = use_context::<UsePersistent<EventIndex>>();
+    let mut storage = use_context::<UsePersistent<SecretSatan>>();
+    let mut history = use_context::<UsePersistent<History>>();
+    let mut state = use_context::<Signal<SecretSatan>>();
+    let mut rows = use_signal(|| None::<Vec<Vec<String>>>);
+    let mut mapping = use_signal(ColumnMapping::default);
+    let mut error = use_signal(|| None::<String>);
+
+    let headers = rows.read().as_ref().and_then(|rows| rows.first().cloned()).unwrap_or_default();
+    let preview = rows
+        .read()
+        .as_ref()
+        .map(|rows| csv::preview(rows.get(1..).unwrap_or_default(), *mapping.read(), &state.read()));
+    let adding = preview.as_ref().map_or(0, |preview| preview.iter().filter(|row| !row.duplicate).count());
+
+    rsx! {
+        label {
+            class: "underline cursor-pointer",
+            "Import CSV"
+            input {
+                r#type: "file",
+                accept: ".csv,text/csv",
+                class: "hidden",
+                onchange: move |event| {
+                    let Some(files) = event.files() else {
+                        return;
+                    };
+                    spawn(async move {
+                        let Some(file) = files.files().into_iter().next() else {
+                            return;
+                        };
+                        let Some(contents) = files.read_file_to_string(&file).await else {
+                            error.set(Some(format!("{file} couldn't be read.")));
+                            return;
+                        };
+                        let parsed = csv::parse(&contents);
+                        match parsed.first() {
+                            Some(headers) => {
+                                mapping.set(ColumnMapping::guess(headers));
+                                error.set(None);
+                                rows.set(Some(parsed));
+                            }
+                            None => error.set(Some(format!("{file} is empty."))),
+                        }
+                    });
+                }
+            }
+        }
+        button {
+            r#type: "button",
+            class: "underline cursor-pointer",
+            onclick: move |_| {
+                let name = events.get().current().name.clone();
+                download(&format!("{name}.csv"), "text/csv", &csv::export(&state.read()));
+            },
+            "Export CSV"
+        }
+        if let Some(reason) = error.read().clone() {
+            span { class: "font-semibold", "{reason}" }
+        }
+        if let Some(preview) = preview {
+            div {
+                class: "basis-full bg-white border border-gray-200 rounded-xl px-4 py-2 text-gray-900",
+                div {
+                    class: "flex flex-row flex-wrap gap-4",
+                    for (label, field) in [("Name", 0), ("Email", 1), ("Household or tags", 2), ("Exclusions", 3)] {
+                        label {
+                            class: "flex flex-col",
+                            span { class: "font-semibold", "{label}" }
+                            select {
+                                class: "rounded-lg py-1 pl-2 pr-8",
+                                onchange: move |event| {
+                                    let column = event.value().parse::<usize>().ok();
+                                    let mut current = mapping.write();
+                                    match field {
+                                        0 => current.name = column,
+                                        1 => current.email = column,
+                                        2 => current.tags = column,
+                                        _ => current.exclusions = column,
+                                    }
+                                },
+                                option { value: "", "(none)" }
+                                for (column, header) in headers.iter().enumerate() {
+                                    option {
+                                        value: "{column}",
+                                        selected: column_for(*mapping.read(), field) == Some(column),
+                                        "{header}"
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+                table {
+                    class: "w-full mt-2 text-left",
+                    thead {
+                        tr {
+                            th { "Name" }
+                            th { "Email" }
+                            th { "Household or tags" }
+                            th { "Exclusions" }
+                            th { "" }
+                        }
+                    }
+                    tbody {
+                        for row in preview.iter() {
+                            tr {
+                                class: if row.duplicate { "line-through text-gray-500" } else { "" },
+                                td { "{row.participant.name}" }
+                                td { {row.participant.email.clone().unwrap_or_default()} }
+                                td { {row.participant.tags.join(", ")} }
+                                td { {row.participant.excluding.join(", ")} }
+                                td {
+                                    class: "text-red-800",
+                                    if row.duplicate {
+                                        "Already on the list, skipped"
+                                    } else if !row.unknown_exclusions.is_empty() {
+                                        "Nobody called {row.unknown_exclusions.join(\", \")}"
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+                div {
+                    class: "flex flex-row gap-4 mt-2",
+                    button {
+                        r#type: "button",
+                        class: "underline cursor-pointer disabled:cursor-not-allowed disabled:opacity-50",
+                        disabled: adding == 0,
+                        onclick: move |_| {
+                            history.update(|history| history.record(state.read().clone()));
+                            let list = csv::apply(&state.read(), &preview);
+                            state.set(list.clone());
+                            storage.set(list);
+                            rows.set(None);
+                        },
+                        "Add {adding} participants"
+                    }
+                    button {
+                        r#type: "button",
+                        class: "underline cursor-pointer",
+                        onclick: move |_| rows.set(None),
+                        "Cancel"
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn column_for(mapping: ColumnMapping, field: usize) -> Option<usize> {
+    match field {
+        0 => mapping.name,
+        1 => mapping.email,
+        2 => mapping.tags,
+        _ => mapping.exclusions,
+    }
+}
@@ -0,0 +1,20 @@
+use dioxus::prelude::*;
+
+/// Hands `contents` to the browser as a file to save.
+pub fn download(filename: &str, mime: &str, contents: &str) {
+    // Everything goes into the script as a JSON string, so it's safely quoted
+    let quote = |value: &str| serde_json::to_string(value).unwrap_or_default();
+    eval(&format!(
+        r#"
+        const blob = new Blob([{contents}], {{ type: {mime} }});
+        const link = document.createElement("a");
+        link.href = URL.createObjectURL(blob);
+        link.download = {filename};
+        link.click();
+        URL.revokeObjectURL(link.href);
+        "#,
+        contents = quote(contents),
+        mime = quote(mime),
+        filename = quote(filename),
+    ));
+}
@@ -0,0 +1,160 @@
+use crate::commitment::verify;
+use crate::{History, SecretSatan, UsePersistent};
+use dioxus::prelude::*;
+
+/// The commitment to the current draw, for the organizer to publish now, and
+/// its opening, for them to publish once gifts are exchanged.
+#[component]
+pub fn DrawCommitment() -> Element {
+    let mut history = use_context::<UsePersistent<History>>();
+    let state = use_context::<Signal<SecretSatan>>();
+    let mut copied = use_signal(|| None::<&'static str>);
+    let mut showing_seed = use_signal(|| false);
+
+    let results = state.read().results.clone();
+    let Some(commitment) = history.get().commitment_to(&results).cloned() else {
+        return rsx! {
+            p {
+                class: "text-sm text-white mt-4",
+                "This draw was made before draws were committed to, so it can't be checked later. Draw again to get a commitment."
+            }
+        };
+    };
+
+    rsx! {
+        div {
+            class: "flex flex-col gap-2 text-sm text-white mt-4",
+            p {
+                "Commitment: "
+                code { class: "break-all", "{commitment.hash}" }
+            }
+            match commitment.published.clone() {
+                None => rsx! {
+                    p { "Share this with everyone before anyone sees the results. It doesn't give anything away, but it proves later that the draw wasn't changed or drawn again. The results are shown once it's been copied to share." }
+                },
+                Some(at) => rsx! {
+                    p { "Shared at {at}. It proves later that the draw wasn't changed." }
+                },
+            }
+            div {
+                class: "flex flex-row flex-wrap gap-2 items-center",
+                button {
+                    r#type: "button",
+                    class: "underline cursor-pointer",
+                    onclick: {
+                        let hash = commitment.hash.clone();
+                        move |_| {
+                            eval(&format!("navigator.clipboard.writeText({hash:?})"));
+                            let now = String::from(js_sys::Date::new_0().to_iso_string());
+                            history.update(|history| history.publish(&hash, now));
+                            copied.set(Some("commitment"));
+                        }
+                    },
+                    if *copied.read() == Some("commitment") { "Copied" } else if commitment.published.is_some() { "Copy the commitment" } else { "Copy the commitment to share" }
+                }
+                if commitment.published.is_some() {
+                    button {
+                        r#type: "button",
+                        class: "underline cursor-pointer",
+                        title: "Anyone with this can see every pairing, so only share it once gifts have been exchanged.",
+                        onclick: {
+                            let hash = commitment.hash.clone();
+                            move |_| {
+                                if let Some(opening) = history.update(|history| history.reveal(&hash)) {
+                                    eval(&format!("navigator.clipboard.writeText({opening:?})"));
+                                    copied.set(Some("opening"));
+                                }
+                            }
+                        },
+                        if *copied.read() == Some("opening") { "Copied" } else { "Copy the draw to publish after the exchange" }
+                    }
+                }
+                if commitment.revealed {
+                    span { "(already published)" }
+                }
+            }
+            if let Some(seed) = commitment.seed.clone() {
+                p { "This draw was seeded from {seed.contributions.len()} people's contributions." }
+                if seed.tainted {
+                    p { class: "bg-white text-red-700 rounded-lg px-3 py-2", "Reveals were closed before everyone revealed, so leaving out {seed.left_out.join(\", \")} may have changed the draw." }
+                }
+                button {
+                    r#type: "button",
+                    class: "underline cursor-pointer self-start",
+                    title: "With the seed and the list, anyone can draw the same results, so only share it once gifts have been exchanged.",
+                    onclick: move |_| {
+                        let shown = *showing_seed.read();
+                        showing_seed.set(!shown);
+                    },
+                    if *showing_seed.read() { "Hide how the seed was made" } else { "Show how the seed was made" }
+                }
+                if *showing_seed.read() {
+                    pre { class: "bg-white text-gray-900 rounded-lg px-3 py-2 whitespace-pre-wrap break-all", "{seed.derivation()}" }
+                }
+            }
+        }
+    }
+}
+
+/// Checks a published draw against the commitment published before it.
+#[component]
+pub fn VerifyDraw() -> Element {
+    let mut open = use_signal(|| false);
+    let mut commitment = use_signal(String::new);
+    let mut opening = use_signal(String::new);
+
+    let input = "block w-full bg-white text-gray-800 px-3 py-2 rounded-lg";
+    let checked = match (commitment.read().trim(), opening.read().trim()) {
+        ("", _) | (_, "") => None,
+        (hash, text) => Some(verify(hash, text)),
+    };
+
+    rsx! {
+        div {
+            class: "flex flex-col gap-2 text-sm text-white mt-4",
+            button {
+                r#type: "button",
+                class: "underline cursor-pointer self-start",
+                onclick: move |_| {
+                    let shown = *open.read();
+                    open.set(!shown);
+                },
+                if *open.read() { "Done checking" } else { "Check a published draw" }
+            }
+            if *open.read() {
+                input {
+                    r#type: "text",
+                    placeholder: "The commitment shared at the draw",
+                    class: input,
+                    value: commitment.read().clone(),
+                    oninput: move |event| commitment.set(event.value()),
+                }
+                textarea {
+                    placeholder: "The draw published after the exchange",
+                    class: "{input} field-sizing-content",
+                    value: opening.read().clone(),
+                    oninput: move |event| opening.set(event.value()),
+                }
+                match checked {
+                    None => rsx! {
+                        p { "The commitment is the SHA-256, in hex, of the salt, a newline, then the results exactly as published." }
+                    },
+                    Some(Err(reason)) => rsx! {
+                        p { class: "bg-white text-red-700 rounded-lg px-3 py-2", "{reason}" }
+                    },
+                    Some(Ok(results)) => rsx! {
+                        div {
+                            class: "bg-white text-gray-900 rounded-lg px-3 py-2",
+                            p { class: "font-bold", "It matches. This is the draw that was committed to:" }
+                            ul {
+                                for assignment in results {
+                                    li { "{assignment.giver_label()} → {assignment.receiver_label()}" }
+                                }
+                            }
+                        }
+                    },
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use crate::{DrawMode, SecretSatan, UsePersistent};
+use dioxus::prelude::*;
+
+#[component]
+pub fn DrawModeSelect() -> Element {
+    let mut storage = use_context::<UsePersistent<SecretSatan>>();
+    let mut state = use_context::<Signal<SecretSatan>>();
+    let mode = state.read().mode;
+
+    let mut set_mode = move |mode: DrawMode| {
+        state.write().mode = mode;
+        storage.set(state.read().clone());
+    };
+
+    rsx! {
+        div {
+            class: "flex flex-row flex-wrap gap-2 items-center text-sm text-white mb-2",
+            label {
+                r#for: "draw-mode",
+                class: "font-semibold",
+                "Gifts:"
+            }
+            select {
+                id: "draw-mode",
+                class: "bg-white text-gray-800 rounded-lg py-1 pl-2 pr-8",
+                onchange: move |event| {
+                    set_mode(match event.value().as_str() {
+                        "group" => DrawMode::GroupGift { min_team_size: 2, max_team_size: 3 },
+                        _ => DrawMode::OneToOne,
+                    });
+                },
+                option {
+                    value: "one",
+                    selected: mode == DrawMode::OneToOne,
+                    "One giver per gift"
+                }
+                option {
+                    value: "group",
+                    selected: mode != DrawMode::OneToOne,
+                    "Teams of givers pool for one gift"
+                }
+            }
+            if let DrawMode::GroupGift { min_team_size, max_team_size } = mode {
+                label {
+                    "Teams of "
+                    input {
+                        r#type: "number",
+                        min: "1",
+                        value: "{min_team_size}",
+                        class: "w-14 bg-white text-gray-800 rounded-lg py-1 px-2",
+                        onchange: move |event| {
+                            if let Ok(min_team_size) = event.value().parse::<usize>() {
+                                set_mode(DrawMode::GroupGift { min_team_size, max_team_size: max_team_size.max(min_team_size) });
+                            }
+                        }
+                    }
+                    " to "
+                    input {
+                        r#type: "number",
+                        min: "1",
+                        value: "{max_team_size}",
+                        class: "w-14 bg-white text-gray-800 rounded-lg py-1 px-2",
+                        onchange: move |event| {
+                            if let Ok(max_team_size) = event.value().parse::<usize>() {
+                                set_mode(DrawMode::GroupGift { min_team_size: min_team_size.min(max_team_size), max_team_size });
+                            }
+                        }
+                    }
+                    " givers, counting each unit once"
+                }
+            }
+        }
+    }
+}
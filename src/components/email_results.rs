@@ -0,0 +1,137 @@
+use crate::delivery::{Delivery, DeliveryStatus, DEFAULT_TEMPLATE};
+use crate::server::Server;
+use crate::{EventIndex, SecretSatan, UsePersistent};
+use dioxus::prelude::*;
+
+/// Sends each giver their result from the server, by email or however else
+/// the event is set up to, and shows how that went without showing who
+/// anyone got. Only shown in server mode.
+#[component]
+pub fn EmailResults() -> Element {
+    let server = use_context::<Option<Server>>();
+    let events = use_context::<UsePersistent<EventIndex>>();
+    let state = use_context::<Signal<SecretSatan>>();
+    let mut deliveries = use_signal(Vec::<Delivery>::new);
+    let mut error = use_signal(|| None::<String>);
+    let mut sending = use_signal(|| false);
+    let mut editing = use_signal(|| false);
+    let mut template = use_signal(|| DEFAULT_TEMPLATE.to_string());
+
+    // `None` checks on everyone, and `Some` sends: to one giver, or to
+    // everyone still waiting when the giver is `None`.
+    let sender = use_coroutine({
+        let server = server.clone();
+        move |mut rx: UnboundedReceiver<Option<Option<String>>>| async move {
+            use futures_util::StreamExt;
+            let Some(server) = server else {
+                return;
+            };
+            while let Some(send) = rx.next().await {
+                let event = events.get().current().clone();
+                let result = match send {
+                    None => server.deliveries(&event.storage_key()).await,
+                    Some(giver) => {
+                        sending.set(true);
+                        let message = template.read().clone();
+                        let result = server
+                            .deliver(&event.storage_key(), &event.name, Some(&message), giver.as_deref())
+                            .await;
+                        sending.set(false);
+                        result
+                    }
+                };
+                match result {
+                    Ok(list) => {
+                        deliveries.set(list);
+                        error.set(None);
+                    }
+                    Err(reason) => error.set(Some(reason.to_string())),
+                }
+            }
+        }
+    });
+
+    // Check again whenever there's a new draw or another event is opened.
+    use_effect(move || {
+        let _ = (state.read().results.len(), events.get().current().storage_key());
+        sender.send(None);
+    });
+
+    server.as_ref()?;
+    let waiting = deliveries
+        .read()
+        .iter()
+        .filter(|delivery| matches!(delivery.status, DeliveryStatus::NotSent | DeliveryStatus::Failed(_)))
+        .count();
+
+    rsx! {
+        div {
+            class: "flex flex-col gap-2 text-sm text-white mt-4",
+            div {
+                class: "flex flex-row flex-wrap gap-2 items-center",
+                "Or send them: "
+                button {
+                    r#type: "button",
+                    class: "underline cursor-pointer disabled:cursor-not-allowed disabled:opacity-50",
+                    disabled: *sending.read() || waiting == 0,
+                    onclick: move |_| sender.send(Some(None)),
+                    if *sending.read() { "Sending…" } else if waiting == 0 { "Everyone's been sent theirs" } else { "Send to everyone who's waiting ({waiting})" }
+                }
+                button {
+                    r#type: "button",
+                    class: "underline cursor-pointer",
+                    onclick: move |_| {
+                        let shown = *editing.read();
+                        editing.set(!shown);
+                    },
+                    if *editing.read() { "Done editing the message" } else { "Edit the message" }
+                }
+            }
+            if *editing.read() {
+                textarea {
+                    class: "block w-full bg-white text-gray-800 px-3 py-2 rounded-lg field-sizing-content",
+                    value: template.read().clone(),
+                    oninput: move |event| template.set(event.value()),
+                }
+                p { "{{giver}}, {{receiver}}, {{event}}, {{budget}}, {{date}}, {{details}} and {{wishlist}} are filled in for each person." }
+            }
+            if let Some(reason) = error.read().clone() {
+                p { class: "bg-white text-red-700 rounded-lg px-3 py-2", "{reason}" }
+            }
+            if !deliveries.read().is_empty() {
+                ul {
+                    class: "grid gap-2 sm:grid-cols-2 md:grid-cols-3",
+                    for delivery in deliveries.read().clone() {
+                        li {
+                            class: "bg-white text-gray-900 rounded-lg px-4 py-2",
+                            p { class: "font-bold", "{delivery.giver}" }
+                            p { {delivery.email.clone().unwrap_or_else(|| "No email address".to_string())} }
+                            p {
+                                class: "italic",
+                                match &delivery.status {
+                                    DeliveryStatus::NotSent => "Not sent yet".to_string(),
+                                    DeliveryStatus::NoAddress => "Nothing set up can reach them".to_string(),
+                                    DeliveryStatus::Sent if delivery.attempts > 1 => format!("Sent ({} attempts)", delivery.attempts),
+                                    DeliveryStatus::Sent => "Sent".to_string(),
+                                    DeliveryStatus::Failed(reason) => format!("Failed: {reason}"),
+                                }
+                            }
+                            if delivery.status != DeliveryStatus::NotSent {
+                                button {
+                                    r#type: "button",
+                                    class: "underline cursor-pointer disabled:cursor-not-allowed disabled:opacity-50",
+                                    disabled: *sending.read(),
+                                    onclick: {
+                                        let giver = delivery.giver.clone();
+                                        move |_| sender.send(Some(Some(giver.clone())))
+                                    },
+                                    if matches!(delivery.status, DeliveryStatus::Failed(_)) { "Retry" } else { "Resend" }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use crate::{SecretSatan, UsePersistent};
+use dioxus::prelude::*;
+
+/// The budget and date printed on cards.
+#[component]
+pub fn EventDetails() -> Element {
+    let mut storage = use_context::<UsePersistent<SecretSatan>>();
+    let mut state = use_context::<Signal<SecretSatan>>();
+
+    let budget = state.read().budget.clone().unwrap_or_default();
+    let date = state.read().date.clone().unwrap_or_default();
+    let filled = |value: String| Some(value.trim().to_string()).filter(|value| !value.is_empty());
+
+    rsx! {
+        div {
+            class: "flex flex-row flex-wrap gap-4 items-center text-sm text-white mb-4",
+            label {
+                class: "flex flex-row gap-2 items-center",
+                "Budget"
+                input {
+                    r#type: "text",
+                    class: "rounded-lg py-1 px-2 text-gray-800 w-28",
+                    placeholder: "$25",
+                    value: "{budget}",
+                    onchange: move |event| {
+                        state.write().budget = filled(event.value());
+                        storage.set(state.read().clone());
+                    }
+                }
+            }
+            label {
+                class: "flex flex-row gap-2 items-center",
+                "Exchange date"
+                input {
+                    r#type: "date",
+                    class: "rounded-lg py-1 px-2 text-gray-800",
+                    value: "{date}",
+                    onchange: move |event| {
+                        state.write().date = filled(event.value());
+                        storage.set(state.read().clone());
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,208 @@
+use crate::{EventIndex, History, Participant, SecretSatan, UsePersistent};
+use dioxus::prelude::*;
+
+#[component]
+pub fn EventSwitcher() -> Element {
+    let mut events = use_context::<UsePersistent<EventIndex>>();
+    let mut storage = use_context::<UsePersistent<SecretSatan>>();
+    let mut history = use_context::<UsePersistent<History>>();
+    let mut state = use_context::<Signal<SecretSatan>>();
+    let mut selected_participant = use_context::<Signal<Option<Participant>>>();
+    let mut show_archived = use_signal(|| false);
+    let mut confirm_delete = use_signal(|| None::<String>);
+
+    let index = events.get();
+    let current = index.current().clone();
+    let active: Vec<_> = index.active().cloned().collect();
+    let archived: Vec<_> = index.archived().cloned().collect();
+
+    // Saves the index and loads whichever event is now open.
+    let mut open = move |index: EventIndex| {
+        let (key, history_key) = (index.current().storage_key(), index.current().history_key());
+        events.set(index);
+        storage.switch(key, SecretSatan::default);
+        history.switch(history_key, History::default);
+        state.set(storage.get());
+        selected_participant.set(None);
+    };
+
+    // Removes an event and its history once the deletion has been confirmed,
+    // opening another if it was the open one.
+    let mut delete = move |id: String| {
+        let mut index = events.get();
+        let was_open = index.current().id == id;
+        if let Some(deleted) = index.delete(&id) {
+            storage.remove(&deleted.storage_key());
+            storage.remove(&deleted.history_key());
+        }
+        confirm_delete.set(None);
+        match was_open {
+            true => open(index),
+            false => events.set(index),
+        }
+    };
+
+    rsx! {
+        div {
+            class: "flex flex-row flex-wrap gap-2 items-center text-sm text-white mb-4",
+            select {
+                class: "bg-white text-gray-800 rounded-lg py-1 pl-2 pr-8 font-semibold",
+                onchange: move |event| {
+                    let mut index = events.get();
+                    index.switch(&event.value());
+                    open(index);
+                },
+                for event in active.iter() {
+                    option {
+                        value: event.id.clone(),
+                        selected: event.id == current.id,
+                        {event.name.clone()}
+                    }
+                }
+            }
+            button {
+                r#type: "button",
+                class: "underline cursor-pointer",
+                onclick: move |_| {
+                    let mut index = events.get();
+                    let id = index.create("New exchange");
+                    index.switch(&id);
+                    open(index);
+                },
+                "New"
+            }
+            button {
+                r#type: "button",
+                class: "underline cursor-pointer",
+                onclick: move |_| {
+                    let copy = state.read().clone();
+                    let mut index = events.get();
+                    let name = format!("{} (copy)", index.current().name);
+                    let id = index.create(name);
+                    index.switch(&id);
+                    open(index);
+                    state.set(copy.clone());
+                    storage.set(copy);
+                },
+                "Duplicate"
+            }
+            button {
+                r#type: "button",
+                class: "underline cursor-pointer",
+                onclick: move |_| {
+                    let mut index = events.get();
+                    let id = index.current().id.clone();
+                    let prompt = eval(&format!("return prompt('Rename this exchange', {:?})", index.current().name));
+                    spawn(async move {
+                        if let Ok(value) = prompt.join().await {
+                            if let Some(name) = value.as_str().filter(|name| !name.trim().is_empty()) {
+                                index.rename(&id, name.trim());
+                                events.set(index);
+                            }
+                        }
+                    });
+                },
+                "Rename"
+            }
+            button {
+                r#type: "button",
+                class: "underline cursor-pointer",
+                onclick: move |_| {
+                    let mut index = events.get();
+                    let id = index.current().id.clone();
+                    index.archive(&id);
+                    open(index);
+                },
+                "Archive"
+            }
+            if confirm_delete.read().as_deref() == Some(current.id.as_str()) {
+                span {
+                    "Delete {current.name} for good? "
+                    button {
+                        r#type: "button",
+                        class: "underline cursor-pointer font-semibold",
+                        onclick: move |_| delete(events.get().current().id.clone()),
+                        "Yes"
+                    }
+                    " / "
+                    button {
+                        r#type: "button",
+                        class: "underline cursor-pointer",
+                        onclick: move |_| confirm_delete.set(None),
+                        "No"
+                    }
+                }
+            } else {
+                button {
+                    r#type: "button",
+                    class: "underline cursor-pointer",
+                    onclick: move |_| confirm_delete.set(Some(events.get().current().id.clone())),
+                    "Delete"
+                }
+            }
+            if !archived.is_empty() {
+                button {
+                    r#type: "button",
+                    class: "underline cursor-pointer",
+                    onclick: move |_| show_archived.toggle(),
+                    if *show_archived.read() { "Hide archived" } else { "Show archived ({archived.len()})" }
+                }
+            }
+        }
+        if *show_archived.read() {
+            ul {
+                class: "text-sm text-white mb-4",
+                for event in archived.iter() {
+                    li {
+                        key: "{event.id}",
+                        span { class: "font-semibold mr-2", {event.name.clone()} }
+                        button {
+                            r#type: "button",
+                            class: "underline cursor-pointer mr-2",
+                            onclick: {
+                                let id = event.id.clone();
+                                move |_| {
+                                    let mut index = events.get();
+                                    index.restore(&id);
+                                    events.set(index);
+                                }
+                            },
+                            "Restore"
+                        }
+                        if confirm_delete.read().as_deref() == Some(event.id.as_str()) {
+                            span {
+                                "Delete {event.name} for good? "
+                                button {
+                                    r#type: "button",
+                                    class: "underline cursor-pointer font-semibold",
+                                    onclick: {
+                                        let id = event.id.clone();
+                                        move |_| delete(id.clone())
+                                    },
+                                    "Yes"
+                                }
+                                " / "
+                                button {
+                                    r#type: "button",
+                                    class: "underline cursor-pointer",
+                                    onclick: move |_| confirm_delete.set(None),
+                                    "No"
+                                }
+                            }
+                        } else {
+                            button {
+                                r#type: "button",
+                                class: "underline cursor-pointer",
+                                onclick: {
+                                    let id = event.id.clone();
+                                    move |_| confirm_delete.set(Some(id.clone()))
+                                },
+                                "Delete"
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
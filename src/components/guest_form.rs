@@ -1,39 +1,43 @@
 use dioxus::prelude::*;
 
-use crate::{components, use_persistent, Participant, SecretSatan};
-use components::AddGiver;
+use crate::{components, History, Participant, Role, SecretSatan, UsePersistent};
+use components::{AddGiver, DrawModeSelect};
 
 #[component]
 pub fn GuestForm() -> Element {
-    let mut storage = use_persistent("satan", || SecretSatan::default());
+    let mut storage = use_context::<UsePersistent<SecretSatan>>();
+    let mut history = use_context::<UsePersistent<History>>();
     let mut state = use_context::<Signal<SecretSatan>>();
     let mut modal_open = use_context::<Signal<bool>>();
 
     let mut name_signal = use_signal(|| "".to_string());
     let mut excluding_signal = use_signal(|| "".to_string());
-    let mut giving_list = use_context::<Signal<Vec<Participant>>>();
+    let mut role_signal = use_signal(Role::default);
 
-    let mut participants = storage.get().participants.clone();
+    let participants = storage.get().participants.clone();
 
     rsx! {
       div {
             class: "w-full",
             form {
-                onsubmit: move |event| {
-                    let mut participant = Participant::default();
-                    participant.name = name_signal.read().clone();
+                onsubmit: move |_| {
+                    let mut participant = Participant::new(name_signal.read().clone());
                     if participant.name.is_empty() {
                         return;
                     }
-                    participant.excluding = excluding_signal.read().clone().split('\n').map(|name| name.trim().to_string()).collect();
+                    participant.excluding = excluding_signal.read().clone().split('\n').map(|name| name.trim().to_string()).filter(|name| !name.is_empty()).collect();
+                    participant.role = *role_signal.read();
 
+                    history.update(|history| history.record(state.read().clone()));
                     state.write().participants.push(participant.clone());
-                    storage.set(SecretSatan { participants: state.read().participants.clone() });
+                    storage.set(state.read().clone());
 
                     name_signal.set("".to_string());
                     excluding_signal.set("".to_string());
+                    role_signal.set(Role::default());
                 },
-                AddGiver { name_signal, excluding_signal}
+                AddGiver { name_signal, excluding_signal, role_signal }
+                DrawModeSelect {}
                 div {
                     class: "flex flex-row flex-wrap gap-2 w-full justify-between",
                     div {
@@ -51,12 +55,17 @@ pub fn GuestForm() -> Element {
                             class: "group text-gray-800 bg-white px-1 py-2 rounded-lg border-gray-200 border-2 mr-2 cursor-pointer disabled:cursor-not-allowed disabled:opacity-50 calculate-button transition",
                             disabled: participants.is_empty() || participants.len() < 3,
                             onclick: move |_| {
-                                let participants = state.read().clone().assign_participants();
-                                match participants {
-                                    Ok(participants) => giving_list.set(participants.clone()),
-                                    Err(_) => {
-                                        giving_list.set(vec![]);
-                                        eval("alert('There was an error calculating the gift giving list. Please try again.')");
+                                let draw = state.read().draw();
+                                match draw {
+                                    Ok(assignments) => {
+                                        state.write().set_results(assignments.clone());
+                                        storage.set(state.read().clone());
+                                        history.update(|history| history.commit(assignments, None, &mut rand::thread_rng()));
+                                    }
+                                    Err(error) => {
+                                        state.write().set_results(Vec::new());
+                                        storage.set(state.read().clone());
+                                        eval(&format!("alert({:?})", format!("There was an error calculating the gift giving list. {error}")));
                                     },
                                 }
                             },
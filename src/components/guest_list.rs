@@ -1,12 +1,11 @@
 use dioxus::prelude::*;
 
-use crate::{components, use_persistent, Participant, SecretSatan};
-use components::GuestListItem;
+use crate::{components, Participant, Role, SecretSatan};
+use components::{GuestListItem, UnitSelect};
 
 #[component]
 pub fn RemoveGuestButton(participant: Participant) -> Element {
     let mut selected_participant = use_context::<Signal<Option<Participant>>>();
-    let name = participant.name.clone();
     rsx! {
         span {
             class: "p-1 bg-white rounded-full absolute bottom-2 right-2 select-none hover:shadow-lg hover:cursor-pointer text-xs",
@@ -25,6 +24,7 @@ pub fn RemoveGuestButton(participant: Participant) -> Element {
 pub fn GuestList() -> Element {
     let state = use_context::<Signal<SecretSatan>>();
     let participants = state.read().participants.clone();
+    let state = state.read().clone();
 
     rsx! {
         div {
@@ -39,14 +39,31 @@ pub fn GuestList() -> Element {
                             class: "text-2xl font-bold",
                             {participant.name.clone()}
                         }
-                        h3 {
-                            class: "text-sm font-semibold select-none",
-                            "can't give to"
+                        if let Some(email) = participant.email.clone() {
+                            p { class: "text-sm select-none", "{email}" }
                         }
-                        ul {
-                            // class: "select-none",
-                            for guest in participants.clone().iter().filter(|p| participant.name != p.name) {
-                                GuestListItem { guest: guest.clone().name, participant: participant.clone() }
+                        if !participant.tags.is_empty() {
+                            p { class: "text-sm italic select-none", {participant.tags.join(", ")} }
+                        }
+                        if let Some(wishlist) = participant.wishlist.clone() {
+                            p { class: "text-sm whitespace-pre-line", "Wishes: {wishlist}" }
+                        }
+                        UnitSelect { participant: participant.clone() }
+                        match participant.role {
+                            Role::Both => None,
+                            Role::GiverOnly => rsx! { p { class: "text-sm italic select-none", "only gives a gift" } },
+                            Role::ReceiverOnly => rsx! { p { class: "text-sm italic select-none", "only receives a gift" } },
+                        }
+                        if participant.role.gives() {
+                            h3 {
+                                class: "text-sm font-semibold select-none",
+                                "can't give to"
+                            }
+                            ul {
+                                // class: "select-none",
+                                for guest in participants.clone().iter().filter(|p| participant.name != p.name && p.role.receives() && !state.unit_of(&participant.name).is_some_and(|unit| unit.contains(&p.name))) {
+                                    GuestListItem { guest: guest.clone().name, participant: participant.clone() }
+                                }
                             }
                         }
                     }
@@ -1,9 +1,10 @@
-use crate::{use_persistent, Participant, SecretSatan};
+use crate::{History, Participant, SecretSatan, UsePersistent};
 use dioxus::prelude::*;
 
 #[component]
 pub fn GuestListItem(guest: String, participant: Participant) -> Element {
-    let mut storage = use_persistent("satan", || SecretSatan::default());
+    let mut storage = use_context::<UsePersistent<SecretSatan>>();
+    let mut history = use_context::<UsePersistent<History>>();
     let mut state = use_context::<Signal<SecretSatan>>();
 
     rsx! {
@@ -16,8 +17,9 @@ pub fn GuestListItem(guest: String, participant: Participant) -> Element {
                     checked: participant.excluding.contains(&guest),
                     class: "mr-2 rounded text-red-800 ",
                     onchange: move |event| {
+                        history.update(|history| history.record(state.read().clone()));
                         let mut participants = state.read().clone().participants;
-                        let mut participant = participants.iter_mut().find(|p| p.name == participant.name).unwrap();
+                        let participant = participants.iter_mut().find(|p| p.name == participant.name).unwrap();
 
                         if event.checked() {
                             participant.excluding.push(guest.clone());
@@ -25,8 +27,8 @@ pub fn GuestListItem(guest: String, participant: Participant) -> Element {
                             participant.excluding.retain(|name| name != &guest);
                         }
 
-                        state.write().participants = participants.clone();
-                        storage.set(SecretSatan { participants: participants.clone() });
+                        state.write().participants = participants;
+                        storage.set(state.read().clone());
                     }
                 }
                 {guest.clone()}
@@ -0,0 +1,200 @@
+use crate::components::{download, CsvImport};
+use crate::share::{self, ShareError};
+use crate::transfer::{diff, export_event, import_event, merge_import, ImportedEvent};
+use crate::{EventIndex, History, SecretSatan, UsePersistent};
+use dioxus::prelude::*;
+
+/// Saves the open event to a `.json` file or a link, and loads one back in
+/// after showing what it would change.
+#[component]
+pub fn ImportExport() -> Element {
+    let events = use_context::<UsePersistent<EventIndex>>();
+    let mut storage = use_context::<UsePersistent<SecretSatan>>();
+    let mut history = use_context::<UsePersistent<History>>();
+    let mut state = use_context::<Signal<SecretSatan>>();
+    // A link someone shared is offered for import as soon as the app opens.
+    let shared = use_hook(shared_event);
+    let mut pending = use_signal(|| shared.clone().ok().flatten());
+    let mut error = use_signal(|| shared.clone().err().map(|error| error.to_string()));
+    let mut link = use_signal(|| None::<String>);
+
+    use_effect(move || {
+        if !matches!(shared, Ok(None)) {
+            // Reloading shouldn't offer the same import again.
+            eval("history.replaceState(null, '', location.pathname + location.search)");
+        }
+    });
+
+    let mut apply = move |list: SecretSatan| {
+        history.update(|history| history.record(state.read().clone()));
+        state.set(list.clone());
+        storage.set(list);
+        pending.set(None);
+    };
+
+    let preview = pending.read().clone().map(|imported| {
+        let changes = diff(&state.read(), &imported.list);
+        (imported, changes)
+    });
+
+    rsx! {
+        div {
+            class: "flex flex-row flex-wrap gap-2 items-center text-sm text-white mb-4",
+            button {
+                r#type: "button",
+                class: "underline cursor-pointer",
+                onclick: move |_| {
+                    let name = events.get().current().name.clone();
+                    download(&format!("{name}.json"), "application/json", &export_event(&name, &state.read()));
+                },
+                "Export JSON"
+            }
+            label {
+                class: "underline cursor-pointer",
+                "Import JSON"
+                input {
+                    r#type: "file",
+                    accept: ".json,application/json",
+                    class: "hidden",
+                    onchange: move |event| {
+                        let Some(files) = event.files() else {
+                            return;
+                        };
+                        spawn(async move {
+                            let Some(file) = files.files().into_iter().next() else {
+                                return;
+                            };
+                            let Some(contents) = files.read_file_to_string(&file).await else {
+                                error.set(Some(format!("{file} couldn't be read.")));
+                                return;
+                            };
+                            match import_event(&contents) {
+                                Ok(imported) => {
+                                    error.set(None);
+                                    pending.set(Some(imported));
+                                }
+                                Err(reason) => error.set(Some(reason)),
+                            }
+                        });
+                    }
+                }
+            }
+            button {
+                r#type: "button",
+                class: "underline cursor-pointer",
+                onclick: move |_| {
+                    let name = events.get().current().name.clone();
+                    match share::encode(&name, &state.read()) {
+                        Ok(fragment) => {
+                            error.set(None);
+                            let copy = eval(&format!(
+                                "const url = location.origin + location.pathname + location.search + '#' + {fragment:?};
+                                navigator.clipboard?.writeText(url).catch(() => {{}});
+                                return url;"
+                            ));
+                            spawn(async move {
+                                if let Ok(url) = copy.join().await {
+                                    link.set(url.as_str().map(str::to_string));
+                                }
+                            });
+                        }
+                        Err(reason) => {
+                            link.set(None);
+                            error.set(Some(reason.to_string()));
+                        }
+                    }
+                },
+                "Share link"
+            }
+            CsvImport {}
+            if let Some(reason) = error.read().clone() {
+                span { class: "font-semibold", "{reason}" }
+            }
+            if let Some(url) = link.read().clone() {
+                span {
+                    class: "basis-full flex flex-row gap-2 items-center",
+                    "Copied to your clipboard:"
+                    input {
+                        r#type: "text",
+                        readonly: true,
+                        class: "flex-1 rounded-lg py-1 px-2 text-gray-800",
+                        value: "{url}",
+                        onfocus: move |_| {
+                            eval("document.activeElement.select()");
+                        }
+                    }
+                    button {
+                        r#type: "button",
+                        class: "underline cursor-pointer",
+                        onclick: move |_| link.set(None),
+                        "Done"
+                    }
+                }
+            }
+        }
+        if let Some((imported, changes)) = preview {
+            div {
+                class: "mb-4 bg-white border border-gray-200 rounded-xl px-4 py-2 text-gray-900 text-sm",
+                p {
+                    class: "font-semibold",
+                    match &imported.name {
+                        Some(name) => format!("Importing {name}"),
+                        None => "Importing a list".to_string(),
+                    }
+                }
+                if changes.is_empty() {
+                    p { "It's the same as this list." }
+                }
+                if !changes.added.is_empty() {
+                    p { "New: {changes.added.join(\", \")}" }
+                }
+                if !changes.changed.is_empty() {
+                    p { "Different exclusions or roles: {changes.changed.join(\", \")}" }
+                }
+                if !changes.removed.is_empty() {
+                    p { "Not in the file: {changes.removed.join(\", \")}" }
+                }
+                if changes.settings_changed {
+                    p { "Groups, draw settings, the budget, the date or results differ." }
+                }
+                div {
+                    class: "flex flex-row gap-4 mt-2",
+                    button {
+                        r#type: "button",
+                        class: "underline cursor-pointer",
+                        title: "Add new people and exclusions, keeping everything already here",
+                        onclick: {
+                            let imported = imported.list.clone();
+                            move |_| {
+                                let merged = merge_import(&state.read(), &imported);
+                                apply(merged);
+                            }
+                        },
+                        "Merge into this list"
+                    }
+                    button {
+                        r#type: "button",
+                        class: "underline cursor-pointer",
+                        onclick: {
+                            let imported = imported.list.clone();
+                            move |_| apply(imported.clone())
+                        },
+                        "Replace this list"
+                    }
+                    button {
+                        r#type: "button",
+                        class: "underline cursor-pointer",
+                        onclick: move |_| pending.set(None),
+                        "Cancel"
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The event in the page's URL fragment, if someone shared one.
+fn shared_event() -> Result<Option<ImportedEvent>, ShareError> {
+    let hash = web_sys::window().and_then(|window| window.location().hash().ok()).unwrap_or_default();
+    share::decode(&hash)
+}
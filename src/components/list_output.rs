@@ -1,37 +1,156 @@
-use crate::{use_persistent, Participant, SecretSatan};
+use crate::components::{DrawCommitment, EmailResults, Notifications, PassTheDevice, QrCodes};
+use crate::print::{cards_html, CardStyle};
+use crate::reveal::{reveals, seal};
+use crate::{EventIndex, History, SecretSatan, UsePersistent};
 use dioxus::prelude::*;
 
 #[component]
 pub fn ListOutput() -> Element {
-    let state = use_context::<Signal<SecretSatan>>();
-    let giving_list = use_context::<Signal<Vec<Participant>>>();
-    let selected_participant = use_context::<Signal<Option<Participant>>>();
+    let events = use_context::<UsePersistent<EventIndex>>();
+    let mut storage = use_context::<UsePersistent<SecretSatan>>();
+    let mut state = use_context::<Signal<SecretSatan>>();
+    let giving_list = state.read().results.clone();
+    // Kept hidden so the organizer can take part without knowing the results.
+    let mut show_pairings = use_signal(|| false);
+    let mut copied = use_signal(|| None::<String>);
+    let mut passing = use_signal(|| false);
+    let history = use_context::<UsePersistent<History>>();
 
-    let participants = state.read().participants.clone();
+    let event = events.get().current().name.clone();
+    let links = reveals(&event, &giving_list);
+    let total = links.len();
+    let seen = state.read().viewed.len();
+
+    // Nothing about the results is shown until their commitment is shared,
+    // so a draw can't be looked at and quietly replaced.
+    if !giving_list.is_empty() && !history.get().can_show(&giving_list) {
+        return rsx! {
+            div {
+                class: "mt-4",
+                h2 {
+                    class: "text-2xl font-bold w-full text-white",
+                    "Gift Giving List"
+                }
+                DrawCommitment {}
+            }
+        };
+    }
 
     rsx! {
         div {
-            class: if giving_list.read().is_empty() { "hidden mt-4" } else { "mt-4" },
+            class: if giving_list.is_empty() { "hidden mt-4" } else { "mt-4" },
             h2 {
                 class: "text-2xl font-bold w-full text-white",
                 "Gift Giving List"
             }
+            p {
+                class: "text-sm text-white",
+                "Send everyone their own link. It only shows them who they're giving to, and you can't see what's in it."
+            }
+
+            ul {
+                class: "grid grid-row grid-wrap gap-2 w-full mt-4 sm:grid-cols-2 md:grid-cols-3",
+                for reveal in links {
+                    li {
+                        class: "flex flex-row justify-between items-center bg-white rounded-lg px-4 py-2",
+                        span { class: "font-bold", "{reveal.giver}" }
+                        button {
+                            r#type: "button",
+                            class: "underline cursor-pointer text-sm",
+                            onclick: {
+                                let reveal = reveal.clone();
+                                move |_| {
+                                    let fragment = seal(&reveal, &mut rand::thread_rng());
+                                    eval(&format!(
+                                        "navigator.clipboard.writeText(location.origin + location.pathname + '#' + {fragment:?})"
+                                    ));
+                                    copied.set(Some(reveal.giver.clone()));
+                                }
+                            },
+                            if copied.read().as_deref() == Some(reveal.giver.as_str()) { "Copied" } else { "Copy link" }
+                        }
+                    }
+                }
+            }
 
             div {
-                class: "grid grid-row grid-wrap gap-4 w-full mt-4 sm:grid-cols-2 md:grid-cols-3",
-                for participant in giving_list.read().iter() {
-                    div {
-                        class: "shadow-sm rounded-lg candy-cane-striped hover:shadow-lg",
+                class: "flex flex-row flex-wrap gap-2 items-center text-sm text-white mt-4",
+                "Or pass one phone around the room: "
+                button {
+                    r#type: "button",
+                    class: "underline cursor-pointer",
+                    onclick: move |_| passing.set(true),
+                    if seen == 0 { "Start" } else { "Continue" }
+                }
+                if seen > 0 {
+                    span { "({seen} of {total} have seen theirs)" }
+                    button {
+                        r#type: "button",
+                        class: "underline cursor-pointer",
+                        onclick: move |_| {
+                            state.write().viewed.clear();
+                            storage.set(state.read().clone());
+                        },
+                        "Let everyone look again"
+                    }
+                }
+            }
+            PassTheDevice { open: passing }
+
+            div {
+                class: "flex flex-row flex-wrap gap-2 items-center text-sm text-white mt-2",
+                "Or print them: "
+                for (label, style) in [("Cards", CardStyle::Card), ("Cards with names on the envelope only", CardStyle::Envelope)] {
+                    button {
+                        r#type: "button",
+                        class: "underline cursor-pointer",
+                        onclick: move |_| {
+                            let event = events.get().current().name.clone();
+                            let html = cards_html(&event, &state.read(), style);
+                            eval(&format!(
+                                "const page = window.open('', '_blank');
+                                page.document.write({html:?});
+                                page.document.close();
+                                page.focus();
+                                page.print();"
+                            ));
+                        },
+                        "{label}"
+                    }
+                }
+            }
+            QrCodes {}
+            EmailResults {}
+            Notifications {}
+            DrawCommitment {}
+
+            button {
+                r#type: "button",
+                class: "underline cursor-pointer text-sm text-white mt-4",
+                onclick: move |_| {
+                    let shown = *show_pairings.read();
+                    show_pairings.set(!shown);
+                },
+                if *show_pairings.read() { "Hide everyone's pairings" } else { "Show everyone's pairings" }
+            }
+
+            if *show_pairings.read() {
+                div {
+                    class: "grid grid-row grid-wrap gap-4 w-full mt-4 sm:grid-cols-2 md:grid-cols-3",
+                    for assignment in giving_list.iter() {
                         div {
-                            class: "h-full px-4 py-2 rounded-lg bg-gradient-to-br from-white from-65%",
-                            h2 {
-                                class: "text-2xl font-bold",
-                                {participant.name.clone()}
-                                span {
-                                    class: "font-normal",
-                                    " is giving to "
+                            class: "shadow-sm rounded-lg candy-cane-striped hover:shadow-lg",
+                            div {
+                                class: "h-full px-4 py-2 rounded-lg bg-gradient-to-br from-white from-65%",
+                                h2 {
+                                    class: "text-2xl font-bold",
+                                    {assignment.giver_label()}
+                                    span {
+                                        class: "font-normal",
+                                        if assignment.givers.len() > 1 { " are giving to " } else { " is giving to " }
+                                    }
+                                    {assignment.receiver_label()}
                                 }
-                                {participant.giving_to.clone().unwrap()}
                             }
                         }
                     }
@@ -39,4 +158,4 @@ pub fn ListOutput() -> Element {
             }
         }
     }
-}
\ No newline at end of file
+}
@@ -1,16 +1,62 @@
 mod app;
 mod add_giver;
+mod contribute;
+mod csv_import;
+mod download;
+mod draw_commitment;
+mod draw_mode_select;
+mod email_results;
+mod event_details;
+mod event_switcher;
 mod guest_list_item;
 mod guest_list;
 mod guest_form;
+mod import_export;
 mod list_output;
 mod modals;
+mod notifications;
+mod organizer_login;
+mod pass_the_device;
+mod qr_codes;
+mod recovery_screen;
+mod registrations;
+mod reveal_screen;
+mod rotation_plan;
+mod save_error_banner;
+mod shared_seed;
+mod signup_screen;
+mod sync_watcher;
+mod undo_toolbar;
+mod unit_select;
 
 pub use add_giver::AddGiver;
 pub use app::App;
+pub use contribute::Contribute;
+pub use csv_import::CsvImport;
+pub use download::download;
+pub use draw_commitment::{DrawCommitment, VerifyDraw};
+pub use draw_mode_select::DrawModeSelect;
+pub use email_results::EmailResults;
+pub use event_details::EventDetails;
+pub use event_switcher::EventSwitcher;
 pub use guest_form::GuestForm;
 pub use guest_list::GuestList;
 pub use guest_list_item::GuestListItem;
+pub use import_export::ImportExport;
 pub use list_output::ListOutput;
 pub use modals::ClearListModal;
 pub use modals::DeleteParticipantModal;
+pub use notifications::Notifications;
+pub use organizer_login::OrganizerLogin;
+pub use pass_the_device::PassTheDevice;
+pub use qr_codes::QrCodes;
+pub use recovery_screen::RecoveryScreen;
+pub use registrations::Registrations;
+pub use reveal_screen::RevealScreen;
+pub use rotation_plan::RotationPlan;
+pub use save_error_banner::SaveErrorBanner;
+pub use shared_seed::SharedSeed;
+pub use signup_screen::SignupScreen;
+pub use sync_watcher::SyncWatcher;
+pub use undo_toolbar::UndoToolbar;
+pub use unit_select::UnitSelect;
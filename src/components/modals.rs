@@ -1,9 +1,10 @@
-use crate::{use_persistent, Participant, SecretSatan};
+use crate::{History, Participant, SecretSatan, UsePersistent};
 use dioxus::prelude::*;
 
 #[component]
 pub fn DeleteParticipantModal() -> Element {
-    let mut storage = use_persistent("satan", || SecretSatan::default());
+    let mut storage = use_context::<UsePersistent<SecretSatan>>();
+    let mut history = use_context::<UsePersistent<History>>();
     let mut state = use_context::<Signal<SecretSatan>>();
     let mut participant = use_context::<Signal<Option<Participant>>>();
     let mut name = String::new();
@@ -43,9 +44,10 @@ pub fn DeleteParticipantModal() -> Element {
                                 button {
                                     r#type: "button",
                                     class: "bg-red-900 text-white px-3 py-2 rounded-lg border-red-700 border-2 cursor-danger whitespace-nowrap disabled:cursor-not-allowed disabled:opacity-50 hover:bg-red-600 cursor-pointer",
-                                    onclick: move |event| {
+                                    onclick: move |_| {
+                                        history.update(|history| history.record(state.read().clone()));
                                         state.write().remove_participant(&selected.clone());
-                                        storage.set(SecretSatan { participants: state.read().participants.clone() });
+                                        storage.set(state.read().clone());
                                         participant.set(None);
                                     },
                                     "Yes"
@@ -53,7 +55,7 @@ pub fn DeleteParticipantModal() -> Element {
                                 button {
                                     r#type: "button",
                                     class: "group text-gray-800 bg-white px-1 py-2 rounded-lg border-gray-200 border-2 mr-2 cursor-pointer disabled:cursor-not-allowed disabled:opacity-50 calculate-button transition",
-                                    onclick: move |event| {
+                                    onclick: move |_| {
                                         participant.set(None);
                                     },
                                     span {
@@ -72,7 +74,8 @@ pub fn DeleteParticipantModal() -> Element {
 
 #[component]
 pub fn ClearListModal() -> Element {
-    let mut storage = use_persistent("satan", || SecretSatan::default());
+    let mut storage = use_context::<UsePersistent<SecretSatan>>();
+    let mut history = use_context::<UsePersistent<History>>();
     let mut state = use_context::<Signal<SecretSatan>>();
     let mut open = use_context::<Signal<bool>>();
 
@@ -107,8 +110,9 @@ pub fn ClearListModal() -> Element {
                                 button {
                                     r#type: "button",
                                     class: "bg-red-900 text-white px-3 py-2 rounded-lg border-red-700 border-2 cursor-danger whitespace-nowrap disabled:cursor-not-allowed disabled:opacity-50 hover:bg-red-600 cursor-pointer",
-                                    onclick: move |event| {
-                                        state.write().participants.clear();
+                                    onclick: move |_| {
+                                        history.update(|history| history.record(state.read().clone()));
+                                        state.set(SecretSatan::default());
                                         storage.set(SecretSatan::default());
                                         open.set(false);
                                     },
@@ -117,7 +121,7 @@ pub fn ClearListModal() -> Element {
                                 button {
                                     r#type: "button",
                                     class: "group text-gray-800 bg-white px-1 py-2 rounded-lg border-gray-200 border-2 mr-2 cursor-pointer disabled:cursor-not-allowed disabled:opacity-50 calculate-button transition",
-                                    onclick: move |event| {
+                                    onclick: move |_| {
                                         open.set(false);
                                     },
                                     span {
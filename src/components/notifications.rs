@@ -0,0 +1,154 @@
+use crate::delivery::{NotificationOptions, NotificationSettings, DEFAULT_REMINDER};
+use crate::server::Server;
+use crate::{EventIndex, UsePersistent};
+use dioxus::prelude::*;
+
+/// Chooses where the event's results and reminders go, and sends reminders.
+/// Only shown in server mode.
+#[component]
+pub fn Notifications() -> Element {
+    let server = use_context::<Option<Server>>();
+    let events = use_context::<UsePersistent<EventIndex>>();
+    let mut options = use_signal(NotificationOptions::default);
+    let mut webhook = use_signal(String::new);
+    let mut reminder = use_signal(|| DEFAULT_REMINDER.to_string());
+    let mut writing = use_signal(|| false);
+    let mut message = use_signal(|| None::<String>);
+
+    let load = use_coroutine({
+        let server = server.clone();
+        move |mut rx: UnboundedReceiver<String>| async move {
+            use futures_util::StreamExt;
+            let Some(server) = server else {
+                return;
+            };
+            while let Some(event) = rx.next().await {
+                match server.notifications(&event).await {
+                    Ok(loaded) => {
+                        webhook.set(loaded.settings.webhook.clone().unwrap_or_default());
+                        options.set(loaded);
+                        message.set(None);
+                    }
+                    Err(reason) => message.set(Some(reason.to_string())),
+                }
+            }
+        }
+    });
+
+    // Load again whenever another event is opened.
+    use_effect(move || {
+        load.send(events.get().current().storage_key());
+    });
+
+    let server = server?;
+
+    let save = {
+        let server = server.clone();
+        move |settings: NotificationSettings| {
+            let server = server.clone();
+            let event = events.get().current().storage_key();
+            options.write().settings = settings.clone();
+            spawn(async move {
+                if let Err(reason) = server.save_notifications(&event, &settings).await {
+                    message.set(Some(reason.to_string()));
+                }
+            });
+        }
+    };
+
+    let send_reminder = move |_| {
+        let server = server.clone();
+        let current = events.get().current().clone();
+        let text = reminder.read().clone();
+        spawn(async move {
+            message.set(Some("Sending…".to_string()));
+            let result = server.announce(&current.storage_key(), &current.name, &text).await;
+            message.set(Some(match result {
+                Ok(failures) if failures.is_empty() => "Reminder sent.".to_string(),
+                Ok(failures) => format!("Some of the reminder didn't go out: {}", failures.join("; ")),
+                Err(reason) => reason.to_string(),
+            }));
+        });
+    };
+
+    let current = options.read().clone();
+
+    rsx! {
+        div {
+            class: "flex flex-col gap-2 text-sm text-white mt-4",
+            div {
+                class: "flex flex-row flex-wrap gap-4 items-center",
+                "Send through: "
+                label {
+                    class: "flex gap-1 items-center",
+                    input {
+                        r#type: "checkbox",
+                        checked: current.settings.email,
+                        disabled: !current.email_available,
+                        onchange: {
+                            let (mut save, settings) = (save.clone(), current.settings.clone());
+                            move |event: FormEvent| save(NotificationSettings { email: event.checked(), ..settings.clone() })
+                        },
+                    }
+                    if current.email_available { "Email" } else { "Email (not set up on this server)" }
+                }
+                label {
+                    class: "flex gap-1 items-center",
+                    input {
+                        r#type: "checkbox",
+                        checked: current.settings.files,
+                        disabled: !current.files_available,
+                        onchange: {
+                            let (mut save, settings) = (save.clone(), current.settings.clone());
+                            move |event: FormEvent| save(NotificationSettings { files: event.checked(), ..settings.clone() })
+                        },
+                    }
+                    if current.files_available { "Files to send by hand" } else { "Files (not set up on this server)" }
+                }
+            }
+            input {
+                r#type: "url",
+                placeholder: "Chat webhook for reminders, e.g. from Slack or Matrix (optional)",
+                class: "block w-full bg-white text-gray-800 px-3 py-2 rounded-lg",
+                value: webhook.read().clone(),
+                oninput: move |event| webhook.set(event.value()),
+                onchange: {
+                    let (mut save, settings) = (save.clone(), current.settings.clone());
+                    move |_| {
+                        let url = webhook.read().trim().to_string();
+                        save(NotificationSettings { webhook: (!url.is_empty()).then_some(url), ..settings.clone() })
+                    }
+                },
+            }
+            div {
+                class: "flex flex-row flex-wrap gap-2 items-center",
+                button {
+                    r#type: "button",
+                    class: "underline cursor-pointer",
+                    onclick: send_reminder,
+                    "Send everyone a reminder"
+                }
+                button {
+                    r#type: "button",
+                    class: "underline cursor-pointer",
+                    onclick: move |_| {
+                        let shown = *writing.read();
+                        writing.set(!shown);
+                    },
+                    if *writing.read() { "Done editing the reminder" } else { "Edit the reminder" }
+                }
+            }
+            if *writing.read() {
+                textarea {
+                    class: "block w-full bg-white text-gray-800 px-3 py-2 rounded-lg field-sizing-content",
+                    value: reminder.read().clone(),
+                    oninput: move |event| reminder.set(event.value()),
+                }
+                p { "{{event}}, {{budget}}, {{date}} and {{details}} are filled in." }
+            }
+            if let Some(message) = message.read().clone() {
+                p { class: "bg-white text-gray-900 rounded-lg px-3 py-2", "{message}" }
+            }
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use crate::server::{self, ORGANIZER_PREFIX};
+use dioxus::prelude::*;
+
+/// What someone sees when the server is serving the app but this browser
+/// doesn't have the organizer's key.
+#[component]
+pub fn OrganizerLogin() -> Element {
+    let mut key = use_signal(String::new);
+    let mut error = use_signal(|| None::<String>);
+
+    let unlock = move |_| {
+        // Take the key on its own or the whole link.
+        let typed = key.read().trim().to_string();
+        let typed = typed.rsplit_once('#').map_or(typed.as_str(), |(_, fragment)| fragment);
+        let typed = typed.strip_prefix(ORGANIZER_PREFIX).unwrap_or(typed);
+        if typed.is_empty() {
+            return;
+        }
+        match server::save_organizer_key(typed) {
+            Ok(()) => {
+                if let Some(window) = web_sys::window() {
+                    let _ = window.location().reload();
+                }
+            }
+            Err(reason) => error.set(Some(reason.to_string())),
+        }
+    };
+
+    rsx! {
+        div {
+            class: "lg:w-2/3 mx-auto p-4",
+            div {
+                class: "candy-cane-striped p-2 rounded-xl",
+                div {
+                    class: "bg-white border border-red-400 rounded-xl p-4 text-gray-900",
+                    h1 { class: "text-2xl font-bold", "Only the organizer can open this" }
+                    p {
+                        class: "mt-2 text-sm text-gray-700",
+                        "Open the organizer link the server printed when it started, or paste it here. If you were sent an invite link, open that instead."
+                    }
+                    form {
+                        class: "mt-4 flex gap-2",
+                        onsubmit: unlock,
+                        input {
+                            r#type: "password",
+                            placeholder: "Organizer link or key",
+                            class: "grow bg-white text-gray-800 px-3 py-2 rounded-lg border border-gray-300 focus:border-red-900",
+                            value: key.read().clone(),
+                            oninput: move |event| key.set(event.value()),
+                        }
+                        button {
+                            r#type: "submit",
+                            class: "bg-red-900 text-white px-3 py-2 rounded-lg border-red-700 border-2 cursor-pointer hover:bg-red-600",
+                            "Open"
+                        }
+                    }
+                    if let Some(reason) = error.read().clone() {
+                        p { class: "text-sm text-red-700 mt-2", "{reason}" }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,150 @@
+use crate::reveal::reveals;
+use crate::{EventIndex, SecretSatan, UsePersistent};
+use dioxus::prelude::*;
+
+#[derive(Clone, PartialEq)]
+enum Stage {
+    Pick,
+    Confirm(String),
+    Reveal(String),
+}
+
+/// One phone passed around the room. Each person picks their name, confirms
+/// it's them, and holds a button to see who they're giving to. The result is
+/// hidden again as soon as they let go.
+#[component]
+pub fn PassTheDevice(open: Signal<bool>) -> Element {
+    let events = use_context::<UsePersistent<EventIndex>>();
+    let mut storage = use_context::<UsePersistent<SecretSatan>>();
+    let mut state = use_context::<Signal<SecretSatan>>();
+    let mut stage = use_signal(|| Stage::Pick);
+    let mut holding = use_signal(|| false);
+
+    let list = state.read().clone();
+    let givers = list.result_givers();
+    let seen = givers.iter().filter(|giver| list.has_viewed(giver)).count();
+
+    let mut hold = move |name: String| {
+        holding.set(true);
+        state.write().mark_viewed(&name);
+        storage.set(state.read().clone());
+    };
+
+    rsx! {
+        dialog {
+            open: *open.read(),
+            class: "relative z-10",
+            div { class: "fixed inset-0 bg-red-200/25 backdrop-blur-sm backdrop-grayscale" }
+            div {
+                class: "fixed inset-0 z-10 w-screen h-screen overflow-y-auto",
+                div {
+                    class: "flex items-center min-h-full justify-center p-4",
+                    div {
+                        class: "candy-cane-striped p-2 rounded-xl w-full max-w-md",
+                        div {
+                            class: "bg-white border border-red-400 rounded-xl p-4 text-gray-900 text-center select-none",
+                            match stage.read().clone() {
+                                Stage::Pick => rsx! {
+                                    h1 { class: "text-2xl font-bold", "Who's holding the phone?" }
+                                    p { class: "text-sm text-gray-700", "{seen} of {givers.len()} have seen who they're giving to." }
+                                    if list.everyone_viewed() {
+                                        p { class: "mt-2 font-semibold", "Everyone's done! Hand the phone back to the organizer." }
+                                    }
+                                    div {
+                                        class: "flex flex-row flex-wrap gap-2 justify-center mt-4",
+                                        for giver in givers.iter().cloned() {
+                                            button {
+                                                r#type: "button",
+                                                class: "px-3 py-2 rounded-lg border-2 border-gray-200 cursor-pointer disabled:cursor-not-allowed disabled:opacity-50 disabled:line-through",
+                                                disabled: list.has_viewed(&giver),
+                                                onclick: {
+                                                    let giver = giver.clone();
+                                                    move |_| stage.set(Stage::Confirm(giver.clone()))
+                                                },
+                                                "{giver}"
+                                            }
+                                        }
+                                    }
+                                    button {
+                                        r#type: "button",
+                                        class: "underline cursor-pointer text-sm mt-4",
+                                        onclick: move |_| open.set(false),
+                                        "Close"
+                                    }
+                                },
+                                Stage::Confirm(name) => rsx! {
+                                    h1 { class: "text-2xl font-bold", "Are you {name}?" }
+                                    div {
+                                        class: "flex justify-center gap-4 mt-4",
+                                        button {
+                                            r#type: "button",
+                                            class: "bg-red-900 text-white px-3 py-2 rounded-lg border-red-700 border-2 cursor-pointer hover:bg-red-600",
+                                            onclick: move |_| stage.set(Stage::Reveal(name.clone())),
+                                            "Yes, it's me"
+                                        }
+                                        button {
+                                            r#type: "button",
+                                            class: "underline cursor-pointer",
+                                            onclick: move |_| stage.set(Stage::Pick),
+                                            "No"
+                                        }
+                                    }
+                                },
+                                Stage::Reveal(name) => {
+                                    let event = events.get().current().name.clone();
+                                    let result = reveals(&event, &list.results).into_iter().find(|reveal| reveal.giver == name);
+                                    rsx! {
+                                        h1 { class: "text-2xl font-bold", "Hi {name}!" }
+                                        div {
+                                            class: "my-4 h-24 flex flex-col items-center justify-center",
+                                            if *holding.read() {
+                                                if let Some(result) = result {
+                                                    p { "You're giving a gift to" }
+                                                    p { class: "text-4xl font-bold", "{result.receiver}" }
+                                                    if !result.sharing_with.is_empty() {
+                                                        p { class: "text-sm text-gray-700", "together with {result.sharing_with.join(\", \")}" }
+                                                    }
+                                                }
+                                            } else {
+                                                p { class: "text-gray-500", "Hidden" }
+                                            }
+                                        }
+                                        button {
+                                            r#type: "button",
+                                            class: "bg-red-900 text-white px-3 py-4 w-full rounded-lg border-red-700 border-2 cursor-pointer touch-none",
+                                            onmousedown: {
+                                                let name = name.clone();
+                                                move |_| hold(name.clone())
+                                            },
+                                            ontouchstart: {
+                                                let name = name.clone();
+                                                move |_| hold(name.clone())
+                                            },
+                                            onmouseup: move |_| holding.set(false),
+                                            onmouseleave: move |_| holding.set(false),
+                                            ontouchend: move |_| holding.set(false),
+                                            ontouchcancel: move |_| holding.set(false),
+                                            // Long presses shouldn't open a menu over the result
+                                            prevent_default: "oncontextmenu",
+                                            oncontextmenu: move |_| {},
+                                            "Press and hold to see"
+                                        }
+                                        button {
+                                            r#type: "button",
+                                            class: "underline cursor-pointer text-sm mt-4",
+                                            onclick: move |_| {
+                                                holding.set(false);
+                                                stage.set(Stage::Pick);
+                                            },
+                                            "Done, pass it on"
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use crate::print::qr_sheet_html;
+use crate::reveal::{reveals, seal};
+use crate::{qr, EventIndex, SecretSatan, UsePersistent};
+use dioxus::prelude::*;
+
+/// A QR code of each giver's reveal link, to print and hand out or scan
+/// straight off the screen.
+#[component]
+pub fn QrCodes() -> Element {
+    let events = use_context::<UsePersistent<EventIndex>>();
+    let state = use_context::<Signal<SecretSatan>>();
+    // Made on request, since every link gets a fresh key.
+    let mut codes = use_signal(|| None::<Vec<(String, String)>>);
+    let mut error = use_signal(|| None::<String>);
+
+    let generate = move |_| {
+        let event = events.get().current().name.clone();
+        let page = web_sys::window()
+            .and_then(|window| {
+                let location = window.location();
+                Some(location.origin().ok()? + &location.pathname().ok()?)
+            })
+            .unwrap_or_default();
+        let mut rng = rand::thread_rng();
+        let generated: Result<Vec<_>, String> = reveals(&event, &state.read().results)
+            .into_iter()
+            .map(|reveal| {
+                let link = format!("{page}#{}", seal(&reveal, &mut rng));
+                Ok((reveal.giver, qr::svg(&link)?))
+            })
+            .collect();
+        match generated {
+            Ok(generated) => {
+                error.set(None);
+                codes.set(Some(generated));
+            }
+            Err(reason) => error.set(Some(format!("The QR codes couldn't be made. {reason}"))),
+        }
+    };
+
+    rsx! {
+        div {
+            class: "flex flex-row flex-wrap gap-2 items-center text-sm text-white mt-2",
+            "Or hand out QR codes: "
+            if codes.read().is_some() {
+                button {
+                    r#type: "button",
+                    class: "underline cursor-pointer",
+                    onclick: move |_| {
+                        let event = events.get().current().name.clone();
+                        let html = qr_sheet_html(&event, codes.read().as_deref().unwrap_or_default());
+                        eval(&format!(
+                            "const page = window.open('', '_blank');
+                            page.document.write({html:?});
+                            page.document.close();
+                            page.focus();
+                            page.print();"
+                        ));
+                    },
+                    "Print"
+                }
+                button {
+                    r#type: "button",
+                    class: "underline cursor-pointer",
+                    onclick: move |_| codes.set(None),
+                    "Hide"
+                }
+            } else {
+                button {
+                    r#type: "button",
+                    class: "underline cursor-pointer",
+                    onclick: generate,
+                    "Show"
+                }
+            }
+            if let Some(reason) = error.read().clone() {
+                span { class: "font-semibold", "{reason}" }
+            }
+        }
+        if let Some(codes) = codes.read().clone() {
+            div {
+                class: "grid gap-4 w-full mt-4 grid-cols-2 sm:grid-cols-3 md:grid-cols-4",
+                for (giver, image) in codes {
+                    div {
+                        key: "{giver}",
+                        class: "bg-white rounded-lg p-2 text-center",
+                        p { class: "font-bold", "{giver}" }
+                        div { dangerous_inner_html: "{image}" }
+                    }
+                }
+            }
+        }
+    }
+}
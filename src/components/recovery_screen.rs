@@ -0,0 +1,80 @@
+use crate::components::download;
+use crate::storage::Unreadable;
+use crate::{EventIndex, SecretSatan, UsePersistent};
+use dioxus::prelude::*;
+
+#[component]
+pub fn RecoveryScreen(unreadable: Unreadable) -> Element {
+    let mut events = use_context::<UsePersistent<EventIndex>>();
+    let mut storage = use_context::<UsePersistent<SecretSatan>>();
+    let mut state = use_context::<Signal<SecretSatan>>();
+    let mut confirm = use_signal(|| false);
+
+    let raw = unreadable.raw.clone();
+    let filename = format!("secret-satan-{}-backup.json", unreadable.key);
+    let is_event_index = unreadable.key == "events";
+
+    rsx! {
+        div {
+            class: "lg:w-2/3 mx-auto p-4",
+            div {
+                class: "candy-cane-striped p-2 rounded-xl",
+                div {
+                    class: "bg-white border border-red-400 rounded-xl p-4",
+                    h1 {
+                        class: "text-2xl font-bold text-gray-900",
+                        "We couldn't read your saved list"
+                    }
+                    p {
+                        class: "mt-2 text-sm text-gray-700",
+                        "Your data is still saved exactly as it was. Download a copy before doing anything else, so nothing is lost."
+                    }
+                    p {
+                        class: "mt-2 text-xs text-gray-500",
+                        "{unreadable.error}"
+                    }
+                    textarea {
+                        readonly: true,
+                        class: "block w-full mt-4 bg-gray-100 text-gray-800 px-3 py-2 rounded-lg font-mono text-xs h-48",
+                        value: "{raw}"
+                    }
+                    div {
+                        class: "flex justify-end gap-4 mt-4",
+                        button {
+                            r#type: "button",
+                            class: "group text-gray-800 bg-white px-1 py-2 rounded-lg border-gray-200 border-2 cursor-pointer calculate-button transition",
+                            onclick: move |_| download(&filename, "application/json", &raw),
+                            span {
+                                class: "bg-white py-1 px-1 rounded-md",
+                                "Download raw data"
+                            }
+                        }
+                        if *confirm.read() {
+                            button {
+                                r#type: "button",
+                                class: "bg-red-900 text-white px-3 py-2 rounded-lg border-red-700 border-2 cursor-danger whitespace-nowrap hover:bg-red-600 cursor-pointer",
+                                onclick: move |_| {
+                                    if is_event_index {
+                                        events.set(EventIndex::default());
+                                    } else {
+                                        storage.set(SecretSatan::default());
+                                        state.set(SecretSatan::default());
+                                    }
+                                    confirm.set(false);
+                                },
+                                "Yes, replace it"
+                            }
+                        } else {
+                            button {
+                                r#type: "button",
+                                class: "bg-red-900 text-white px-3 py-2 rounded-lg border-red-700 border-2 cursor-danger whitespace-nowrap hover:bg-red-600 cursor-pointer",
+                                onclick: move |_| confirm.set(true),
+                                "Start over"
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
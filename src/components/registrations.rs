@@ -0,0 +1,199 @@
+use crate::server::{invite_fragment, Server, ServerError};
+use crate::signup::{Invite, Signup, SignupStatus};
+use crate::{EventIndex, History, SecretSatan, UsePersistent};
+use dioxus::prelude::*;
+
+/// Lets participants sign themselves up through an invite link, and the
+/// organizer approve them onto the list. Only shown in server mode.
+#[component]
+pub fn Registrations() -> Element {
+    let server = use_context::<Option<Server>>();
+    let events = use_context::<UsePersistent<EventIndex>>();
+    let mut storage = use_context::<UsePersistent<SecretSatan>>();
+    let mut history = use_context::<UsePersistent<History>>();
+    let mut state = use_context::<Signal<SecretSatan>>();
+    let mut invite = use_signal(|| None::<Invite>);
+    let mut signups = use_signal(Vec::<Signup>::new);
+    let mut error = use_signal(|| None::<String>);
+    let mut copied = use_signal(|| false);
+
+    let refresh = use_coroutine({
+        let server = server.clone();
+        move |mut rx: UnboundedReceiver<String>| async move {
+            use futures_util::StreamExt;
+            let Some(server) = server else {
+                return;
+            };
+            while let Some(event) = rx.next().await {
+                let loaded = async { Ok::<_, ServerError>((server.invite(&event).await?, server.signups(&event).await?)) };
+                match loaded.await {
+                    Ok((loaded, list)) => {
+                        invite.set(loaded);
+                        signups.set(list);
+                        error.set(None);
+                    }
+                    Err(reason) => error.set(Some(reason.to_string())),
+                }
+            }
+        }
+    });
+
+    // Load again whenever another event is opened.
+    use_effect(move || {
+        refresh.send(events.get().current().storage_key());
+    });
+
+    let server = server?;
+
+    let save_invite = {
+        let server = server.clone();
+        move |open: bool| {
+            let server = server.clone();
+            let current = events.get().current().clone();
+            spawn(async move {
+                match server.save_invite(&current.storage_key(), &current.name, open).await {
+                    Ok(saved) => invite.set(Some(saved)),
+                    Err(reason) => error.set(Some(reason.to_string())),
+                }
+            });
+        }
+    };
+
+    let decide = move |mut signup: Signup, status: SignupStatus| {
+        let server = server.clone();
+        if status == SignupStatus::Approved {
+            let mut list = state.read().clone();
+            if let Err(reason) = signup.approve(&mut list) {
+                error.set(Some(reason.to_string()));
+                return;
+            }
+            history.update(|history| history.record(state.read().clone()));
+            state.set(list.clone());
+            storage.set(list);
+        }
+        spawn(async move {
+            match server.set_signup_status(signup.id, status).await {
+                Ok(()) => refresh.send(events.get().current().storage_key()),
+                Err(reason) => error.set(Some(reason.to_string())),
+            }
+        });
+    };
+
+    let pending: Vec<Signup> = signups.read().iter().filter(|signup| signup.status == SignupStatus::Pending).cloned().collect();
+    let decided = signups.read().len() - pending.len();
+    // Names they'd avoid that the draw won't know, for the organizer to check.
+    let pending: Vec<(Signup, Vec<String>)> = pending
+        .into_iter()
+        .map(|signup| {
+            let unknown = signup.form.unknown_avoid(&state.read());
+            (signup, unknown)
+        })
+        .collect();
+
+    rsx! {
+        div {
+            class: "flex flex-col gap-2 text-sm text-white mb-4",
+            div {
+                class: "flex flex-row flex-wrap gap-2 items-center",
+                match invite.read().clone() {
+                    None => rsx! {
+                        button {
+                            r#type: "button",
+                            class: "underline cursor-pointer",
+                            onclick: {
+                                let save_invite = save_invite.clone();
+                                move |_| save_invite(true)
+                            },
+                            "Invite people to sign up"
+                        }
+                    },
+                    Some(current) => rsx! {
+                        span { if current.open { "Sign-ups are open." } else { "Sign-ups are closed." } }
+                        button {
+                            r#type: "button",
+                            class: "underline cursor-pointer",
+                            onclick: {
+                                let fragment = invite_fragment(&current);
+                                move |_| {
+                                    eval(&format!(
+                                        "navigator.clipboard.writeText(location.origin + location.pathname + '#' + {fragment:?})"
+                                    ));
+                                    copied.set(true);
+                                }
+                            },
+                            if *copied.read() { "Copied" } else { "Copy invite link" }
+                        }
+                        button {
+                            r#type: "button",
+                            class: "underline cursor-pointer",
+                            onclick: {
+                                let save_invite = save_invite.clone();
+                                move |_| save_invite(!current.open)
+                            },
+                            if current.open { "Close sign-ups" } else { "Reopen sign-ups" }
+                        }
+                        button {
+                            r#type: "button",
+                            class: "underline cursor-pointer",
+                            onclick: move |_| refresh.send(events.get().current().storage_key()),
+                            "Check for new sign-ups"
+                        }
+                        if decided > 0 {
+                            span { "({decided} already reviewed)" }
+                        }
+                    },
+                }
+            }
+            if let Some(reason) = error.read().clone() {
+                p { class: "bg-white text-red-700 rounded-lg px-3 py-2", "{reason}" }
+            }
+            if !pending.is_empty() {
+                ul {
+                    class: "grid gap-2 sm:grid-cols-2",
+                    for (signup, unknown) in pending {
+                        li {
+                            class: "bg-white text-gray-900 rounded-lg px-4 py-2",
+                            p { class: "font-bold", "{signup.form.name}" }
+                            if let Some(email) = signup.form.email.clone() {
+                                p { "{email}" }
+                            }
+                            if let Some(wishlist) = signup.form.wishlist.clone() {
+                                p { class: "whitespace-pre-line", "Wishes: {wishlist}" }
+                            }
+                            if !signup.form.avoid.is_empty() {
+                                p { class: "italic", "Would rather not give to {signup.form.avoid.join(\", \")}" }
+                            }
+                            if !unknown.is_empty() {
+                                p {
+                                    class: "text-red-700",
+                                    "Not on the list, so the draw will ignore them: {unknown.join(\", \")}"
+                                }
+                            }
+                            div {
+                                class: "flex gap-4 mt-1",
+                                button {
+                                    r#type: "button",
+                                    class: "underline cursor-pointer",
+                                    onclick: {
+                                        let (signup, mut decide) = (signup.clone(), decide.clone());
+                                        move |_| decide(signup.clone(), SignupStatus::Approved)
+                                    },
+                                    "Add to the list"
+                                }
+                                button {
+                                    r#type: "button",
+                                    class: "underline cursor-pointer",
+                                    onclick: {
+                                        let (signup, mut decide) = (signup.clone(), decide.clone());
+                                        move |_| decide(signup.clone(), SignupStatus::Rejected)
+                                    },
+                                    "Turn down"
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use crate::reveal::{Reveal, RevealError};
+use dioxus::prelude::*;
+
+/// What someone sees when they open their reveal link, instead of the
+/// organizer's list.
+#[component]
+pub fn RevealScreen(reveal: Result<Reveal, RevealError>) -> Element {
+    let mut shown = use_signal(|| false);
+
+    rsx! {
+        div {
+            class: "lg:w-2/3 mx-auto p-4",
+            div {
+                class: "candy-cane-striped p-2 rounded-xl",
+                div {
+                    class: "bg-white border border-red-400 rounded-xl p-4 text-gray-900 text-center",
+                    match reveal {
+                        Err(error) => rsx! {
+                            h1 { class: "text-2xl font-bold", "We couldn't open this link" }
+                            p { class: "mt-2 text-sm text-gray-700", "{error} Ask your organizer to send it again." }
+                        },
+                        Ok(reveal) => rsx! {
+                            p { class: "text-sm text-gray-700", "{reveal.event}" }
+                            h1 { class: "text-2xl font-bold", "Hi {reveal.giver}!" }
+                            if *shown.read() {
+                                p { class: "mt-4", "You're giving a gift to" }
+                                p { class: "text-4xl font-bold my-2", "{reveal.receiver}" }
+                                if !reveal.sharing_with.is_empty() {
+                                    p { class: "text-sm text-gray-700", "together with {reveal.sharing_with.join(\", \")}" }
+                                }
+                            } else {
+                                p { class: "mt-2 text-sm text-gray-700", "Make sure nobody's looking over your shoulder." }
+                                button {
+                                    r#type: "button",
+                                    class: "mt-4 bg-red-900 text-white px-3 py-2 rounded-lg border-red-700 border-2 cursor-pointer hover:bg-red-600",
+                                    onclick: move |_| shown.set(true),
+                                    "Show me who I'm giving to"
+                                }
+                            }
+                        },
+                    }
+                }
+            }
+        }
+    }
+}
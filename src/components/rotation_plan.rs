@@ -0,0 +1,128 @@
+use crate::{History, SecretSatan, UsePersistent};
+use dioxus::prelude::*;
+
+/// The most years that can be planned at once. Each year is a full draw,
+/// tried many times over, so long plans would freeze the page.
+const MAX_YEARS: usize = 10;
+
+#[component]
+pub fn RotationPlan() -> Element {
+    let mut storage = use_context::<UsePersistent<SecretSatan>>();
+    let mut history = use_context::<UsePersistent<History>>();
+    let mut state = use_context::<Signal<SecretSatan>>();
+    let mut years = use_signal(|| 5usize);
+
+    let rotation = state.read().rotation.clone();
+    let participants = state.read().participants.len();
+    // People joining, leaving or being renamed can leave the plan behind.
+    let out_of_date = rotation
+        .as_ref()
+        .and_then(|rotation| rotation.years.get(rotation.next))
+        .is_some_and(|next| !state.read().fits(next));
+
+    rsx! {
+        div {
+            class: "w-full text-white",
+            h2 {
+                class: "text-xl font-bold",
+                "Plan ahead"
+            }
+            div {
+                class: "flex flex-row flex-wrap gap-2 items-center text-sm mt-1",
+                label {
+                    "Plan "
+                    input {
+                        r#type: "number",
+                        min: "1",
+                        max: "{MAX_YEARS}",
+                        value: "{years}",
+                        class: "w-14 bg-white text-gray-800 rounded-lg py-1 px-2",
+                        onchange: move |event| {
+                            if let Ok(value) = event.value().parse::<usize>() {
+                                years.set(value.clamp(1, MAX_YEARS));
+                            }
+                        }
+                    }
+                    " years of draws so nobody repeats a pairing"
+                }
+                button {
+                    r#type: "button",
+                    class: "group text-gray-800 bg-white px-1 py-1 rounded-lg border-gray-200 border-2 cursor-pointer disabled:cursor-not-allowed disabled:opacity-50 calculate-button transition",
+                    disabled: participants < 3,
+                    onclick: move |_| {
+                        let this_year = js_sys::Date::new_0().get_full_year() as i32;
+                        let plan = state.read().plan_rotation(this_year, *years.read());
+                        match plan {
+                            Ok(rotation) => {
+                                state.write().rotation = Some(rotation);
+                                storage.set(state.read().clone());
+                            }
+                            Err(error) => {
+                                eval(&format!("alert({:?})", format!("There was an error planning the rotation. {error}")));
+                            }
+                        }
+                    },
+                    span {
+                        class: "bg-white py-1 px-1 rounded-md",
+                        "Plan rotation"
+                    }
+                }
+            }
+            if let Some(rotation) = rotation {
+                div {
+                    class: "text-sm mt-2",
+                    match rotation.next_year() {
+                        Some(year) => rsx! {
+                            p {
+                                "Next up: ",
+                                span { class: "font-semibold", "{year}" }
+                                " (year {rotation.next + 1} of {rotation.years.len()})"
+                            }
+                            if out_of_date {
+                                p { class: "italic", "The list has changed since this plan was made, so it no longer fits. Plan the rotation again." }
+                            } else {
+                                button {
+                                    r#type: "button",
+                                    class: "underline cursor-pointer",
+                                    onclick: move |_| {
+                                        let next = state.write().take_planned_year();
+                                        match next {
+                                            Ok(Some((_, assignments))) => {
+                                                state.write().set_results(assignments.clone());
+                                                storage.set(state.read().clone());
+                                                history.update(|history| history.commit(assignments, None, &mut rand::thread_rng()));
+                                            }
+                                            Ok(None) => {}
+                                            Err(error) => {
+                                                eval(&format!("alert({:?})", error.to_string()));
+                                            }
+                                        }
+                                    },
+                                    "Use the {year} draw"
+                                }
+                            }
+                        },
+                        None => rsx! {
+                            p { "Every planned year has been used." }
+                        },
+                    }
+                    if rotation.repeats() > 0 {
+                        p {
+                            class: "italic",
+                            "{rotation.repeats()} pairings had to repeat to fit every year in."
+                        }
+                    }
+                    button {
+                        r#type: "button",
+                        class: "underline cursor-pointer",
+                        onclick: move |_| {
+                            state.write().rotation = None;
+                            storage.set(state.read().clone());
+                        },
+                        "Clear plan"
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use crate::components::download;
+use crate::transfer::export_event;
+use crate::{EventIndex, SecretSatan, UsePersistent};
+use dioxus::prelude::*;
+
+#[component]
+pub fn SaveErrorBanner() -> Element {
+    let mut events = use_context::<UsePersistent<EventIndex>>();
+    let mut storage = use_context::<UsePersistent<SecretSatan>>();
+    let state = use_context::<Signal<SecretSatan>>();
+
+    let error = storage.save_error().or_else(|| events.save_error())?;
+
+    rsx! {
+        div {
+            role: "alert",
+            class: "mb-4 p-2 candy-cane-striped rounded-xl",
+            div {
+                class: "bg-white border border-red-400 rounded-xl px-4 py-2 text-gray-900",
+                p {
+                    class: "font-semibold",
+                    "Your changes aren't being saved."
+                }
+                p {
+                    class: "text-sm text-gray-700",
+                    "{error} You can keep working, but everything will be lost when you close this page unless you export it."
+                }
+                div {
+                    class: "flex flex-row gap-4 mt-2 text-sm",
+                    button {
+                        r#type: "button",
+                        class: "bg-red-900 text-white px-3 py-1 rounded-lg border-red-700 border-2 cursor-pointer hover:bg-red-600",
+                        onclick: move |_| {
+                            let name = events.get().current().name.clone();
+                            download(&format!("{name}.json"), "application/json", &export_event(&name, &state.read()));
+                        },
+                        "Export your data now"
+                    }
+                    button {
+                        r#type: "button",
+                        class: "underline cursor-pointer",
+                        onclick: move |_| {
+                            storage.retry();
+                            events.retry();
+                        },
+                        "Try saving again"
+                    }
+                }
+            }
+        }
+    }
+}
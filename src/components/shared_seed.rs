@@ -0,0 +1,251 @@
+use crate::entropy::{Contribution, Phase, Round, Seed};
+use crate::server::{contribution_fragment, Server};
+use crate::{EventIndex, History, SecretSatan, UsePersistent};
+use dioxus::prelude::*;
+use std::collections::BTreeMap;
+
+/// Draws from a seed everyone contributes to, so nobody has to trust the
+/// organizer's device: words typed in at the party, or, in server mode,
+/// values committed to and revealed through each person's own link.
+#[component]
+pub fn SharedSeed() -> Element {
+    let server = use_context::<Option<Server>>();
+    let events = use_context::<UsePersistent<EventIndex>>();
+    let mut storage = use_context::<UsePersistent<SecretSatan>>();
+    let mut history = use_context::<UsePersistent<History>>();
+    let mut state = use_context::<Signal<SecretSatan>>();
+    let mut typing = use_signal(|| false);
+    let mut words = use_signal(BTreeMap::<String, String>::new);
+    let mut round = use_signal(|| None::<Round>);
+    let mut error = use_signal(|| None::<String>);
+
+    let load = use_coroutine({
+        let server = server.clone();
+        move |mut rx: UnboundedReceiver<String>| async move {
+            use futures_util::StreamExt;
+            let Some(server) = server else {
+                return;
+            };
+            while let Some(event) = rx.next().await {
+                match server.entropy_round(&event).await {
+                    Ok(loaded) => {
+                        round.set(loaded);
+                        error.set(None);
+                    }
+                    Err(reason) => error.set(Some(reason.to_string())),
+                }
+            }
+        }
+    });
+
+    // Load again whenever another event is opened.
+    use_effect(move || {
+        load.send(events.get().current().storage_key());
+    });
+
+    let mut draw_from = move |seed: Seed| {
+        let draw = state.read().draw_with_rng(&mut seed.rng());
+        match draw {
+            Ok(assignments) => {
+                state.write().set_results(assignments.clone());
+                storage.set(state.read().clone());
+                history.update(|history| history.commit(assignments, Some(seed), &mut rand::thread_rng()));
+            }
+            Err(error) => {
+                eval(&format!("alert({:?})", format!("There was an error calculating the gift giving list. {error}")));
+            }
+        }
+    };
+
+    let set_phase = {
+        let server = server.clone();
+        move |phase: Phase| {
+            let Some(server) = server.clone() else {
+                return;
+            };
+            let event = events.get().current().storage_key();
+            spawn(async move {
+                match server.set_entropy_phase(&event, phase).await {
+                    Ok(updated) => round.set(Some(updated)),
+                    Err(reason) => error.set(Some(reason.to_string())),
+                }
+            });
+        }
+    };
+
+    let names: Vec<String> = state.read().participants.iter().map(|participant| participant.name.clone()).collect();
+    if names.len() < 3 {
+        return rsx! {};
+    }
+    let typed: Vec<Contribution> = words
+        .read()
+        .iter()
+        .filter(|(name, word)| names.contains(name) && !word.trim().is_empty())
+        .map(|(name, word)| Contribution {
+            name: name.clone(),
+            value: word.trim().to_string(),
+        })
+        .collect();
+
+    rsx! {
+        div {
+            class: "w-full flex flex-col gap-2 text-sm text-white",
+            h2 { class: "text-xl font-bold", "Draw from everyone's randomness" }
+            p { "Instead of trusting this device, everyone can add something to the seed the draw uses. How the seed was made is kept with the draw, so anyone can work it out again." }
+            button {
+                r#type: "button",
+                class: "underline cursor-pointer self-start",
+                onclick: move |_| {
+                    let shown = *typing.read();
+                    typing.set(!shown);
+                },
+                if *typing.read() { "Done typing words" } else { "Have everyone type a word here" }
+            }
+            if *typing.read() {
+                p { "Pass the device around. Nobody should see anyone else's word." }
+                div {
+                    class: "grid gap-2 sm:grid-cols-2 md:grid-cols-3",
+                    for name in names.clone() {
+                        label {
+                            class: "flex flex-col",
+                            "{name}"
+                            input {
+                                r#type: "password",
+                                autocomplete: "off",
+                                class: "bg-white text-gray-800 px-3 py-2 rounded-lg",
+                                value: words.read().get(&name).cloned().unwrap_or_default(),
+                                oninput: {
+                                    let name = name.clone();
+                                    move |event: FormEvent| {
+                                        words.write().insert(name.clone(), event.value());
+                                    }
+                                },
+                            }
+                        }
+                    }
+                }
+                button {
+                    r#type: "button",
+                    class: "underline cursor-pointer self-start disabled:cursor-not-allowed disabled:opacity-50",
+                    disabled: typed.is_empty(),
+                    onclick: {
+                        let typed = typed.clone();
+                        move |_| {
+                            draw_from(Seed::derive(typed.clone()));
+                            words.write().clear();
+                            typing.set(false);
+                        }
+                    },
+                    "Draw from {typed.len()} of {names.len()} words"
+                }
+            }
+            if server.is_some() {
+                div {
+                    class: "flex flex-row flex-wrap gap-2 items-center",
+                    match round.read().clone() {
+                        None => rsx! {
+                            button {
+                                r#type: "button",
+                                class: "underline cursor-pointer",
+                                onclick: {
+                                    let set_phase = set_phase.clone();
+                                    move |_| set_phase(Phase::Committing)
+                                },
+                                "Collect contributions through everyone's own link"
+                            }
+                        },
+                        Some(current) if current.phase == Phase::Committing => rsx! {
+                            span { "{current.pledges.len()} of {current.seats.len()} have contributed." }
+                            button {
+                                r#type: "button",
+                                class: "underline cursor-pointer",
+                                onclick: move |_| load.send(events.get().current().storage_key()),
+                                "Check again"
+                            }
+                            button {
+                                r#type: "button",
+                                class: "underline cursor-pointer disabled:cursor-not-allowed disabled:opacity-50",
+                                disabled: current.pledges.is_empty(),
+                                onclick: {
+                                    let set_phase = set_phase.clone();
+                                    move |_| set_phase(Phase::Revealing)
+                                },
+                                "Stop collecting and ask everyone to reveal"
+                            }
+                            ul {
+                                class: "w-full flex flex-row flex-wrap gap-x-4",
+                                for seat in current.seats.clone() {
+                                    li {
+                                        button {
+                                            r#type: "button",
+                                            class: "underline cursor-pointer",
+                                            onclick: {
+                                                let fragment = contribution_fragment(&seat);
+                                                move |_| {
+                                                    eval(&format!(
+                                                        "navigator.clipboard.writeText(location.origin + location.pathname + '#' + {fragment:?})"
+                                                    ));
+                                                }
+                                            },
+                                            "Copy {seat.name}'s link"
+                                        }
+                                        if current.pledges.iter().any(|pledge| pledge.name == seat.name) {
+                                            " ✓"
+                                        }
+                                    }
+                                }
+                            }
+                        },
+                        Some(current) => rsx! {
+                            span { "{current.pledges.iter().filter(|pledge| pledge.revealed).count()} of {current.pledges.len()} have revealed theirs." }
+                            if current.tainted {
+                                span { class: "bg-white text-red-700 rounded-lg px-2", "Reveals were closed before everyone revealed, so this seed doesn't rest on everyone's value." }
+                            } else if current.phase == Phase::Closed {
+                                span { "Reveals are closed." }
+                            } else {
+                                button {
+                                    r#type: "button",
+                                    class: "underline cursor-pointer",
+                                    onclick: move |_| load.send(events.get().current().storage_key()),
+                                    "Check again"
+                                }
+                            }
+                            if let Some(seed) = current.seed() {
+                                button {
+                                    r#type: "button",
+                                    class: "underline cursor-pointer",
+                                    onclick: move |_| draw_from(seed.clone()),
+                                    "Draw from the revealed contributions"
+                                }
+                            } else if current.pledges.iter().any(|pledge| pledge.revealed) {
+                                // Drawing before everyone reveals would let the last to reveal
+                                // choose whether to, so leaving anyone out is made explicit.
+                                button {
+                                    r#type: "button",
+                                    class: "underline cursor-pointer",
+                                    onclick: {
+                                        let set_phase = set_phase.clone();
+                                        move |_| set_phase(Phase::Closed)
+                                    },
+                                    "Stop waiting and leave out {current.unrevealed().join(\", \")}"
+                                }
+                            }
+                            button {
+                                r#type: "button",
+                                class: "underline cursor-pointer",
+                                onclick: {
+                                    let set_phase = set_phase.clone();
+                                    move |_| set_phase(Phase::Committing)
+                                },
+                                "Start over"
+                            }
+                        },
+                    }
+                }
+            }
+            if let Some(reason) = error.read().clone() {
+                p { class: "bg-white text-red-700 rounded-lg px-3 py-2", "{reason}" }
+            }
+        }
+    }
+}
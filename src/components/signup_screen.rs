@@ -0,0 +1,120 @@
+use crate::server::{PublicInvite, Server, ServerError};
+use crate::signup::SignupForm;
+use dioxus::prelude::*;
+
+const INPUT: &str = "block w-full bg-white text-gray-800 px-3 py-2 rounded-lg border border-gray-300 focus:border-red-900 mb-2";
+
+/// What someone sees when they open an invite link: a form to sign
+/// themselves up, for the organizer to approve.
+#[component]
+pub fn SignupScreen(token: String) -> Element {
+    let server = use_context::<Option<Server>>();
+    let mut invite = use_signal(|| None::<Result<PublicInvite, ServerError>>);
+    let mut name = use_signal(String::new);
+    let mut email = use_signal(String::new);
+    let mut wishlist = use_signal(String::new);
+    let mut avoid = use_signal(String::new);
+    let mut error = use_signal(|| None::<String>);
+    let mut signed_up = use_signal(|| false);
+
+    use_hook({
+        let (server, token) = (server.clone(), token.clone());
+        move || {
+            spawn(async move {
+                let result = match server {
+                    Some(server) => server.public_invite(&token).await,
+                    None => Err(ServerError::Unreachable),
+                };
+                invite.set(Some(result));
+            });
+        }
+    });
+
+    let submit = move |_| {
+        let Some(server) = server.clone() else {
+            return;
+        };
+        let token = token.clone();
+        let form = SignupForm {
+            name: name.read().clone(),
+            email: Some(email.read().clone()),
+            wishlist: Some(wishlist.read().clone()),
+            avoid: avoid.read().lines().map(str::to_string).collect(),
+        };
+        spawn(async move {
+            match server.sign_up(&token, &form).await {
+                Ok(_) => signed_up.set(true),
+                Err(reason) => error.set(Some(reason.to_string())),
+            }
+        });
+    };
+
+    rsx! {
+        div {
+            class: "lg:w-2/3 mx-auto p-4",
+            div {
+                class: "candy-cane-striped p-2 rounded-xl",
+                div {
+                    class: "bg-white border border-red-400 rounded-xl p-4 text-gray-900",
+                    match invite.read().clone() {
+                        None => rsx! { p { class: "text-center", "Loading…" } },
+                        Some(Err(reason)) => rsx! {
+                            h1 { class: "text-2xl font-bold text-center", "We couldn't open this invite" }
+                            p { class: "mt-2 text-sm text-gray-700 text-center", "{reason}" }
+                        },
+                        Some(Ok(invite)) if !invite.open => rsx! {
+                            h1 { class: "text-2xl font-bold text-center", "{invite.name}" }
+                            p { class: "mt-2 text-sm text-gray-700 text-center", "Sign-ups for this event have closed." }
+                        },
+                        Some(Ok(_)) if *signed_up.read() => rsx! {
+                            h1 { class: "text-2xl font-bold text-center", "You're signed up!" }
+                            p { class: "mt-2 text-sm text-gray-700 text-center", "Your organizer will add you to the list and let you know who you're giving to after the draw." }
+                        },
+                        Some(Ok(invite)) => rsx! {
+                            h1 { class: "text-2xl font-bold", "Sign up for {invite.name}" }
+                            form {
+                                class: "mt-4",
+                                onsubmit: submit,
+                                input {
+                                    r#type: "text",
+                                    placeholder: "Your name",
+                                    required: true,
+                                    class: INPUT,
+                                    value: name.read().clone(),
+                                    oninput: move |event| name.set(event.value()),
+                                }
+                                input {
+                                    r#type: "email",
+                                    placeholder: "Your email address (optional)",
+                                    class: INPUT,
+                                    value: email.read().clone(),
+                                    oninput: move |event| email.set(event.value()),
+                                }
+                                textarea {
+                                    placeholder: "Gift ideas for whoever draws you (optional)",
+                                    class: "{INPUT} field-sizing-content",
+                                    value: wishlist.read().clone(),
+                                    oninput: move |event| wishlist.set(event.value()),
+                                }
+                                textarea {
+                                    placeholder: "Anyone you'd rather not give to, one per line (optional)",
+                                    class: "{INPUT} field-sizing-content",
+                                    value: avoid.read().clone(),
+                                    oninput: move |event| avoid.set(event.value()),
+                                }
+                                if let Some(reason) = error.read().clone() {
+                                    p { class: "text-sm text-red-700 mb-2", "{reason}" }
+                                }
+                                button {
+                                    r#type: "submit",
+                                    class: "bg-red-900 text-white px-3 py-2 rounded-lg border-red-700 border-2 cursor-pointer hover:bg-red-600",
+                                    "Sign me up"
+                                }
+                            }
+                        },
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,148 @@
+use crate::merge::{merge, Conflict};
+use crate::{storage, Participant, SecretSatan, UsePersistent};
+use dioxus::prelude::*;
+use futures_util::StreamExt;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+/// Keeps the open event in step with other tabs.
+///
+/// Browsers fire a `storage` event in every other tab when localStorage
+/// changes. Their changes are merged with ours against the last copy we saw
+/// from them, and anyone both tabs changed differently is listed here to be
+/// settled by hand.
+#[component]
+pub fn SyncWatcher() -> Element {
+    let mut storage = use_context::<UsePersistent<SecretSatan>>();
+    let mut state = use_context::<Signal<SecretSatan>>();
+    // The last version another tab saved, or what we loaded
+    let mut base = use_signal(|| (storage.key(), storage.get()));
+    let mut conflicts = use_signal(Vec::<Conflict>::new);
+
+    // Opening another event starts over from what's stored for it.
+    use_effect(move || {
+        let key = storage.key();
+        if base.peek().0 != key {
+            base.set((key, storage.get()));
+            conflicts.set(Vec::new());
+        }
+    });
+
+    let sync = use_coroutine(move |mut rx: UnboundedReceiver<(String, Option<String>)>| async move {
+        while let Some((key, raw)) = rx.next().await {
+            if key != storage.key() {
+                continue;
+            }
+            let theirs = match raw.map(|raw| storage::upgrade::<SecretSatan>(&raw)) {
+                Some(Ok(theirs)) => theirs,
+                // Removed, e.g. the event was deleted over there
+                None => SecretSatan::default(),
+                // Saved by a newer version of the app; leave it alone
+                Some(Err(_)) => continue,
+            };
+
+            let outcome = merge(&base.peek().1, &state.peek(), &theirs);
+            base.set((key, theirs.clone()));
+            state.set(outcome.merged.clone());
+            if !outcome.conflicts.is_empty() {
+                // Don't save until it's decided whose changes win
+                storage.sync(outcome.merged);
+            } else if outcome.merged != theirs {
+                storage.set(outcome.merged);
+            } else {
+                storage.sync(outcome.merged);
+            }
+            conflicts.set(outcome.conflicts);
+        }
+    });
+
+    use_hook(move || {
+        let tx = sync.tx();
+        let listener = Closure::<dyn Fn(web_sys::StorageEvent)>::new(move |event: web_sys::StorageEvent| {
+            // No key means the whole of localStorage was cleared
+            if let Some(key) = event.key() {
+                let _ = tx.unbounded_send((key, event.new_value()));
+            }
+        });
+        if let Some(window) = web_sys::window() {
+            let _ = window.add_event_listener_with_callback("storage", listener.as_ref().unchecked_ref());
+        }
+        // The app lives as long as the page, so the listener can too.
+        listener.forget();
+    });
+
+    let mut resolve = move |conflict: Conflict, choice: Option<Participant>| {
+        {
+            let mut list = state.write();
+            let existing = list.participants.iter().position(|p| p.name == conflict.name);
+            match (existing, choice) {
+                (Some(index), Some(participant)) => list.participants[index] = participant,
+                (None, Some(participant)) => list.add_participant(participant),
+                (Some(index), None) => {
+                    let participant = list.participants[index].clone();
+                    list.remove_participant(&participant);
+                }
+                (None, None) => {}
+            }
+        }
+        conflicts.write().retain(|c| c.name != conflict.name);
+        if conflicts.read().is_empty() {
+            storage.set(state.read().clone());
+        }
+    };
+
+    if conflicts.read().is_empty() {
+        return None;
+    }
+
+    rsx! {
+        div {
+            role: "alert",
+            class: "mb-4 bg-white border border-yellow-400 rounded-xl px-4 py-2 text-gray-900",
+            p {
+                class: "font-semibold",
+                "This list was changed in another tab at the same time as this one."
+            }
+            p {
+                class: "text-sm text-gray-700",
+                "Choose which changes to keep. Nothing is saved until you've picked for everyone."
+            }
+            ul {
+                class: "mt-2 flex flex-col gap-2 text-sm",
+                for conflict in conflicts.read().iter().cloned() {
+                    li {
+                        key: "{conflict.name}",
+                        class: "flex flex-row flex-wrap gap-4 items-center",
+                        span { class: "font-semibold", "{conflict.name}" }
+                        button {
+                            r#type: "button",
+                            class: "underline cursor-pointer",
+                            onclick: {
+                                let conflict = conflict.clone();
+                                move |_| resolve(conflict.clone(), conflict.ours.clone())
+                            },
+                            "Keep mine ({describe(conflict.ours.as_ref())})"
+                        }
+                        button {
+                            r#type: "button",
+                            class: "underline cursor-pointer",
+                            onclick: {
+                                let conflict = conflict.clone();
+                                move |_| resolve(conflict.clone(), conflict.theirs.clone())
+                            },
+                            "Use theirs ({describe(conflict.theirs.as_ref())})"
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn describe(participant: Option<&Participant>) -> String {
+    match participant {
+        None => "removed".to_string(),
+        Some(participant) if participant.excluding.is_empty() => "no exclusions".to_string(),
+        Some(participant) => format!("excluding {}", participant.excluding.join(", ")),
+    }
+}
@@ -0,0 +1,97 @@
+use crate::{History, SecretSatan, UsePersistent};
+use dioxus::prelude::*;
+use futures_util::StreamExt;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+#[derive(Clone, Copy)]
+enum Step {
+    Undo,
+    Redo,
+}
+
+/// Undo and redo buttons, also bound to Ctrl+Z, Ctrl+Shift+Z and Ctrl+Y
+/// (or ⌘ on a Mac).
+#[component]
+pub fn UndoToolbar() -> Element {
+    let mut storage = use_context::<UsePersistent<SecretSatan>>();
+    let mut history = use_context::<UsePersistent<History>>();
+    let mut state = use_context::<Signal<SecretSatan>>();
+
+    let mut step = move |step: Step| {
+        let current = state.read().clone();
+        let restored = history.update(|history| match step {
+            Step::Undo => history.undo(current),
+            Step::Redo => history.redo(current),
+        });
+        if let Some(restored) = restored {
+            state.set(restored.clone());
+            storage.set(restored);
+        }
+    };
+
+    let shortcuts = use_coroutine(move |mut rx: UnboundedReceiver<Step>| async move {
+        while let Some(requested) = rx.next().await {
+            step(requested);
+        }
+    });
+
+    use_hook(move || {
+        let tx = shortcuts.tx();
+        let listener = Closure::<dyn Fn(web_sys::KeyboardEvent)>::new(move |event: web_sys::KeyboardEvent| {
+            if !(event.ctrl_key() || event.meta_key()) || is_typing(&event) {
+                return;
+            }
+            let requested = match event.key().to_lowercase().as_str() {
+                "z" if event.shift_key() => Step::Redo,
+                "z" => Step::Undo,
+                "y" => Step::Redo,
+                _ => return,
+            };
+            event.prevent_default();
+            let _ = tx.unbounded_send(requested);
+        });
+        if let Some(window) = web_sys::window() {
+            let _ = window.add_event_listener_with_callback("keydown", listener.as_ref().unchecked_ref());
+        }
+        // The app lives as long as the page, so the listener can too.
+        listener.forget();
+    });
+
+    let can_undo = history.get().can_undo();
+    let can_redo = history.get().can_redo();
+
+    rsx! {
+        div {
+            class: "flex flex-row gap-2 text-sm text-white mb-4",
+            button {
+                r#type: "button",
+                class: "underline cursor-pointer disabled:cursor-not-allowed disabled:opacity-50",
+                title: "Undo (Ctrl+Z)",
+                disabled: !can_undo,
+                onclick: move |_| step(Step::Undo),
+                "Undo"
+            }
+            button {
+                r#type: "button",
+                class: "underline cursor-pointer disabled:cursor-not-allowed disabled:opacity-50",
+                title: "Redo (Ctrl+Shift+Z)",
+                disabled: !can_redo,
+                onclick: move |_| step(Step::Redo),
+                "Redo"
+            }
+        }
+    }
+}
+
+/// Leaves the browser's own undo alone while someone is typing in a field.
+fn is_typing(event: &web_sys::KeyboardEvent) -> bool {
+    let Some(element) = event.target().and_then(|target| target.dyn_into::<web_sys::Element>().ok()) else {
+        return false;
+    };
+    match element.tag_name().as_str() {
+        "TEXTAREA" => true,
+        "INPUT" => element.get_attribute("type").as_deref() != Some("checkbox"),
+        _ => false,
+    }
+}
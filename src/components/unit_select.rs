@@ -0,0 +1,62 @@
+use crate::{Participant, SecretSatan, UsePersistent};
+use dioxus::prelude::*;
+
+#[component]
+pub fn UnitSelect(participant: Participant) -> Element {
+    let mut storage = use_context::<UsePersistent<SecretSatan>>();
+    let mut state = use_context::<Signal<SecretSatan>>();
+
+    let partners: Vec<String> = state
+        .read()
+        .unit_of(&participant.name)
+        .map(|unit| unit.members.iter().filter(|name| **name != participant.name).cloned().collect())
+        .unwrap_or_default();
+    let others: Vec<String> = state
+        .read()
+        .participants
+        .iter()
+        .filter(|p| p.name != participant.name && !partners.contains(&p.name))
+        .map(|p| p.name.clone())
+        .collect();
+    let name = participant.name.clone();
+
+    rsx! {
+        div {
+            class: "text-sm mb-1",
+            if !partners.is_empty() {
+                p {
+                    class: "select-none",
+                    "together with ",
+                    span { class: "font-semibold", {partners.join(" & ")} }
+                    button {
+                        r#type: "button",
+                        class: "ml-2 underline cursor-pointer",
+                        onclick: move |_| {
+                            state.write().leave_unit(&name);
+                            storage.set(state.read().clone());
+                        },
+                        "leave"
+                    }
+                }
+            }
+            if !others.is_empty() {
+                select {
+                    name: format!("{}-unit", participant.name.replace(' ', "-")),
+                    class: "rounded text-sm py-0 pl-1 pr-6",
+                    onchange: move |event| {
+                        let other = event.value();
+                        if other.is_empty() {
+                            return;
+                        }
+                        state.write().join_unit(&participant.name, &other);
+                        storage.set(state.read().clone());
+                    },
+                    option { value: "", selected: true, "Gives together with…" }
+                    for other in others.iter() {
+                        option { value: other.clone(), {other.clone()} }
+                    }
+                }
+            }
+        }
+    }
+}
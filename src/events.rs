@@ -0,0 +1,173 @@
+use serde::{Deserialize, Serialize};
+
+/// The exchanges the organizer is running, and which one is open.
+///
+/// Each event's `SecretSatan` is stored separately under its `storage_key`.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct EventIndex {
+    pub events: Vec<EventMeta>,
+    pub current: String,
+    next_id: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct EventMeta {
+    pub id: String,
+    pub name: String,
+    pub archived: bool,
+}
+
+impl EventMeta {
+    /// Where this event's participants, exclusions and results are stored.
+    pub fn storage_key(&self) -> String {
+        self.id.clone()
+    }
+
+    /// Where this event's undo history is stored.
+    pub fn history_key(&self) -> String {
+        format!("{}-history", self.id)
+    }
+}
+
+impl Default for EventIndex {
+    /// A single event stored under the key the app has always used, so lists
+    /// saved before events existed show up as the first event.
+    fn default() -> EventIndex {
+        EventIndex {
+            events: vec![EventMeta {
+                id: "satan".to_string(),
+                name: "Secret Satan".to_string(),
+                archived: false,
+            }],
+            current: "satan".to_string(),
+            next_id: 2,
+        }
+    }
+}
+
+impl EventIndex {
+    pub fn get(&self, id: &str) -> Option<&EventMeta> {
+        self.events.iter().find(|event| event.id == id)
+    }
+
+    /// The open event. Falls back to the first event if `current` is stale.
+    pub fn current(&self) -> &EventMeta {
+        self.get(&self.current)
+            .or_else(|| self.events.first())
+            .expect("there is always at least one event")
+    }
+
+    /// Events that haven't been archived, in the order they were created.
+    pub fn active(&self) -> impl Iterator<Item = &EventMeta> {
+        self.events.iter().filter(|event| !event.archived)
+    }
+
+    pub fn archived(&self) -> impl Iterator<Item = &EventMeta> {
+        self.events.iter().filter(|event| event.archived)
+    }
+
+    /// Adds a new event and returns its id. Doesn't switch to it.
+    pub fn create(&mut self, name: impl ToString) -> String {
+        let id = format!("satan-{}", self.next_id);
+        self.next_id += 1;
+        self.events.push(EventMeta {
+            id: id.clone(),
+            name: name.to_string(),
+            archived: false,
+        });
+        id
+    }
+
+    pub fn switch(&mut self, id: &str) {
+        if self.get(id).is_some() {
+            self.current = id.to_string();
+        }
+    }
+
+    pub fn rename(&mut self, id: &str, name: impl ToString) {
+        if let Some(event) = self.events.iter_mut().find(|event| event.id == id) {
+            event.name = name.to_string();
+        }
+    }
+
+    /// Hides an event from the switcher. The open event moves to another
+    /// active one, and a fresh event is created if none are left.
+    pub fn archive(&mut self, id: &str) {
+        if let Some(event) = self.events.iter_mut().find(|event| event.id == id) {
+            event.archived = true;
+        }
+        if self.current == id {
+            self.open_first_active();
+        }
+    }
+
+    pub fn restore(&mut self, id: &str) {
+        if let Some(event) = self.events.iter_mut().find(|event| event.id == id) {
+            event.archived = false;
+        }
+    }
+
+    /// Forgets an event and returns it so its stored data can be removed.
+    pub fn delete(&mut self, id: &str) -> Option<EventMeta> {
+        let position = self.events.iter().position(|event| event.id == id)?;
+        let event = self.events.remove(position);
+        if self.current == id {
+            self.open_first_active();
+        }
+        Some(event)
+    }
+
+    /// Opens the first active event, creating one if there are none.
+    fn open_first_active(&mut self) {
+        let first = self.active().next().map(|event| event.id.clone());
+        self.current = match first {
+            Some(id) => id,
+            None => self.create("Secret Satan"),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_index_uses_the_original_storage_key() {
+        let index = EventIndex::default();
+        assert_eq!(index.current().storage_key(), "satan");
+    }
+
+    #[test]
+    fn created_events_get_their_own_storage_key() {
+        let mut index = EventIndex::default();
+        let office = index.create("Office");
+        let books = index.create("Book club");
+        assert_ne!(office, books);
+        assert_ne!(index.get(&office).unwrap().storage_key(), index.current().storage_key());
+
+        index.switch(&books);
+        assert_eq!(index.current().name, "Book club");
+    }
+
+    #[test]
+    fn archiving_the_open_event_moves_to_another() {
+        let mut index = EventIndex::default();
+        let office = index.create("Office");
+        index.switch(&office);
+        index.archive(&office);
+        assert_eq!(index.current, "satan");
+        assert_eq!(index.archived().count(), 1);
+
+        index.restore(&office);
+        assert_eq!(index.archived().count(), 0);
+    }
+
+    #[test]
+    fn deleting_the_last_event_leaves_a_fresh_one() {
+        let mut index = EventIndex::default();
+        let deleted = index.delete("satan").unwrap();
+        assert_eq!(deleted.id, "satan");
+        assert_eq!(index.events.len(), 1);
+        assert_ne!(index.current, "satan");
+    }
+}
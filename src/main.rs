@@ -2,10 +2,33 @@ use dioxus::prelude::*;
 use dioxus_logger::tracing::Level;
 
 use secret_satan::components::App;
+use secret_satan::server::{self, Server};
+use secret_satan::storage::{self, ServerBackend, StorageBackend, StorageError};
+use std::rc::Rc;
 
 fn main() {
     // Init logger
     dioxus_logger::init(Level::INFO)
         .expect("failed to init logger");
-    launch(App);
+    wasm_bindgen_futures::spawn_local(async {
+        // Keep data on the server when it's the one serving the app, and in
+        // the browser on the static site. Swap in another `StorageBackend`
+        // here to keep it somewhere else.
+        server::remember_organizer_key();
+        let opened = ServerBackend::open("/api").await;
+        // Without the organizer's key the server still takes sign-ups.
+        let api = match &opened {
+            Ok(_) => Some(Server::new("/api", true)),
+            Err(StorageError::Unauthorized) => Some(Server::new("/api", false)),
+            Err(_) => None,
+        };
+        let backend: Rc<dyn StorageBackend> = match opened {
+            Ok(server) => Rc::new(server),
+            Err(_) => storage::browser_backend().await,
+        };
+        LaunchBuilder::new()
+            .with_context_provider(move || Box::new(api.clone()))
+            .with_context_provider(move || Box::new(backend.clone()))
+            .launch(App);
+    });
 }
@@ -0,0 +1,146 @@
+//! Paper cards for people who'd rather not use a phone.
+//!
+//! The cards are a standalone HTML page laid out for printing, so they look
+//! the same whatever the app's styles are doing.
+use crate::reveal::reveals;
+use crate::SecretSatan;
+
+/// How each card is laid out on the page.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum CardStyle {
+    /// The giver's name and result on one card.
+    #[default]
+    Card,
+    /// A card without the giver's name to go inside an envelope, and a label
+    /// with just their name for the outside.
+    Envelope,
+}
+
+/// A printable page with one card per giver in the latest results.
+pub fn cards_html(event: &str, list: &SecretSatan, style: CardStyle) -> String {
+    let mut details = Vec::new();
+    if let Some(budget) = list.budget.as_deref().filter(|budget| !budget.trim().is_empty()) {
+        details.push(format!("Budget: {}", escape(budget)));
+    }
+    if let Some(date) = list.date.as_deref().filter(|date| !date.trim().is_empty()) {
+        details.push(format!("Date: {}", escape(date)));
+    }
+    let details = details.join(" &middot; ");
+    let title = escape(event);
+
+    let mut cards = String::new();
+    for reveal in reveals(event, &list.results) {
+        let giver = escape(&reveal.giver);
+        let mut receiver = escape(&reveal.receiver);
+        if !reveal.sharing_with.is_empty() {
+            let others: Vec<String> = reveal.sharing_with.iter().map(|other| escape(other)).collect();
+            receiver.push_str(&format!("<small>together with {}</small>", others.join(", ")));
+        }
+        let inside = format!(
+            "<p class=\"event\">{title}</p><p>You are the Secret Santa for:</p><p class=\"receiver\">{receiver}</p><p class=\"details\">{details}</p>"
+        );
+        cards.push_str(&match style {
+            CardStyle::Card => format!("<section class=\"card\"><p class=\"giver\">{giver}</p>{inside}</section>"),
+            CardStyle::Envelope => format!(
+                "<section class=\"envelope\"><div class=\"label\"><p class=\"giver\">{giver}</p></div><div class=\"card\">{inside}</div></section>"
+            ),
+        });
+    }
+
+    format!(
+        "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>{title}</title><style>{STYLES}</style></head><body>{cards}</body></html>"
+    )
+}
+
+/// A printable page of QR codes, each labelled with whose it is. `codes`
+/// pairs a giver with their code as an SVG image.
+pub fn qr_sheet_html(event: &str, codes: &[(String, String)]) -> String {
+    let title = escape(event);
+    let mut cells = String::new();
+    for (giver, image) in codes {
+        cells.push_str(&format!(
+            "<section class=\"code\"><p class=\"giver\">{}</p>{image}<p class=\"details\">Scan to see who you're giving to</p></section>",
+            escape(giver)
+        ));
+    }
+    format!(
+        "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>{title}</title><style>{STYLES}</style></head><body class=\"codes\">{cells}</body></html>"
+    )
+}
+
+/// Four cards to an A4 or Letter page, with dashed lines to cut along.
+const STYLES: &str = "
+@page { margin: 10mm; }
+body { margin: 0; font-family: Georgia, serif; display: grid; grid-template-columns: 1fr 1fr; }
+.card { break-inside: avoid; height: 128mm; box-sizing: border-box; border: 1px dashed #999; padding: 8mm; text-align: center; display: flex; flex-direction: column; justify-content: center; }
+.card p { margin: 2mm 0; }
+.giver { font-size: 14pt; font-weight: bold; }
+.event { font-size: 11pt; color: #444; }
+.receiver { font-size: 22pt; font-weight: bold; }
+.receiver small { display: block; font-size: 10pt; font-weight: normal; }
+.details { font-size: 10pt; color: #444; }
+.envelope { break-inside: avoid; }
+.envelope .card { height: 100mm; }
+.codes { grid-template-columns: 1fr 1fr 1fr; }
+.code { break-inside: avoid; border: 1px dashed #999; padding: 4mm; text-align: center; }
+.code svg { width: 45mm; height: 45mm; }
+.label { height: 28mm; box-sizing: border-box; border: 1px dashed #999; border-bottom: none; display: flex; align-items: center; justify-content: center; }
+";
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Assignment;
+
+    fn list() -> SecretSatan {
+        let mut list = SecretSatan::new();
+        list.set_results(vec![
+            Assignment {
+                givers: vec!["Alice".to_string()],
+                receivers: vec!["Bob <3".to_string()],
+            },
+            Assignment {
+                givers: vec!["Bob <3".to_string()],
+                receivers: vec!["Alice".to_string()],
+            },
+        ]);
+        list.budget = Some("$25".to_string());
+        list
+    }
+
+    #[test]
+    fn every_giver_gets_a_card() {
+        let html = cards_html("Office", &list(), CardStyle::Card);
+        assert_eq!(html.matches("class=\"card\"").count(), 2);
+        assert!(html.contains("Budget: $25"));
+        assert!(!html.contains("Date:"));
+        assert!(html.contains("Bob &lt;3"));
+        assert!(!html.contains("Bob <3"));
+    }
+
+    #[test]
+    fn qr_sheets_label_every_code() {
+        let codes = vec![("Alice".to_string(), "<svg></svg>".to_string()), ("Bob".to_string(), "<svg></svg>".to_string())];
+        let html = qr_sheet_html("Office", &codes);
+        assert_eq!(html.matches("class=\"code\"").count(), 2);
+        assert!(html.contains("<p class=\"giver\">Bob</p><svg>"));
+    }
+
+    #[test]
+    fn envelope_cards_leave_the_name_for_the_outside() {
+        let html = cards_html("Office", &list(), CardStyle::Envelope);
+        let envelope = html.split("<section class=\"envelope\">").nth(1).unwrap();
+        let envelope = envelope.split("</section>").next().unwrap();
+        let (label, card) = envelope.split_once("<div class=\"card\">").unwrap();
+        assert!(label.contains("Alice"));
+        assert!(!label.contains("Secret Santa"));
+        assert!(!card.contains("Alice"));
+    }
+}
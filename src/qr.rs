@@ -0,0 +1,29 @@
+//! QR codes for reveal links, so people can scan their own result at a party.
+use qrcode::render::svg;
+use qrcode::{EcLevel, QrCode};
+
+/// `text` as a QR code in an SVG image, ready to put straight into a page.
+/// Fails if it's too long to encode.
+pub fn svg(text: &str) -> Result<String, String> {
+    let code = QrCode::with_error_correction_level(text, EcLevel::M).map_err(|error| error.to_string())?;
+    let image = code.render::<svg::Color>().min_dimensions(200, 200).quiet_zone(true).build();
+    // Drop the XML declaration, which doesn't belong inside HTML.
+    Ok(image.find("<svg").map_or(image.clone(), |start| image[start..].to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn links_become_svg_images() {
+        let image = svg("https://example.com/#reveal=abc.def").unwrap();
+        assert!(image.starts_with("<svg"));
+        assert!(image.contains("<path"));
+    }
+
+    #[test]
+    fn text_too_long_for_a_code_is_refused() {
+        assert!(svg(&"x".repeat(10_000)).is_err());
+    }
+}
@@ -0,0 +1,108 @@
+//! Links that each show one person who they're giving to, so the organizer can
+//! hand results out without seeing them.
+//!
+//! A link's fragment holds that person's result, encrypted with a key made
+//! just for that link, followed by the key itself. Neither ever reaches a
+//! server, and nothing else in the app can open it.
+pub use secret_satan_engine::reveal::{reveals, Reveal};
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use rand::{CryptoRng, RngCore};
+use std::fmt;
+
+/// What comes before the encrypted result in the fragment.
+pub const FRAGMENT_PREFIX: &str = "reveal=";
+
+const NONCE_LEN: usize = 12;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RevealError {
+    Malformed,
+    WrongKey,
+}
+
+impl fmt::Display for RevealError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RevealError::Malformed => write!(f, "This link is incomplete. It may have been cut off when it was copied."),
+            RevealError::WrongKey => write!(f, "This link has been changed and can't be opened."),
+        }
+    }
+}
+
+impl std::error::Error for RevealError {}
+
+/// The URL fragment, without the `#`, for one person's link.
+pub fn seal<R: RngCore + CryptoRng>(reveal: &Reveal, rng: &mut R) -> String {
+    let mut key = [0u8; 32];
+    let mut nonce = [0u8; NONCE_LEN];
+    rng.fill_bytes(&mut key);
+    rng.fill_bytes(&mut nonce);
+
+    let plaintext = serde_json::to_vec(reveal).unwrap_or_default();
+    let ciphertext = ChaCha20Poly1305::new(Key::from_slice(&key))
+        .encrypt(Nonce::from_slice(&nonce), plaintext.as_slice())
+        .expect("encrypting a short message doesn't fail");
+
+    let mut sealed = nonce.to_vec();
+    sealed.extend(ciphertext);
+    format!("{FRAGMENT_PREFIX}{}.{}", URL_SAFE_NO_PAD.encode(sealed), URL_SAFE_NO_PAD.encode(key))
+}
+
+/// Opens a link's fragment. Returns `Ok(None)` when the fragment isn't a
+/// reveal link at all.
+pub fn open(fragment: &str) -> Result<Option<Reveal>, RevealError> {
+    let Some(sealed) = fragment.trim_start_matches('#').strip_prefix(FRAGMENT_PREFIX) else {
+        return Ok(None);
+    };
+    let (sealed, key) = sealed.split_once('.').ok_or(RevealError::Malformed)?;
+    let sealed = URL_SAFE_NO_PAD.decode(sealed).map_err(|_| RevealError::Malformed)?;
+    let key = URL_SAFE_NO_PAD.decode(key).map_err(|_| RevealError::Malformed)?;
+    if key.len() != 32 || sealed.len() <= NONCE_LEN {
+        return Err(RevealError::Malformed);
+    }
+
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+    let plaintext = ChaCha20Poly1305::new(Key::from_slice(&key))
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| RevealError::WrongKey)?;
+    serde_json::from_slice(&plaintext).map(Some).map_err(|_| RevealError::Malformed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Assignment;
+
+    fn assignment(givers: &[&str], receiver: &str) -> Assignment {
+        Assignment {
+            givers: givers.iter().map(|giver| giver.to_string()).collect(),
+            receivers: vec![receiver.to_string()],
+        }
+    }
+
+    #[test]
+    fn sealed_links_open_to_the_same_result() {
+        let reveal = reveals("Office", &[assignment(&["Alice"], "Bob")]).remove(0);
+        let fragment = seal(&reveal, &mut rand::thread_rng());
+        // The result isn't readable from the link itself.
+        assert!(!fragment.contains("Bob"));
+        assert_eq!(open(&format!("#{fragment}")), Ok(Some(reveal)));
+    }
+
+    #[test]
+    fn tampered_links_are_refused() {
+        let reveal = reveals("Office", &[assignment(&["Alice"], "Bob")]).remove(0);
+        let fragment = seal(&reveal, &mut rand::thread_rng());
+        let other = seal(&reveal, &mut rand::thread_rng());
+        let (sealed, _) = fragment.split_once('.').unwrap();
+        let (_, other_key) = other.split_once('.').unwrap();
+
+        assert_eq!(open(&format!("{sealed}.{other_key}")), Err(RevealError::WrongKey));
+        assert_eq!(open(sealed), Err(RevealError::Malformed));
+        assert_eq!(open("#share=abc"), Ok(None));
+    }
+}
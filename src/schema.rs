@@ -0,0 +1,15 @@
+//! Schema versions for what only the web app stores, and the migrations that
+//! bring older data up to date. The draw's own types are versioned in the
+//! engine crate.
+use crate::storage::{Migration, Versioned};
+use crate::EventIndex;
+use serde_json::Value;
+
+impl Versioned for EventIndex {
+    const MIGRATIONS: &'static [Migration] = &[event_index_v1];
+}
+
+/// The event index arrived with versioning, so there's nothing to change.
+fn event_index_v1(data: Value) -> Result<Value, String> {
+    Ok(data)
+}
@@ -0,0 +1,339 @@
+//! Talking to a `secret-satan-server`, for the parts of the app that only
+//! work when one is serving it.
+//!
+//! The app provides an `Option<Server>` in context, which is `None` on the
+//! static site. Requests carry the organizer's key once their link has been
+//! opened in this browser; the invite and contribution routes don't need it.
+use crate::delivery::{Delivery, NotificationOptions, NotificationSettings};
+use crate::entropy::{Phase, Round, RoundStatus, Seat};
+use crate::signup::{Invite, Signup, SignupForm, SignupStatus};
+use crate::storage::{LocalStorageBackend, StorageBackend, StorageError};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+
+/// What comes before the token in an invite link's fragment.
+pub const INVITE_PREFIX: &str = "invite=";
+
+/// What comes before the token in someone's contribution link's fragment.
+pub const CONTRIBUTE_PREFIX: &str = "contribute=";
+
+/// What comes before the key in the organizer link the server prints.
+pub const ORGANIZER_PREFIX: &str = "organizer=";
+
+/// Where the organizer's key is kept in this browser.
+const ORGANIZER_KEY: &str = "organizer-key";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ServerError {
+    Unreachable,
+    /// The server turned the request down, saying why.
+    Refused(String),
+}
+
+impl fmt::Display for ServerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ServerError::Unreachable => write!(f, "The server couldn't be reached. Check your connection and try again."),
+            ServerError::Refused(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl std::error::Error for ServerError {}
+
+impl From<StorageError> for ServerError {
+    fn from(_: StorageError) -> ServerError {
+        ServerError::Unreachable
+    }
+}
+
+/// An invite as someone signing up sees it.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct PublicInvite {
+    pub name: String,
+    pub open: bool,
+}
+
+#[derive(Serialize)]
+struct InviteSettings<'a> {
+    name: &'a str,
+    open: bool,
+}
+
+#[derive(Serialize)]
+struct DeliveryRequest<'a> {
+    name: &'a str,
+    template: Option<&'a str>,
+    giver: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct Pledge<'a> {
+    commitment: &'a str,
+}
+
+#[derive(Deserialize)]
+struct Pledged {
+    secret: String,
+}
+
+#[derive(Serialize)]
+struct Reveal<'a> {
+    value: &'a str,
+    secret: &'a str,
+}
+
+#[derive(Serialize)]
+struct Announcement<'a> {
+    name: &'a str,
+    message: &'a str,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Server {
+    base: String,
+    organizer: bool,
+}
+
+impl Server {
+    /// The server's API under `base`, e.g. `/api`. `organizer` is whether
+    /// this browser has the organizer's key, or can only use invite links.
+    pub fn new(base: impl ToString, organizer: bool) -> Server {
+        Server {
+            base: base.to_string(),
+            organizer,
+        }
+    }
+
+    pub fn is_organizer(&self) -> bool {
+        self.organizer
+    }
+
+    fn url(&self, path: &[&str]) -> String {
+        let path: Vec<String> = path
+            .iter()
+            .map(|part| String::from(js_sys::encode_uri_component(part)))
+            .collect();
+        format!("{}/{}", self.base, path.join("/"))
+    }
+
+    /// The invite link for the event stored under `event`, if it has one.
+    pub async fn invite(&self, event: &str) -> Result<Option<Invite>, ServerError> {
+        let response = fetch("GET", &self.url(&["events", event, "invite"]), None).await?;
+        if response.status() == 404 {
+            return Ok(None);
+        }
+        parse(response).await.map(Some)
+    }
+
+    /// Names and opens or closes an event's invite, creating it the first time.
+    pub async fn save_invite(&self, event: &str, name: &str, open: bool) -> Result<Invite, ServerError> {
+        let body = serde_json::to_string(&InviteSettings { name, open }).unwrap_or_default();
+        request(&self.url(&["events", event, "invite"]), "PUT", Some(&body)).await
+    }
+
+    pub async fn signups(&self, event: &str) -> Result<Vec<Signup>, ServerError> {
+        request(&self.url(&["events", event, "signups"]), "GET", None).await
+    }
+
+    pub async fn set_signup_status(&self, id: i64, status: SignupStatus) -> Result<(), ServerError> {
+        let body = serde_json::to_string(&status).unwrap_or_default();
+        let response = fetch("PUT", &self.url(&["signups", &id.to_string(), "status"]), Some(&body)).await?;
+        refused(response).await
+    }
+
+    pub async fn public_invite(&self, token: &str) -> Result<PublicInvite, ServerError> {
+        request(&self.url(&["invites", token]), "GET", None).await
+    }
+
+    pub async fn sign_up(&self, token: &str, form: &SignupForm) -> Result<Signup, ServerError> {
+        let body = serde_json::to_string(form).unwrap_or_default();
+        request(&self.url(&["invites", token, "signups"]), "POST", Some(&body)).await
+    }
+
+    /// Where the event sends results and reminders, and what the server can
+    /// send through.
+    pub async fn notifications(&self, event: &str) -> Result<NotificationOptions, ServerError> {
+        request(&self.url(&["events", event, "notifications"]), "GET", None).await
+    }
+
+    pub async fn save_notifications(&self, event: &str, settings: &NotificationSettings) -> Result<(), ServerError> {
+        let body = serde_json::to_string(settings).unwrap_or_default();
+        let response = fetch("PUT", &self.url(&["events", event, "notifications"]), Some(&body)).await?;
+        refused(response).await
+    }
+
+    /// Sends `message` to everyone, returning anything that went wrong.
+    pub async fn announce(&self, event: &str, name: &str, message: &str) -> Result<Vec<String>, ServerError> {
+        let body = serde_json::to_string(&Announcement { name, message }).unwrap_or_default();
+        request(&self.url(&["events", event, "announcements"]), "POST", Some(&body)).await
+    }
+
+    /// How sending each giver their result has gone.
+    pub async fn deliveries(&self, event: &str) -> Result<Vec<Delivery>, ServerError> {
+        request(&self.url(&["events", event, "deliveries"]), "GET", None).await
+    }
+
+    /// Sends `giver` their result again, or everyone who hasn't had theirs
+    /// yet. `template` replaces the default message.
+    pub async fn deliver(
+        &self,
+        event: &str,
+        name: &str,
+        template: Option<&str>,
+        giver: Option<&str>,
+    ) -> Result<Vec<Delivery>, ServerError> {
+        let body = serde_json::to_string(&DeliveryRequest { name, template, giver }).unwrap_or_default();
+        request(&self.url(&["events", event, "deliveries"]), "POST", Some(&body)).await
+    }
+
+    /// The event's commit-then-reveal round, if one has been started.
+    pub async fn entropy_round(&self, event: &str) -> Result<Option<Round>, ServerError> {
+        let response = fetch("GET", &self.url(&["events", event, "entropy"]), None).await?;
+        if response.status() == 404 {
+            return Ok(None);
+        }
+        parse(response).await.map(Some)
+    }
+
+    /// Moves the event's round on to `phase`. Committing starts it afresh.
+    pub async fn set_entropy_phase(&self, event: &str, phase: Phase) -> Result<Round, ServerError> {
+        let body = serde_json::to_string(&phase).unwrap_or_default();
+        request(&self.url(&["events", event, "entropy"]), "PUT", Some(&body)).await
+    }
+
+    /// Where the round stands for the holder of a contribution link, if
+    /// it's one of a current round's.
+    pub async fn contribution_status(&self, token: &str) -> Result<Option<RoundStatus>, ServerError> {
+        let response = fetch("GET", &self.url(&["contributions", token]), None).await?;
+        if response.status() == 404 {
+            return Ok(None);
+        }
+        parse(response).await.map(Some)
+    }
+
+    /// Sends the commitment, returning the secret needed to reveal it.
+    pub async fn contribute(&self, token: &str, commitment: &str) -> Result<String, ServerError> {
+        let body = serde_json::to_string(&Pledge { commitment }).unwrap_or_default();
+        let pledged: Pledged = request(&self.url(&["contributions", token, "commitments"]), "POST", Some(&body)).await?;
+        Ok(pledged.secret)
+    }
+
+    pub async fn reveal_contribution(&self, token: &str, value: &str, secret: &str) -> Result<(), ServerError> {
+        let body = serde_json::to_string(&Reveal { value, secret }).unwrap_or_default();
+        let response = fetch("POST", &self.url(&["contributions", token, "reveals"]), Some(&body)).await?;
+        refused(response).await
+    }
+}
+
+/// The URL fragment, without the `#`, for an invite link.
+pub fn invite_fragment(invite: &Invite) -> String {
+    format!("{INVITE_PREFIX}{}", invite.token)
+}
+
+/// The token in an invite link's fragment, if that's what it is.
+pub fn invite_token(fragment: &str) -> Option<String> {
+    let token = fragment.trim_start_matches('#').strip_prefix(INVITE_PREFIX)?;
+    (!token.is_empty()).then(|| token.to_string())
+}
+
+/// The URL fragment, without the `#`, for someone's contribution link.
+pub fn contribution_fragment(seat: &Seat) -> String {
+    format!("{CONTRIBUTE_PREFIX}{}", seat.token)
+}
+
+/// The token in a contribution link's fragment, if that's what it is.
+pub fn contribution_token(fragment: &str) -> Option<String> {
+    let token = fragment.trim_start_matches('#').strip_prefix(CONTRIBUTE_PREFIX)?;
+    (!token.is_empty()).then(|| token.to_string())
+}
+
+/// The key in the organizer link's fragment, if that's what it is.
+pub fn organizer_key(fragment: &str) -> Option<String> {
+    let key = fragment.trim_start_matches('#').strip_prefix(ORGANIZER_PREFIX)?;
+    (!key.is_empty()).then(|| key.to_string())
+}
+
+/// Keeps `key` so every request from this browser is the organizer's.
+pub fn save_organizer_key(key: &str) -> Result<(), StorageError> {
+    LocalStorageBackend.set(ORGANIZER_KEY, key.trim())
+}
+
+/// Keeps the key from the organizer link, if that's what the page was opened
+/// with, and takes it out of the address bar so it isn't shared by accident.
+pub fn remember_organizer_key() {
+    let Some(location) = web_sys::window().map(|window| window.location()) else {
+        return;
+    };
+    if let Some(key) = organizer_key(&location.hash().unwrap_or_default()) {
+        if save_organizer_key(&key).is_ok() {
+            let _ = location.set_hash("");
+        }
+    }
+}
+
+async fn request<T: DeserializeOwned>(url: &str, method: &str, body: Option<&str>) -> Result<T, ServerError> {
+    parse(fetch(method, url, body).await?).await
+}
+
+async fn parse<T: DeserializeOwned>(response: web_sys::Response) -> Result<T, ServerError> {
+    let text = text(&response).await?;
+    if !response.ok() {
+        return Err(ServerError::Refused(text));
+    }
+    serde_json::from_str(&text).map_err(|_| ServerError::Unreachable)
+}
+
+async fn refused(response: web_sys::Response) -> Result<(), ServerError> {
+    match response.ok() {
+        true => Ok(()),
+        false => Err(ServerError::Refused(text(&response).await?)),
+    }
+}
+
+async fn text(response: &web_sys::Response) -> Result<String, StorageError> {
+    Ok(JsFuture::from(response.text()?).await?.as_string().unwrap_or_default())
+}
+
+/// Sends a request with an optional JSON body, as the organizer if this
+/// browser has their key.
+pub(crate) async fn fetch(method: &str, url: &str, body: Option<&str>) -> Result<web_sys::Response, StorageError> {
+    let window = web_sys::window().ok_or(StorageError::Unavailable)?;
+    let init = web_sys::RequestInit::new();
+    init.set_method(method);
+    let headers = web_sys::Headers::new()?;
+    if let Some(key) = LocalStorageBackend.get(ORGANIZER_KEY) {
+        headers.set("Authorization", &format!("Bearer {key}"))?;
+    }
+    if let Some(body) = body {
+        headers.set("Content-Type", "application/json")?;
+        init.set_body(&JsValue::from_str(body));
+    }
+    init.set_headers(&headers);
+    let response = JsFuture::from(window.fetch_with_str_and_init(url, &init)).await?;
+    Ok(response.dyn_into()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invite_tokens_come_from_the_fragment() {
+        assert_eq!(invite_token("#invite=abc123"), Some("abc123".to_string()));
+        assert_eq!(invite_token("invite="), None);
+        assert_eq!(invite_token("#reveal=abc"), None);
+        assert_eq!(contribution_token("#contribute=abc123"), Some("abc123".to_string()));
+        assert_eq!(contribution_token("#invite=abc123"), None);
+    }
+
+    #[test]
+    fn organizer_keys_come_from_the_fragment() {
+        assert_eq!(organizer_key("#organizer=abc123"), Some("abc123".to_string()));
+        assert_eq!(organizer_key("#organizer="), None);
+        assert_eq!(organizer_key("#invite=abc123"), None);
+    }
+}
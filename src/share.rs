@@ -0,0 +1,111 @@
+//! Putting a whole event into a link, so it can be handed to someone else
+//! without a server.
+//!
+//! The event is exported the same way as a `.json` file, deflated and
+//! base64url-encoded into the URL fragment, which browsers never send to the
+//! server hosting the app.
+use crate::transfer::{event_file, import_event, ImportedEvent};
+use crate::SecretSatan;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use miniz_oxide::deflate::compress_to_vec;
+use miniz_oxide::inflate::decompress_to_vec_with_limit;
+use std::fmt;
+
+/// What comes before the encoded event in the fragment.
+pub const FRAGMENT_PREFIX: &str = "share=";
+
+/// Longest fragment we'll make. Browsers cope with far more, but email
+/// clients and chat apps cut long links short.
+pub const MAX_FRAGMENT_LEN: usize = 4_000;
+
+/// The most a shared event may inflate to, so a hostile link can't exhaust
+/// memory.
+const MAX_EVENT_BYTES: usize = 1 << 20;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ShareError {
+    TooLarge { length: usize, limit: usize },
+    Invalid(String),
+}
+
+impl fmt::Display for ShareError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ShareError::TooLarge { length, limit } => write!(
+                f,
+                "This list is too big to fit in a link ({length} characters, the most is {limit}). Export it as a file instead."
+            ),
+            ShareError::Invalid(reason) => write!(f, "This link doesn't contain a list that can be opened. {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for ShareError {}
+
+/// The URL fragment, without the `#`, that opens this event.
+pub fn encode(name: &str, list: &SecretSatan) -> Result<String, ShareError> {
+    let json = serde_json::to_string(&event_file(name, list)).map_err(|error| ShareError::Invalid(error.to_string()))?;
+    let fragment = format!("{FRAGMENT_PREFIX}{}", URL_SAFE_NO_PAD.encode(compress_to_vec(json.as_bytes(), 9)));
+    if fragment.len() > MAX_FRAGMENT_LEN {
+        return Err(ShareError::TooLarge {
+            length: fragment.len(),
+            limit: MAX_FRAGMENT_LEN,
+        });
+    }
+    Ok(fragment)
+}
+
+/// Reads the event out of a URL fragment. Returns `Ok(None)` when the
+/// fragment isn't a shared event at all.
+pub fn decode(fragment: &str) -> Result<Option<ImportedEvent>, ShareError> {
+    let Some(encoded) = fragment.trim_start_matches('#').strip_prefix(FRAGMENT_PREFIX) else {
+        return Ok(None);
+    };
+    let invalid = |reason: &str| ShareError::Invalid(reason.to_string());
+    let compressed = URL_SAFE_NO_PAD
+        .decode(encoded.trim_end_matches('='))
+        .map_err(|_| invalid("It may have been cut off when it was copied."))?;
+    let json = decompress_to_vec_with_limit(&compressed, MAX_EVENT_BYTES)
+        .map_err(|_| invalid("It may have been cut off when it was copied."))?;
+    let json = String::from_utf8(json).map_err(|_| invalid("It isn't text."))?;
+    import_event(&json).map(Some).map_err(ShareError::Invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Participant;
+
+    #[test]
+    fn shared_events_open_unchanged() {
+        let mut list = SecretSatan::new();
+        for name in ["Alice", "Bob", "Charlie"] {
+            list.add_participant(Participant::new(name.to_string()));
+        }
+        list.participants[0].excluding.push("Bob".to_string());
+
+        let fragment = encode("Office", &list).unwrap();
+        assert!(fragment.chars().all(|c| c.is_ascii_alphanumeric() || "=-_".contains(c)));
+        let imported = decode(&format!("#{fragment}")).unwrap().unwrap();
+        assert_eq!(imported.name.as_deref(), Some("Office"));
+        assert_eq!(imported.list, list);
+    }
+
+    #[test]
+    fn other_fragments_are_ignored() {
+        assert_eq!(decode(""), Ok(None));
+        assert_eq!(decode("#top"), Ok(None));
+        assert!(decode("#share=not-really").is_err());
+    }
+
+    #[test]
+    fn huge_lists_are_refused() {
+        let mut list = SecretSatan::new();
+        for n in 0..2_000 {
+            // Random-looking names so compression can't hide the size.
+            list.add_participant(Participant::new(format!("{:x}", (n as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15))));
+        }
+        assert!(matches!(encode("Office", &list), Err(ShareError::TooLarge { .. })));
+    }
+}
@@ -0,0 +1,507 @@
+//! Places `use_persistent` can keep its data.
+//!
+//! The hook looks for an `Rc<dyn StorageBackend>` in context and falls back
+//! to the browser's localStorage when there isn't one. Values are wrapped in
+//! an envelope recording their schema version, so older data can be migrated
+//! forward when it's read back.
+pub use secret_satan_engine::version::{upgrade, Migration, Versioned};
+
+use crate::server::fetch;
+use futures_channel::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use futures_util::StreamExt;
+use secret_satan_engine::version;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{IdbDatabase, IdbOpenDbRequest, IdbRequest, IdbTransaction, IdbTransactionMode};
+
+/// How a write that finished in the background went, with the key it was for.
+pub type WriteResult = (String, Result<(), StorageError>);
+
+/// A key-value store of serialized values.
+pub trait StorageBackend {
+    fn get(&self, key: &str) -> Option<String>;
+    fn set(&self, key: &str, value: &str) -> Result<(), StorageError>;
+    fn remove(&self, key: &str);
+
+    /// For backends that write in the background, how each write went once
+    /// it's done, in the order they were made. Others report from `set`.
+    fn write_results(&self) -> Option<UnboundedReceiver<WriteResult>> {
+        None
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StorageError {
+    /// There's no storage to write to, e.g. it's been turned off.
+    Unavailable,
+    /// The storage is full.
+    QuotaExceeded,
+    /// The server only lets the organizer in.
+    Unauthorized,
+    /// Anything else the backend reported.
+    Other(String),
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StorageError::Unavailable => write!(f, "Storage isn't available in this browser."),
+            StorageError::QuotaExceeded => write!(f, "Storage is full."),
+            StorageError::Unauthorized => write!(f, "Only the organizer can change the list. Open the organizer link again."),
+            StorageError::Other(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl std::error::Error for StorageError {}
+
+impl From<JsValue> for StorageError {
+    fn from(value: JsValue) -> StorageError {
+        match value.dyn_ref::<web_sys::DomException>() {
+            Some(exception) if exception.name() == "QuotaExceededError" => StorageError::QuotaExceeded,
+            Some(exception) if exception.name() == "SecurityError" => StorageError::Unavailable,
+            Some(exception) => StorageError::Other(exception.message()),
+            None => StorageError::Other(format!("{value:?}")),
+        }
+    }
+}
+
+/// The backend `use_persistent` uses when none is provided.
+pub fn default_backend() -> Rc<dyn StorageBackend> {
+    Rc::new(LocalStorageBackend)
+}
+
+/// Where the browser's choice of backend is remembered.
+const BACKEND_CHOICE: &str = "storage-backend";
+
+/// Where the app keeps its data without a server. That's localStorage, which
+/// other tabs hear about, unless the page was opened with `?storage=indexeddb`
+/// for IndexedDB's far bigger quota. The choice is remembered until it's
+/// changed with `?storage=local`, and falls back to localStorage where
+/// IndexedDB can't be opened, as in some private browsing modes. The first
+/// time IndexedDB is used, everything already in localStorage is copied over,
+/// so no lists are left behind.
+pub async fn browser_backend() -> Rc<dyn StorageBackend> {
+    let search = web_sys::window().and_then(|window| window.location().search().ok()).unwrap_or_default();
+    if let Some(choice) = backend_choice(&search) {
+        let _ = LocalStorageBackend.set(BACKEND_CHOICE, &choice);
+    }
+    if LocalStorageBackend.get(BACKEND_CHOICE).as_deref() != Some("indexeddb") {
+        return default_backend();
+    }
+    match IndexedDbBackend::open(INDEXED_DB_NAME).await {
+        Ok(backend) => {
+            backend.adopt(LocalStorageBackend::entries());
+            Rc::new(backend)
+        }
+        Err(_) => default_backend(),
+    }
+}
+
+/// The backend asked for in a query string like `?storage=indexeddb`.
+fn backend_choice(search: &str) -> Option<String> {
+    search
+        .trim_start_matches('?')
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("storage="))
+        .filter(|choice| ["indexeddb", "local"].contains(choice))
+        .map(str::to_string)
+}
+
+
+/// Stored data that couldn't be read back, kept as-is so it isn't lost.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Unreadable {
+    pub key: String,
+    pub raw: String,
+    pub error: String,
+}
+
+/// Reads the value under `key`, migrating it to the current version.
+///
+/// Returns `Ok(None)` when nothing is stored, and the raw data when it can't
+/// be parsed or migrated.
+pub fn load<T: Versioned>(backend: &dyn StorageBackend, key: &str) -> Result<Option<T>, Unreadable> {
+    let Some(raw) = backend.get(key) else {
+        return Ok(None);
+    };
+    upgrade(&raw).map(Some).map_err(|error| Unreadable {
+        key: key.to_string(),
+        raw,
+        error,
+    })
+}
+
+/// Serializes `value` at the current version and writes it under `key`.
+pub fn save<T: Versioned>(backend: &dyn StorageBackend, key: &str, value: &T) -> Result<(), StorageError> {
+    let value = version::to_string(value).map_err(|error| StorageError::Other(error.to_string()))?;
+    backend.set(key, &value)
+}
+
+/// The browser's localStorage.
+///
+/// Some private browsing modes turn localStorage off entirely, so it's looked
+/// up on every call and treated as empty when it's missing.
+pub struct LocalStorageBackend;
+
+impl LocalStorageBackend {
+    fn storage() -> Result<web_sys::Storage, StorageError> {
+        web_sys::window()
+            .ok_or(StorageError::Unavailable)?
+            .local_storage()?
+            .ok_or(StorageError::Unavailable)
+    }
+
+    /// Everything stored, for moving it somewhere else.
+    fn entries() -> Vec<(String, String)> {
+        let Ok(storage) = Self::storage() else {
+            return Vec::new();
+        };
+        (0..storage.length().unwrap_or(0))
+            .filter_map(|index| {
+                let key = storage.key(index).ok().flatten()?;
+                let value = storage.get_item(&key).ok().flatten()?;
+                Some((key, value))
+            })
+            .collect()
+    }
+}
+
+impl StorageBackend for LocalStorageBackend {
+    fn get(&self, key: &str) -> Option<String> {
+        Self::storage().ok()?.get_item(key).ok().flatten()
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<(), StorageError> {
+        Ok(Self::storage()?.set_item(key, value)?)
+    }
+
+    fn remove(&self, key: &str) {
+        if let Ok(storage) = Self::storage() {
+            let _ = storage.remove_item(key);
+        }
+    }
+}
+
+/// Keeps everything in memory. Nothing survives a reload, which makes it
+/// handy for tests.
+#[derive(Default)]
+pub struct MemoryBackend {
+    entries: RefCell<HashMap<String, String>>,
+}
+
+impl StorageBackend for MemoryBackend {
+    fn get(&self, key: &str) -> Option<String> {
+        self.entries.borrow().get(key).cloned()
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<(), StorageError> {
+        self.entries.borrow_mut().insert(key.to_string(), value.to_string());
+        Ok(())
+    }
+
+    fn remove(&self, key: &str) {
+        self.entries.borrow_mut().remove(key);
+    }
+}
+
+const INDEXED_DB_NAME: &str = "secret-satan";
+const INDEXED_DB_STORE: &str = "entries";
+
+/// The browser's IndexedDB, which has far more room than localStorage.
+///
+/// IndexedDB is asynchronous, so everything is read into memory by `open`
+/// and writes are sent off in the background. Each is only done once its
+/// transaction commits, and how it went is sent to everyone watching
+/// `write_results`, so a full disk isn't mistaken for a save.
+pub struct IndexedDbBackend {
+    db: IdbDatabase,
+    cache: RefCell<HashMap<String, String>>,
+    watchers: Rc<RefCell<Vec<UnboundedSender<WriteResult>>>>,
+}
+
+impl IndexedDbBackend {
+    pub async fn open(name: &str) -> Result<IndexedDbBackend, StorageError> {
+        let factory = web_sys::window()
+            .and_then(|window| window.indexed_db().ok().flatten())
+            .ok_or(StorageError::Unavailable)?;
+        let request = factory.open_with_u32(name, 1)?;
+        let upgrade = Closure::<dyn FnMut(web_sys::Event)>::new(|event: web_sys::Event| {
+            let Some(request) = event.target().and_then(|target| target.dyn_into::<IdbOpenDbRequest>().ok()) else {
+                return;
+            };
+            if let Ok(db) = request.result().and_then(|db| db.dyn_into::<IdbDatabase>()) {
+                if !db.object_store_names().contains(INDEXED_DB_STORE) {
+                    let _ = db.create_object_store(INDEXED_DB_STORE);
+                }
+            }
+        });
+        request.set_onupgradeneeded(Some(upgrade.as_ref().unchecked_ref()));
+        let db: IdbDatabase = finished(&request).await?.dyn_into()?;
+        request.set_onupgradeneeded(None);
+
+        let store = db.transaction_with_str(INDEXED_DB_STORE)?.object_store(INDEXED_DB_STORE)?;
+        let keys: js_sys::Array = finished(&store.get_all_keys()?).await?.dyn_into()?;
+        let values: js_sys::Array = finished(&store.get_all()?).await?.dyn_into()?;
+        let cache = keys
+            .iter()
+            .zip(values.iter())
+            .filter_map(|(key, value)| Some((key.as_string()?, value.as_string()?)))
+            .collect();
+
+        Ok(IndexedDbBackend {
+            db,
+            cache: RefCell::new(cache),
+            watchers: Rc::default(),
+        })
+    }
+
+    /// Copies `entries` in, as long as nothing has been stored yet.
+    fn adopt(&self, entries: Vec<(String, String)>) {
+        if self.cache.borrow().is_empty() {
+            for (key, value) in entries {
+                let _ = self.set(&key, &value);
+            }
+        }
+    }
+
+    /// Puts `value` under `key`, or deletes it when there's no value, and
+    /// reports how it went once the transaction is over.
+    fn write(&self, key: &str, value: Option<&str>) -> Result<(), StorageError> {
+        let transaction = self
+            .db
+            .transaction_with_str_and_mode(INDEXED_DB_STORE, IdbTransactionMode::Readwrite)?;
+        let store = transaction.object_store(INDEXED_DB_STORE)?;
+        match value {
+            Some(value) => store.put_with_key(&JsValue::from_str(value), &JsValue::from_str(key))?,
+            None => store.delete(&JsValue::from_str(key))?,
+        };
+        let (key, watching) = (key.to_string(), self.watchers.clone());
+        wasm_bindgen_futures::spawn_local(async move {
+            let result = committed(&transaction).await;
+            watching.borrow_mut().retain(|watcher| watcher.unbounded_send((key.clone(), result.clone())).is_ok());
+        });
+        Ok(())
+    }
+}
+
+impl StorageBackend for IndexedDbBackend {
+    fn get(&self, key: &str) -> Option<String> {
+        self.cache.borrow().get(key).cloned()
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<(), StorageError> {
+        self.write(key, Some(value))?;
+        self.cache.borrow_mut().insert(key.to_string(), value.to_string());
+        Ok(())
+    }
+
+    fn remove(&self, key: &str) {
+        let _ = self.write(key, None);
+        self.cache.borrow_mut().remove(key);
+    }
+
+    fn write_results(&self) -> Option<UnboundedReceiver<WriteResult>> {
+        let (watcher, results) = mpsc::unbounded();
+        self.watchers.borrow_mut().push(watcher);
+        Some(results)
+    }
+}
+
+/// A `secret-satan-server`, which keeps everything in SQLite so lists aren't
+/// stuck in one browser.
+///
+/// Like IndexedDB it's asynchronous, so `open` reads every entry up front and
+/// writes are sent off in the background. They go one at a time, in order, so
+/// the server always ends up with the latest value, and how each went is sent
+/// to everyone watching `write_results`.
+#[derive(Clone)]
+pub struct ServerBackend {
+    cache: Arc<Mutex<HashMap<String, String>>>,
+    /// Writes waiting to be sent: a key, and its value or `None` to remove it.
+    queue: UnboundedSender<(String, Option<String>)>,
+    watchers: Rc<RefCell<Vec<UnboundedSender<WriteResult>>>>,
+}
+
+impl ServerBackend {
+    /// Connects to the API under `base`, e.g. `/api`. Fails when the page
+    /// wasn't served by the server, such as on the static site, and with
+    /// `Unauthorized` when this browser doesn't have the organizer's key.
+    pub async fn open(base: &str) -> Result<ServerBackend, StorageError> {
+        let response = fetch("GET", &format!("{base}/entries"), None).await?;
+        if response.status() == 401 {
+            return Err(StorageError::Unauthorized);
+        }
+        if !response.ok() {
+            return Err(StorageError::Unavailable);
+        }
+        let text = JsFuture::from(response.text()?).await?.as_string().unwrap_or_default();
+        let cache = serde_json::from_str(&text).map_err(|_| StorageError::Unavailable)?;
+
+        let (queue, mut writes) = mpsc::unbounded::<(String, Option<String>)>();
+        let watchers: Rc<RefCell<Vec<UnboundedSender<WriteResult>>>> = Rc::default();
+        let (base, watching) = (base.to_string(), watchers.clone());
+        wasm_bindgen_futures::spawn_local(async move {
+            while let Some((key, value)) = writes.next().await {
+                let url = format!("{base}/entries/{}", String::from(js_sys::encode_uri_component(&key)));
+                let result = match &value {
+                    Some(body) => fetch("PUT", &url, Some(body)).await,
+                    None => fetch("DELETE", &url, None).await,
+                };
+                let result = match result {
+                    Ok(response) if response.ok() => Ok(()),
+                    Ok(response) if response.status() == 401 => Err(StorageError::Unauthorized),
+                    Ok(response) => Err(StorageError::Other(format!("The server couldn't save it ({}).", response.status()))),
+                    Err(_) => Err(StorageError::Other("The server couldn't be reached.".to_string())),
+                };
+                watching.borrow_mut().retain(|watcher| watcher.unbounded_send((key.clone(), result.clone())).is_ok());
+            }
+        });
+
+        Ok(ServerBackend {
+            cache: Arc::new(Mutex::new(cache)),
+            queue,
+            watchers,
+        })
+    }
+
+    fn cache(&self) -> std::sync::MutexGuard<'_, HashMap<String, String>> {
+        self.cache.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl StorageBackend for ServerBackend {
+    fn get(&self, key: &str) -> Option<String> {
+        self.cache().get(key).cloned()
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<(), StorageError> {
+        self.queue
+            .unbounded_send((key.to_string(), Some(value.to_string())))
+            .map_err(|_| StorageError::Unavailable)?;
+        self.cache().insert(key.to_string(), value.to_string());
+        Ok(())
+    }
+
+    fn remove(&self, key: &str) {
+        let _ = self.queue.unbounded_send((key.to_string(), None));
+        self.cache().remove(key);
+    }
+
+    fn write_results(&self) -> Option<UnboundedReceiver<WriteResult>> {
+        let (watcher, results) = mpsc::unbounded();
+        self.watchers.borrow_mut().push(watcher);
+        Some(results)
+    }
+}
+
+/// Waits for an IndexedDB request to finish and returns its result.
+async fn finished(request: &IdbRequest) -> Result<JsValue, StorageError> {
+    let promise = js_sys::Promise::new(&mut |resolve, reject| {
+        request.set_onsuccess(Some(&resolve));
+        request.set_onerror(Some(&reject));
+    });
+    let outcome = JsFuture::from(promise).await;
+    request.set_onsuccess(None);
+    request.set_onerror(None);
+    outcome?;
+    Ok(request.result()?)
+}
+
+/// Waits for an IndexedDB transaction to commit, or for why it didn't.
+async fn committed(transaction: &IdbTransaction) -> Result<(), StorageError> {
+    let promise = js_sys::Promise::new(&mut |resolve, reject| {
+        transaction.set_oncomplete(Some(&resolve));
+        transaction.set_onerror(Some(&reject));
+        transaction.set_onabort(Some(&reject));
+    });
+    let outcome = JsFuture::from(promise).await;
+    transaction.set_oncomplete(None);
+    transaction.set_onerror(None);
+    transaction.set_onabort(None);
+    match outcome {
+        Ok(_) => Ok(()),
+        Err(_) => Err(match transaction.error() {
+            Some(error) => StorageError::from(JsValue::from(error)),
+            None => StorageError::Other("The browser didn't save it.".to_string()),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Participant, SecretSatan};
+    use serde_json::Value;
+
+    fn saved_list() -> SecretSatan {
+        let mut secret_satan = SecretSatan::new();
+        secret_satan.add_participant(Participant::new("Alice".to_string()));
+        secret_satan
+    }
+
+    #[test]
+    fn memory_backend_round_trips_values() {
+        let backend = MemoryBackend::default();
+        save(&backend, "satan", &saved_list()).unwrap();
+        assert_eq!(load::<SecretSatan>(&backend, "satan"), Ok(Some(saved_list())));
+
+        backend.remove("satan");
+        assert_eq!(load::<SecretSatan>(&backend, "satan"), Ok(None));
+    }
+
+    #[test]
+    fn saved_values_record_their_version() {
+        let backend = MemoryBackend::default();
+        save(&backend, "satan", &saved_list()).unwrap();
+        let raw: Value = serde_json::from_str(&backend.get("satan").unwrap()).unwrap();
+        assert_eq!(raw["version"], SecretSatan::version());
+    }
+
+    #[test]
+    fn unreadable_entries_keep_their_raw_data() {
+        let backend = MemoryBackend::default();
+        backend.set("satan", "not json").unwrap();
+        let unreadable = load::<SecretSatan>(&backend, "satan").unwrap_err();
+        assert_eq!(unreadable.key, "satan");
+        assert_eq!(unreadable.raw, "not json");
+        // Nothing was overwritten.
+        assert_eq!(backend.get("satan"), Some("not json".to_string()));
+    }
+
+    #[test]
+    fn failed_writes_are_reported() {
+        struct FullBackend;
+        impl StorageBackend for FullBackend {
+            fn get(&self, _key: &str) -> Option<String> {
+                None
+            }
+            fn set(&self, _key: &str, _value: &str) -> Result<(), StorageError> {
+                Err(StorageError::QuotaExceeded)
+            }
+            fn remove(&self, _key: &str) {}
+        }
+
+        assert_eq!(save(&FullBackend, "satan", &saved_list()), Err(StorageError::QuotaExceeded));
+    }
+
+    #[test]
+    fn the_backend_is_chosen_in_the_query_string() {
+        assert_eq!(backend_choice("?storage=indexeddb"), Some("indexeddb".to_string()));
+        assert_eq!(backend_choice("?lang=en&storage=local"), Some("local".to_string()));
+        assert_eq!(backend_choice("?storage=floppy"), None);
+        assert_eq!(backend_choice(""), None);
+    }
+
+    #[test]
+    fn data_from_a_newer_version_is_unreadable() {
+        let backend = MemoryBackend::default();
+        backend.set("satan", r#"{"version": 999, "data": {"participants": []}}"#).unwrap();
+        assert!(load::<SecretSatan>(&backend, "satan").is_err());
+    }
+}